@@ -1,4 +1,4 @@
-// File automatically generated by build.rs.
+// File automatically generated by wgsl_to_wgpu.
 // Changes made to this file will not be saved.
 #[repr(C)]
 #[derive(Debug, Copy, Clone, PartialEq, encase :: ShaderType)]
@@ -12,6 +12,14 @@ impl OverrideConstants {
         [].into_iter().filter_map(|a| a).collect()
     }
 }
+#[doc = r" The override values used by the pipeline after evaluating any defaults."]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ResolvedOverrideConstants {}
+impl OverrideConstants {
+    pub fn resolved(&self) -> ResolvedOverrideConstants {
+        ResolvedOverrideConstants {}
+    }
+}
 #[derive(Debug)]
 pub struct BindGroupLayout0 {
     device: std::sync::Arc<wgpu::Device>,
@@ -70,16 +78,36 @@ impl BindGroupLayout0 {
     }
 }
 #[derive(Clone, Debug, PartialEq, Eq, Hash)]
-pub enum FragmentEntry {}
-impl FragmentEntry {
-    pub fn entry_point_and_targets(&self) -> (&'static str, &[Option<wgpu::ColorTargetState>]) {
+struct PipelineLayoutKey {}
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum UnsupportedDeviceError {
+    MissingFeatures(wgpu::Features),
+    InsufficientLimit {
+        name: &'static str,
+        required: u64,
+        supported: u64,
+    },
+}
+impl std::fmt::Display for UnsupportedDeviceError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
-            _ => unreachable!(),
+            Self::MissingFeatures(features) => {
+                write!(f, "device does not support required features {features:?}")
+            }
+            Self::InsufficientLimit {
+                name,
+                required,
+                supported,
+            } => {
+                write!(
+                    f,
+                    "device limit {name} is {supported} but the shader requires {required}"
+                )
+            }
         }
     }
 }
-#[derive(Clone, Debug, PartialEq, Eq, Hash)]
-struct PipelineLayoutKey {}
+impl std::error::Error for UnsupportedDeviceError {}
 pub struct Shader {
     device: std::sync::Arc<wgpu::Device>,
     shader_module: std::sync::Arc<wgpu::ShaderModule>,
@@ -96,15 +124,61 @@ impl std::ops::Deref for Shader {
 #[bon::bon]
 impl Shader {
     pub const SOURCE : & 'static str = "struct Uniforms {\n    color_rgb: vec3<f32>,\n}\n\n@group(0) @binding(0) \nvar<storage, read_write> uniforms: Uniforms;\n\n@compute @workgroup_size(1, 1, 1) \nfn main(@builtin(global_invocation_id) global_id: vec3<u32>) {\n    if (global_id.x == 0u) {\n        uniforms.color_rgb = vec3(1f);\n        return;\n    } else {\n        return;\n    }\n}\n" ;
-    pub fn new(device: std::sync::Arc<wgpu::Device>) -> Self {
+    pub const REQUIRED_FEATURES: wgpu::Features = wgpu::Features::empty();
+    #[doc = r" The minimum limits for this shader starting from [wgpu::Limits::downlevel_webgl2_defaults]."]
+    pub fn required_limits() -> wgpu::Limits {
+        let limits = wgpu::Limits::downlevel_webgl2_defaults();
+        wgpu::Limits {
+            max_bind_groups: limits.max_bind_groups.max(1),
+            max_bindings_per_bind_group: limits.max_bindings_per_bind_group.max(1),
+            max_storage_buffers_per_shader_stage: limits
+                .max_storage_buffers_per_shader_stage
+                .max(1),
+            max_storage_buffer_binding_size: limits.max_storage_buffer_binding_size.max(16),
+            max_compute_invocations_per_workgroup: limits
+                .max_compute_invocations_per_workgroup
+                .max(1),
+            max_compute_workgroup_size_x: limits.max_compute_workgroup_size_x.max(1),
+            max_compute_workgroup_size_y: limits.max_compute_workgroup_size_y.max(1),
+            max_compute_workgroup_size_z: limits.max_compute_workgroup_size_z.max(1),
+            ..limits
+        }
+    }
+    #[doc = r" Check that `device` supports the features and limits required by this shader."]
+    pub fn check_device(device: &wgpu::Device) -> Result<(), UnsupportedDeviceError> {
+        let missing_features = Self::REQUIRED_FEATURES.difference(device.features());
+        if !missing_features.is_empty() {
+            return Err(UnsupportedDeviceError::MissingFeatures(missing_features));
+        }
+        let mut result = Ok(());
+        Self::required_limits().check_limits_with_fail_fn(
+            &device.limits(),
+            true,
+            |name, required, supported| {
+                result = Err(UnsupportedDeviceError::InsufficientLimit {
+                    name,
+                    required,
+                    supported,
+                });
+            },
+        );
+        result
+    }
+    pub fn new(device: std::sync::Arc<wgpu::Device>) -> Result<Self, UnsupportedDeviceError> {
+        Self::check_device(&device)?;
         let shader_module = device.create_shader_module(wgpu::ShaderModuleDescriptor {
             label: None,
             source: wgpu::ShaderSource::Wgsl(std::borrow::Cow::Borrowed(Self::SOURCE)),
         });
-        let shader_module = std::sync::Arc::new(shader_module);
+        Ok(Self::from_shader_module(device, shader_module))
+    }
+    fn from_shader_module(
+        device: std::sync::Arc<wgpu::Device>,
+        shader_module: wgpu::ShaderModule,
+    ) -> Self {
         Self {
             device,
-            shader_module,
+            shader_module: std::sync::Arc::new(shader_module),
             pipeline_layout_cache: Default::default(),
         }
     }
@@ -136,7 +210,7 @@ pub struct PipelineLayout {
     layout: wgpu::PipelineLayout,
     bind_group_layouts: (BindGroupLayout0,),
     main_pipelines: std::sync::Mutex<
-        std::collections::HashMap<PipelineKey_main, std::sync::Arc<wgpu::ComputePipeline>>,
+        std::collections::HashMap<PipelineKey_main, std::sync::Arc<ComputePipeline_main>>,
     >,
 }
 impl std::ops::Deref for PipelineLayout {
@@ -149,6 +223,119 @@ impl std::ops::Deref for PipelineLayout {
 struct PipelineKey_main {
     overrides: OverrideConstants,
 }
+#[derive(Debug)]
+pub struct ComputePipeline_main {
+    pipeline: wgpu::ComputePipeline,
+    max_workgroups_per_dimension: u32,
+}
+impl std::ops::Deref for ComputePipeline_main {
+    type Target = wgpu::ComputePipeline;
+    fn deref(&self) -> &Self::Target {
+        &self.pipeline
+    }
+}
+impl ComputePipeline_main {
+    #[doc = r" The `@workgroup_size` of the entry point."]
+    #[doc = r" Workgroup sizes can't use override constants, so this is the same for all pipelines."]
+    pub fn workgroup_size() -> [u32; 3] {
+        [1, 1, 1]
+    }
+    #[doc = r" The number of workgroups needed to cover `size` invocations in each dimension."]
+    #[doc = r" Returns an error if a dimension exceeds the device limit."]
+    pub fn workgroup_count(&self, size: [u32; 3]) -> Result<[u32; 3], WorkgroupCountError> {
+        let [x, y, z] = Self::workgroup_size();
+        let count = [
+            size[0].div_ceil(x),
+            size[1].div_ceil(y),
+            size[2].div_ceil(z),
+        ];
+        if count.iter().any(|c| *c > self.max_workgroups_per_dimension) {
+            return Err(WorkgroupCountError {
+                count,
+                max_workgroups_per_dimension: self.max_workgroups_per_dimension,
+            });
+        }
+        Ok(count)
+    }
+    fn set(&self, pass: &mut wgpu::ComputePass, bind_groups: (&BindGroup0,)) {
+        pass.set_pipeline(self);
+        bind_groups.0.set_compute(pass);
+    }
+    #[doc = r" Dispatch enough workgroups to cover `size` invocations in each dimension."]
+    #[doc = r" Nothing is recorded if the workgroup count exceeds the device limit."]
+    pub fn dispatch(
+        &self,
+        pass: &mut wgpu::ComputePass,
+        bind_groups: (&BindGroup0,),
+        size: [u32; 3],
+    ) -> Result<(), WorkgroupCountError> {
+        let count = self.workgroup_count(size)?;
+        self.set(pass, bind_groups);
+        pass.dispatch_workgroups(count[0], count[1], count[2]);
+        Ok(())
+    }
+    #[doc = r" Dispatch using the workgroup counts at `index` in `buffer`."]
+    pub fn dispatch_indirect(
+        &self,
+        pass: &mut wgpu::ComputePass,
+        bind_groups: (&BindGroup0,),
+        buffer: &DispatchIndirectBuffer,
+        index: u64,
+    ) {
+        self.set(pass, bind_groups);
+        pass.dispatch_workgroups_indirect(buffer, index * DispatchIndirectBuffer::ARGS_SIZE);
+    }
+}
+#[doc = r" A direct dispatch that needs more workgroups than the device supports in a dimension."]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct WorkgroupCountError {
+    pub count: [u32; 3],
+    pub max_workgroups_per_dimension: u32,
+}
+impl std::fmt::Display for WorkgroupCountError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "workgroup count {:?} exceeds the device limit of {} per dimension",
+            self.count, self.max_workgroups_per_dimension
+        )
+    }
+}
+impl std::error::Error for WorkgroupCountError {}
+#[doc = r" A buffer of [wgpu::util::DispatchIndirectArgs] for indirect compute dispatches."]
+#[derive(Debug)]
+pub struct DispatchIndirectBuffer(wgpu::Buffer);
+impl std::ops::Deref for DispatchIndirectBuffer {
+    type Target = wgpu::Buffer;
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+impl DispatchIndirectBuffer {
+    pub const ARGS_SIZE: u64 = std::mem::size_of::<wgpu::util::DispatchIndirectArgs>() as u64;
+    pub fn new(buffer: wgpu::Buffer) -> Self {
+        assert!(
+            buffer.usage().contains(wgpu::BufferUsages::INDIRECT),
+            "indirect dispatch buffers require wgpu::BufferUsages::INDIRECT"
+        );
+        Self(buffer)
+    }
+    pub fn init(
+        device: &wgpu::Device,
+        args: &[wgpu::util::DispatchIndirectArgs],
+        usage: wgpu::BufferUsages,
+    ) -> Self {
+        use wgpu::util::DeviceExt;
+        let contents: Vec<u8> = args.iter().flat_map(|a| a.as_bytes()).copied().collect();
+        Self(
+            device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                label: None,
+                contents: &contents,
+                usage: usage | wgpu::BufferUsages::INDIRECT,
+            }),
+        )
+    }
+}
 #[bon::bon]
 impl PipelineLayout {
     pub fn new(
@@ -172,7 +359,7 @@ impl PipelineLayout {
         &self,
         PipelineKey_main { overrides }: PipelineKey_main,
         cache: Option<&wgpu::PipelineCache>,
-    ) -> wgpu::ComputePipeline {
+    ) -> ComputePipeline_main {
         let device = &self.device;
         let module = &self.shader_module;
         let constants = overrides.constants();
@@ -180,21 +367,25 @@ impl PipelineLayout {
             constants: &constants,
             ..Default::default()
         };
-        device.create_compute_pipeline(&wgpu::ComputePipelineDescriptor {
+        let pipeline = device.create_compute_pipeline(&wgpu::ComputePipelineDescriptor {
             label: None,
             layout: Some(&self.layout),
             module,
             entry_point: "main",
             compilation_options,
             cache,
-        })
+        });
+        ComputePipeline_main {
+            pipeline,
+            max_workgroups_per_dimension: device.limits().max_compute_workgroups_per_dimension,
+        }
     }
     # [builder (finish_fn = get)]
     pub fn main_pipeline(
         &self,
         #[builder(default)] overrides: OverrideConstants,
         cache: Option<&wgpu::PipelineCache>,
-    ) -> std::sync::Arc<wgpu::ComputePipeline> {
+    ) -> std::sync::Arc<ComputePipeline_main> {
         let key = PipelineKey_main { overrides };
         self.main_pipelines
             .lock()
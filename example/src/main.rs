@@ -23,7 +23,7 @@ struct State {
     bind_group0: shader::BindGroup0,
     bind_group1: shader::BindGroup1,
     vertex_buffer: wgpu::Buffer,
-    compute_pipeline: Arc<wgpu::ComputePipeline>,
+    compute_pipeline: Arc<compute_shader::ComputePipeline_main>,
     compute_bind_group: compute_shader::BindGroup0,
 }
 
@@ -73,7 +73,7 @@ impl State {
         surface.configure(&device, &config);
 
         // Use the generated bindings to create the pipeline.
-        let module = shader::Shader::new(device.clone());
+        let module = shader::Shader::new(device.clone()).unwrap();
         let render_pipeline_layout = module
             .pipeline_layout()
             .color_texture_filterable(true)
@@ -93,7 +93,8 @@ impl State {
                 targets: [Some(surface_format.into())],
             })
             .overrides(overrides)
-            .get();
+            .get()
+            .unwrap();
 
         // Create a gradient texture.
         let texture = device.create_texture_with_data(
@@ -192,7 +193,7 @@ impl State {
             usage: wgpu::BufferUsages::VERTEX,
         });
 
-        let compute_shader = compute_shader::Shader::new(device.clone());
+        let compute_shader = compute_shader::Shader::new(device.clone()).unwrap();
         let compute_layout = compute_shader.pipeline_layout().get();
         let compute_pipeline = compute_layout.main_pipeline().get();
 
@@ -244,9 +245,10 @@ impl State {
             label: Some("Compute Pass"),
             timestamp_writes: None,
         });
-        compute_pass.set_pipeline(&self.compute_pipeline);
-        self.compute_bind_group.set_compute(&mut compute_pass);
-        compute_pass.dispatch_workgroups(1, 1, 1);
+        // Set the pipeline and bind groups and dispatch enough workgroups for the problem size.
+        self.compute_pipeline
+            .dispatch(&mut compute_pass, (&self.compute_bind_group,), [1, 1, 1])
+            .unwrap();
         drop(compute_pass);
 
         let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
@@ -1,4 +1,4 @@
-// File automatically generated by build.rs.
+// File automatically generated by wgsl_to_wgpu.
 // Changes made to this file will not be saved.
 #[repr(C)]
 #[derive(Debug, Copy, Clone, PartialEq, bytemuck :: Pod, bytemuck :: Zeroable)]
@@ -21,6 +21,9 @@ pub struct OverrideConstants {
     pub scale: Option<ordered_float::OrderedFloat<f32>>,
 }
 impl OverrideConstants {
+    pub const KEY_FORCE_BLACK: &'static str = "force_black";
+    pub const KEY_SCALE: &'static str = "scale";
+    pub const DEFAULT_SCALE: f32 = 1f32;
     pub fn constants(&self) -> std::collections::HashMap<String, f64> {
         [
             Some((
@@ -35,6 +38,19 @@ impl OverrideConstants {
         .collect()
     }
 }
+#[doc = r" The override values used by the pipeline after evaluating any defaults."]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ResolvedOverrideConstants {
+    pub force_black: bool,
+    pub scale: f32,
+}
+impl OverrideConstants {
+    pub fn resolved(&self) -> ResolvedOverrideConstants {
+        let force_black = self.force_black;
+        let scale = self.scale.map(|v| v.into_inner()).unwrap_or(1f32);
+        ResolvedOverrideConstants { force_black, scale }
+    }
+}
 #[derive(Debug)]
 pub struct BindGroupLayout0 {
     device: std::sync::Arc<wgpu::Device>,
@@ -190,24 +206,111 @@ impl VertexInput {
     }
 }
 #[derive(Clone, Debug, PartialEq, Eq, Hash)]
-pub enum FragmentEntry {
+pub enum FragmentEntry_vs_main {
+    #[doc = " The color targets for each fragment output:"]
+    #[doc = " - `0`: `@location(0)` requires a format with float values"]
     fs_main {
         targets: [Option<wgpu::ColorTargetState>; 1usize],
     },
 }
-impl FragmentEntry {
+impl FragmentEntry_vs_main {
     pub fn entry_point_and_targets(&self) -> (&'static str, &[Option<wgpu::ColorTargetState>]) {
         match self {
             Self::fs_main { targets } => ("fs_main", targets),
             _ => unreachable!(),
         }
     }
+    #[doc = r" Check that each target format can store the scalar type of its fragment output."]
+    #[doc = r""]
+    #[doc = r" Returns an error if a float output targets an integer format"]
+    #[doc = r" or an integer output targets a format without the same integer sample type."]
+    pub fn validate_targets(&self) -> Result<(), IncompatibleTargetError> {
+        let (entry_point, targets) = self.entry_point_and_targets();
+        let sample_types: &[Option<wgpu::TextureSampleType>] = match self {
+            Self::fs_main { .. } => &[Some(wgpu::TextureSampleType::Float { filterable: false })],
+            _ => unreachable!(),
+        };
+        for (i, (target, expected)) in targets.iter().zip(sample_types).enumerate() {
+            if let (Some(target), Some(expected)) = (target, expected) {
+                let compatible = matches!(
+                    (target.format.sample_type(None, None), expected),
+                    (
+                        Some(wgpu::TextureSampleType::Float { .. }),
+                        wgpu::TextureSampleType::Float { .. }
+                    ) | (
+                        Some(wgpu::TextureSampleType::Sint),
+                        wgpu::TextureSampleType::Sint
+                    ) | (
+                        Some(wgpu::TextureSampleType::Uint),
+                        wgpu::TextureSampleType::Uint
+                    )
+                );
+                if !compatible {
+                    return Err(IncompatibleTargetError {
+                        entry_point,
+                        target: i,
+                        format: target.format,
+                        expected: *expected,
+                    });
+                }
+            }
+        }
+        Ok(())
+    }
+}
+pub type FragmentEntry = FragmentEntry_vs_main;
+#[doc = r" A color target format that can't store the scalar type of its fragment output."]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct IncompatibleTargetError {
+    pub entry_point: &'static str,
+    pub target: usize,
+    pub format: wgpu::TextureFormat,
+    pub expected: wgpu::TextureSampleType,
 }
+impl std::fmt::Display for IncompatibleTargetError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "target {} for {} has format {:?} which is not compatible with {:?} outputs",
+            self.target, self.entry_point, self.format, self.expected
+        )
+    }
+}
+impl std::error::Error for IncompatibleTargetError {}
 #[derive(Clone, Debug, PartialEq, Eq, Hash)]
 struct PipelineLayoutKey {
     color_texture_filterable: bool,
     color_sampler_filtering: wgpu::SamplerBindingType,
 }
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum UnsupportedDeviceError {
+    MissingFeatures(wgpu::Features),
+    InsufficientLimit {
+        name: &'static str,
+        required: u64,
+        supported: u64,
+    },
+}
+impl std::fmt::Display for UnsupportedDeviceError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::MissingFeatures(features) => {
+                write!(f, "device does not support required features {features:?}")
+            }
+            Self::InsufficientLimit {
+                name,
+                required,
+                supported,
+            } => {
+                write!(
+                    f,
+                    "device limit {name} is {supported} but the shader requires {required}"
+                )
+            }
+        }
+    }
+}
+impl std::error::Error for UnsupportedDeviceError {}
 pub struct Shader {
     device: std::sync::Arc<wgpu::Device>,
     shader_module: std::sync::Arc<wgpu::ShaderModule>,
@@ -224,15 +327,61 @@ impl std::ops::Deref for Shader {
 #[bon::bon]
 impl Shader {
     pub const SOURCE : & 'static str = "struct VertexInput {\n    @location(0) position: vec3<f32>,\n}\n\nstruct VertexOutput {\n    @builtin(position) clip_position: vec4<f32>,\n    @location(0) tex_coords: vec2<f32>,\n}\n\nstruct Uniforms {\n    color_rgb: vec3<f32>,\n}\n\nstruct PushConstants {\n    color_matrix: mat4x4<f32>,\n}\n\noverride force_black: bool;\noverride scale: f32 = 1f;\n\n@group(0) @binding(0) \nvar color_texture: texture_2d<f32>;\n@group(0) @binding(1) \nvar color_sampler: sampler;\n@group(1) @binding(0) \nvar<uniform> uniforms: Uniforms;\nvar<push_constant> constants: PushConstants;\n\n@vertex \nfn vs_main(in: VertexInput) -> VertexOutput {\n    var out: VertexOutput;\n\n    out.clip_position = vec4<f32>(in.position.xyz, 1f);\n    out.tex_coords = ((in.position.xy * 0.5f) + vec2(0.5f));\n    let _e15: VertexOutput = out;\n    return _e15;\n}\n\n@fragment \nfn fs_main(in_1: VertexOutput) -> @location(0) vec4<f32> {\n    let _e4: vec4<f32> = textureSample(color_texture, color_sampler, in_1.tex_coords);\n    let color: vec3<f32> = _e4.xyz;\n    if force_black {\n        return vec4(0f);\n    } else {\n        let _e11: mat4x4<f32> = constants.color_matrix;\n        let _e14: vec3<f32> = uniforms.color_rgb;\n        return (_e11 * vec4<f32>(((color * _e14.xyz) * scale), 1f));\n    }\n}\n" ;
-    pub fn new(device: std::sync::Arc<wgpu::Device>) -> Self {
+    pub const REQUIRED_FEATURES: wgpu::Features = wgpu::Features::PUSH_CONSTANTS;
+    #[doc = r" The minimum limits for this shader starting from [wgpu::Limits::downlevel_webgl2_defaults]."]
+    pub fn required_limits() -> wgpu::Limits {
+        let limits = wgpu::Limits::downlevel_webgl2_defaults();
+        wgpu::Limits {
+            max_push_constant_size: limits.max_push_constant_size.max(64),
+            max_bind_groups: limits.max_bind_groups.max(2),
+            max_bindings_per_bind_group: limits.max_bindings_per_bind_group.max(2),
+            max_uniform_buffers_per_shader_stage: limits
+                .max_uniform_buffers_per_shader_stage
+                .max(1),
+            max_sampled_textures_per_shader_stage: limits
+                .max_sampled_textures_per_shader_stage
+                .max(1),
+            max_samplers_per_shader_stage: limits.max_samplers_per_shader_stage.max(1),
+            max_uniform_buffer_binding_size: limits.max_uniform_buffer_binding_size.max(16),
+            max_color_attachments: limits.max_color_attachments.max(1),
+            ..limits
+        }
+    }
+    #[doc = r" Check that `device` supports the features and limits required by this shader."]
+    pub fn check_device(device: &wgpu::Device) -> Result<(), UnsupportedDeviceError> {
+        let missing_features = Self::REQUIRED_FEATURES.difference(device.features());
+        if !missing_features.is_empty() {
+            return Err(UnsupportedDeviceError::MissingFeatures(missing_features));
+        }
+        let mut result = Ok(());
+        Self::required_limits().check_limits_with_fail_fn(
+            &device.limits(),
+            true,
+            |name, required, supported| {
+                result = Err(UnsupportedDeviceError::InsufficientLimit {
+                    name,
+                    required,
+                    supported,
+                });
+            },
+        );
+        result
+    }
+    pub fn new(device: std::sync::Arc<wgpu::Device>) -> Result<Self, UnsupportedDeviceError> {
+        Self::check_device(&device)?;
         let shader_module = device.create_shader_module(wgpu::ShaderModuleDescriptor {
             label: None,
             source: wgpu::ShaderSource::Wgsl(std::borrow::Cow::Borrowed(Self::SOURCE)),
         });
-        let shader_module = std::sync::Arc::new(shader_module);
+        Ok(Self::from_shader_module(device, shader_module))
+    }
+    fn from_shader_module(
+        device: std::sync::Arc<wgpu::Device>,
+        shader_module: wgpu::ShaderModule,
+    ) -> Self {
         Self {
             device,
-            shader_module,
+            shader_module: std::sync::Arc::new(shader_module),
             pipeline_layout_cache: Default::default(),
         }
     }
@@ -304,7 +453,7 @@ struct PipelineKey_vs_main {
     primitive: wgpu::PrimitiveState,
     depth_stencil: Option<wgpu::DepthStencilState>,
     multisample: wgpu::MultisampleState,
-    fragment: FragmentEntry,
+    fragment: Option<FragmentEntry_vs_main>,
     multiview: Option<std::num::NonZero<u32>>,
 }
 #[bon::bon]
@@ -346,7 +495,15 @@ impl PipelineLayout {
             constants: &constants,
             ..Default::default()
         };
-        let (fragment_entry, targets) = fragment.entry_point_and_targets();
+        let fragment = fragment.as_ref().map(|fragment| {
+            let (entry_point, targets) = fragment.entry_point_and_targets();
+            wgpu::FragmentState {
+                module,
+                entry_point,
+                compilation_options: compilation_options.clone(),
+                targets,
+            }
+        });
         device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
             label: None,
             layout: Some(&self.layout),
@@ -359,12 +516,7 @@ impl PipelineLayout {
             primitive,
             depth_stencil,
             multisample,
-            fragment: Some(wgpu::FragmentState {
-                module,
-                entry_point: fragment_entry,
-                compilation_options,
-                targets,
-            }),
+            fragment,
             multiview,
             cache,
         })
@@ -377,10 +529,13 @@ impl PipelineLayout {
         #[builder(default)] primitive: wgpu::PrimitiveState,
         depth_stencil: Option<wgpu::DepthStencilState>,
         #[builder(default)] multisample: wgpu::MultisampleState,
-        fragment: FragmentEntry,
+        fragment: Option<FragmentEntry_vs_main>,
         multiview: Option<std::num::NonZero<u32>>,
         cache: Option<&wgpu::PipelineCache>,
-    ) -> std::sync::Arc<wgpu::RenderPipeline> {
+    ) -> Result<std::sync::Arc<wgpu::RenderPipeline>, IncompatibleTargetError> {
+        if let Some(fragment) = &fragment {
+            fragment.validate_targets()?;
+        }
         let key = PipelineKey_vs_main {
             in_step_mode,
             overrides,
@@ -390,13 +545,15 @@ impl PipelineLayout {
             fragment,
             multiview,
         };
-        self.vs_main_pipelines
+        let pipeline = self
+            .vs_main_pipelines
             .lock()
             .unwrap()
             .entry(key)
             .or_insert_with_key(|key| {
                 std::sync::Arc::new(self.pipeline_vs_main_from_key(key.clone(), cache))
             })
-            .clone()
+            .clone();
+        Ok(pipeline)
     }
 }
@@ -11,7 +11,7 @@ pub struct GroupData<'a> {
 }
 
 pub struct GroupBinding<'a> {
-    pub name: Option<String>,
+    pub name: String,
     pub binding_index: u32,
     pub binding_type: &'a naga::Type,
     pub address_space: naga::AddressSpace,
@@ -19,23 +19,22 @@ pub struct GroupBinding<'a> {
 
 pub struct BindGroup {
     pub layout_type: Ident,
+    pub group_type: Ident,
     pub new: syn::Path,
     pub new_args: Vec<syn::BareFnArg>,
+    pub dynamic_offsets: bool,
 }
 
 // TODO: Take an iterator instead?
 pub fn bind_groups_module(
     bind_group_data: &BTreeMap<u32, GroupData>,
     shader_stages: wgpu::ShaderStages,
+    dynamic_bindings: &[String],
 ) -> (TokenStream, Vec<BindGroup>) {
     let (bind_group_layouts, bind_groups): (Vec<_>, Vec<_>) = bind_group_data
         .iter()
         .map(|(group_no, group)| {
-            bind_group_layout(
-                *group_no,
-                group,
-                shader_stages,
-            )
+            bind_group_layout(*group_no, group, shader_stages, dynamic_bindings)
         })
         .unzip();
 
@@ -51,12 +50,15 @@ pub fn bind_groups_module(
 fn bind_group_layout_new(
     group: &GroupData,
     shader_stages: wgpu::ShaderStages,
+    dynamic_bindings: &[String],
 ) -> (TokenStream, Vec<syn::BareFnArg>) {
     let (entries, args): (Vec<_>, Vec<_>) = group
         .bindings
         .iter()
         .map(|binding| {
-            bind_group_layout_entry(binding.name.as_ref().unwrap(), binding, shader_stages)
+            let name = &binding.name;
+            let has_dynamic_offset = dynamic_bindings.contains(name);
+            bind_group_layout_entry(name, binding, shader_stages, has_dynamic_offset)
         })
         .unzip();
     let args: Vec<_> = args.into_iter().flatten().collect();
@@ -89,8 +91,8 @@ fn bind_group_layout_create_bind_group(group_name: &Ident, group: &GroupData) ->
         .iter()
         .map(|binding| {
             let binding_index = Literal::usize_unsuffixed(binding.binding_index as usize);
-            let binding_name = binding.name.as_ref().unwrap();
-            let name = Ident::new(binding.name.as_ref().unwrap(), Span::call_site());
+            let binding_name = &binding.name;
+            let name = Ident::new(binding_name, Span::call_site());
             let (arg, resource) = match binding.binding_type.inner {
                 naga::TypeInner::Struct { .. }
                 | naga::TypeInner::Array { .. }
@@ -153,10 +155,50 @@ fn bind_group_layout(
     group_no: u32,
     group: &GroupData,
     shader_stages: wgpu::ShaderStages,
+    dynamic_bindings: &[String],
 ) -> (TokenStream, BindGroup) {
     let layout_name = Ident::new(&format!("BindGroupLayout{group_no}"), Span::call_site());
     let group_name = Ident::new(&format!("BindGroup{group_no}"), Span::call_site());
-    let (new_def, new_args) = bind_group_layout_new(group, shader_stages);
+    let (new_def, new_args) = bind_group_layout_new(group, shader_stages, dynamic_bindings);
+
+    let dynamic_names: Vec<_> = group
+        .bindings
+        .iter()
+        .map(|b| &b.name)
+        .filter(|name| dynamic_bindings.contains(name))
+        .collect();
+    let dynamic_offsets = !dynamic_names.is_empty();
+    let set_methods = if dynamic_offsets {
+        let doc = format!(
+            " Set the bind group with the dynamic offsets for {}.",
+            dynamic_names
+                .iter()
+                .map(|n| format!("`{n}`"))
+                .collect::<Vec<_>>()
+                .join(", ")
+        );
+        quote! {
+            #[doc = #doc]
+            pub fn set(&self, pass: &mut wgpu::RenderPass, offsets: &[u32]) {
+                pass.set_bind_group(#group_no, self, offsets);
+            }
+
+            #[doc = #doc]
+            pub fn set_compute(&self, pass: &mut wgpu::ComputePass, offsets: &[u32]) {
+                pass.set_bind_group(#group_no, self, offsets);
+            }
+        }
+    } else {
+        quote! {
+            pub fn set(&self, pass: &mut wgpu::RenderPass) {
+                pass.set_bind_group(#group_no, self, &[]);
+            }
+
+            pub fn set_compute(&self, pass: &mut wgpu::ComputePass) {
+                pass.set_bind_group(#group_no, self, &[]);
+            }
+        }
+    };
 
     let create_bind_group = bind_group_layout_create_bind_group(&group_name, group);
     let new = syn::parse2(quote!(#layout_name::new)).unwrap();
@@ -186,13 +228,7 @@ fn bind_group_layout(
             }
             
             impl #group_name {
-                pub fn set(&self, pass: &mut wgpu::RenderPass) {
-                    pass.set_bind_group(#group_no, self, &[]);
-                }
-
-                pub fn set_compute(&self, pass: &mut wgpu::ComputePass) {
-                    pass.set_bind_group(#group_no, self, &[]);
-                }
+                #set_methods
             }
 
             #[bon::bon]
@@ -203,8 +239,10 @@ fn bind_group_layout(
         },
         BindGroup {
             layout_type: layout_name,
+            group_type: group_name,
             new,
             new_args,
+            dynamic_offsets,
         },
     )
 }
@@ -213,6 +251,7 @@ fn bind_group_layout_entry(
     name: &str,
     binding: &GroupBinding,
     shader_stages: wgpu::ShaderStages,
+    has_dynamic_offset: bool,
 ) -> (TokenStream, Vec<syn::BareFnArg>) {
     // TODO: Assume storage is only used for compute?
     // TODO: Support just vertex or fragment?
@@ -232,7 +271,7 @@ fn bind_group_layout_entry(
         | naga::TypeInner::Matrix { .. } => {
             quote!(wgpu::BindingType::Buffer {
                 ty: #buffer_binding_type,
-                has_dynamic_offset: false,
+                has_dynamic_offset: #has_dynamic_offset,
                 min_binding_size: None,
             })
         }
@@ -337,6 +376,26 @@ fn storage_access(access: naga::StorageAccess) -> TokenStream {
     }
 }
 
+/// Check that each binding in [crate::WriteOptions::dynamic_bindings] is a buffer binding.
+pub fn check_dynamic_bindings(
+    bind_group_data: &BTreeMap<u32, GroupData>,
+    dynamic_bindings: &[String],
+) -> Result<(), CreateModuleError> {
+    for name in dynamic_bindings {
+        let is_buffer = bind_group_data.values().flat_map(|g| &g.bindings).any(|b| {
+            &b.name == name
+                && matches!(
+                    b.address_space,
+                    naga::AddressSpace::Uniform | naga::AddressSpace::Storage { .. }
+                )
+        });
+        if !is_buffer {
+            return Err(CreateModuleError::InvalidDynamicBinding { name: name.clone() });
+        }
+    }
+    Ok(())
+}
+
 pub fn get_bind_group_data(
     module: &naga::Module,
 ) -> Result<BTreeMap<u32, GroupData>, CreateModuleError> {
@@ -352,8 +411,12 @@ pub fn get_bind_group_data(
             });
             let binding_type = &module.types[module.global_variables[global_handle.0].ty];
 
+            // Globals from GLSL like anonymous buffer blocks may not have a name.
             let group_binding = GroupBinding {
-                name: global.name.clone(),
+                name: global
+                    .name
+                    .clone()
+                    .unwrap_or_else(|| format!("binding_{}_{}", binding.group, binding.binding)),
                 binding_index: binding.binding,
                 binding_type,
                 address_space: global.space,
@@ -436,10 +499,88 @@ mod tests {
         ));
     }
 
+    #[test]
+    fn bind_groups_module_dynamic_offsets() {
+        let source = indoc! {r#"
+            @group(0) @binding(0) var<uniform> camera: mat4x4<f32>;
+            @group(0) @binding(1) var<storage, read> transforms: array<mat4x4<f32>>;
+            @group(0) @binding(2) var color_sampler: sampler;
+
+            @vertex
+            fn main() -> @builtin(position) vec4<f32> {
+                return camera * transforms[0][3];
+            }
+        "#};
+
+        let module = naga::front::wgsl::parse_str(source).unwrap();
+        let bind_group_data = get_bind_group_data(&module).unwrap();
+        let (actual, bind_groups) = bind_groups_module(
+            &bind_group_data,
+            wgpu::ShaderStages::VERTEX,
+            &["transforms".to_owned()],
+        );
+        assert!(bind_groups[0].dynamic_offsets);
+
+        let file: syn::File = syn::parse2(actual).unwrap();
+        let group_impl = file
+            .items
+            .iter()
+            .find(|item| match item {
+                syn::Item::Impl(i) => {
+                    i.trait_.is_none() && i.self_ty.to_token_stream().to_string() == "BindGroup0"
+                }
+                _ => false,
+            })
+            .unwrap();
+        assert_tokens_eq!(
+            quote! {
+                impl BindGroup0 {
+                    #[doc = " Set the bind group with the dynamic offsets for `transforms`."]
+                    pub fn set(&self, pass: &mut wgpu::RenderPass, offsets: &[u32]) {
+                        pass.set_bind_group(0u32, self, offsets);
+                    }
+
+                    #[doc = " Set the bind group with the dynamic offsets for `transforms`."]
+                    pub fn set_compute(&self, pass: &mut wgpu::ComputePass, offsets: &[u32]) {
+                        pass.set_bind_group(0u32, self, offsets);
+                    }
+                }
+            },
+            quote!(#group_impl)
+        );
+
+        let layout_new = file.to_token_stream().to_string();
+        assert_eq!(1, layout_new.matches("has_dynamic_offset : true").count());
+        assert_eq!(1, layout_new.matches("has_dynamic_offset : false").count());
+    }
+
+    #[test]
+    fn check_dynamic_bindings_invalid() {
+        let source = indoc! {r#"
+            @group(0) @binding(0) var<uniform> camera: mat4x4<f32>;
+            @group(0) @binding(1) var color_sampler: sampler;
+
+            @fragment
+            fn main() {}
+        "#};
+
+        let module = naga::front::wgsl::parse_str(source).unwrap();
+        let bind_group_data = get_bind_group_data(&module).unwrap();
+        assert!(check_dynamic_bindings(&bind_group_data, &["camera".to_owned()]).is_ok());
+        assert!(matches!(
+            check_dynamic_bindings(&bind_group_data, &["color_sampler".to_owned()]),
+            Err(CreateModuleError::InvalidDynamicBinding { name }) if name == "color_sampler"
+        ));
+        assert!(matches!(
+            check_dynamic_bindings(&bind_group_data, &["missing".to_owned()]),
+            Err(CreateModuleError::InvalidDynamicBinding { name }) if name == "missing"
+        ));
+    }
+
     fn test_bind_groups(wgsl: &str, rust: &str, stages: wgpu::ShaderStages) {
         let module = naga::front::wgsl::parse_str(wgsl).unwrap();
         let bind_group_data = get_bind_group_data(&module).unwrap();
-        let (actual, _) = bind_groups_module(&bind_group_data, stages);
+        let (actual, _) = bind_groups_module(&bind_group_data, stages, &[]);
 
         assert_tokens_eq!(rust.parse().unwrap(), actual);
     }
@@ -0,0 +1,1004 @@
+//! Generate bindings for multiple shaders from a `wgsl_to_wgpu.toml` configuration file.
+//!
+//! The same configuration file can be used from a build script with `from_config`
+//! or with the `wgsl_to_wgpu` command line tool using `--config`.
+//!
+//! ```toml
+//! # Options for all shader sets.
+//! [options]
+//! derive_bytemuck_vertex = true
+//! derive_encase_host_shareable = true
+//! matrix_vector_types = "glam"
+//!
+//! # A single input file writes to the output file.
+//! [[shaders]]
+//! inputs = ["src/shader.wgsl"]
+//! output = "src/shader.rs"
+//!
+//! # Multiple inputs or directories write to the output directory.
+//! [[shaders]]
+//! inputs = ["shaders/materials"]
+//! output = "src/materials"
+//!
+//! # Options for a shader set replace the options with the same name.
+//! [shaders.options]
+//! include_dirs = ["shaders/common"]
+//! dynamic_bindings = ["transforms"]
+//! defines = { SKINNING = 1 }
+//! type_overrides = { Camera = "crate::camera::Camera" }
+//! ```
+//!
+//! The supported options are the fields of [WriteOptions] except for the validation options,
+//! `include_dirs` for the directories to search for `#include` files,
+//! and `reflection` for also writing a JSON [ShaderReflection](crate::ShaderReflection) next to each output.
+//! Paths are relative to the directory containing the configuration file.
+//! Loading configuration files requires the `config` feature,
+//! and writing the reflection JSON requires the `reflect` feature.
+use std::{
+    collections::BTreeMap,
+    path::{Component, Path, PathBuf},
+};
+
+use proc_macro2::{Span, TokenStream};
+use quote::quote;
+#[cfg(feature = "config")]
+use serde::Deserialize;
+use syn::Ident;
+use thiserror::Error;
+
+use crate::{
+    create_shader_module_composed, ComposedSource, CreateModuleError, ShaderSourceMode,
+    WriteOptions,
+};
+#[cfg(feature = "config")]
+use crate::{MatrixVectorTypes, Translations};
+
+/// Errors while loading a configuration file or generating the bindings it describes.
+#[derive(Debug, PartialEq, Eq, Error)]
+pub enum BuildError {
+    /// Each configuration file, input file, and input directory must be readable.
+    #[error("failed to read {}: {message}", path.display())]
+    ReadError { path: PathBuf, message: String },
+
+    /// Each output file and its parent directories must be writable.
+    #[error("failed to write {}: {message}", path.display())]
+    WriteError { path: PathBuf, message: String },
+
+    /// The configuration file must be valid TOML with only the supported keys and values.
+    #[error("invalid config {}: {message}", path.display())]
+    ConfigError { path: PathBuf, message: String },
+
+    /// Each shader must compose and generate bindings successfully.
+    #[error("{}: {source}", path.display())]
+    ShaderError {
+        path: PathBuf,
+        source: CreateModuleError,
+    },
+
+    /// The output directory must be set with [Builder::out_dir] outside of build scripts.
+    #[error("OUT_DIR is not set")]
+    MissingOutDir,
+
+    /// Shader file and directory names must be valid Rust module names
+    /// after replacing invalid characters with `_`.
+    #[error("{} is not a valid module name", path.display())]
+    InvalidModuleName { path: PathBuf },
+
+    /// Shaders in the same set must have different output paths.
+    /// Files with the same name in different input directories can be placed in subdirectories.
+    #[error("{} and {} both write to {}", first.display(), second.display(), output.display())]
+    DuplicateOutput {
+        first: PathBuf,
+        second: PathBuf,
+        output: PathBuf,
+    },
+}
+
+/// Generate and write the bindings for each shader described by the configuration file at `path`.
+///
+/// This is intended for build scripts and prints `cargo:rerun-if-changed`
+/// for the configuration file, input directories, and every included file.
+/// Outputs are only written if their contents change.
+///
+/// ```no_run
+/// // build.rs
+/// wgsl_to_wgpu::build::from_config("wgsl_to_wgpu.toml").unwrap();
+/// ```
+///
+/// Requires the `config` feature.
+#[cfg(feature = "config")]
+pub fn from_config(path: impl AsRef<Path>) -> Result<(), BuildError> {
+    let path = path.as_ref();
+    println!("cargo:rerun-if-changed={}", path.display());
+
+    let config = Config::from_file(path)?;
+    for set in &config.shaders {
+        write_outputs(set, &set.outputs()?)?;
+    }
+    Ok(())
+}
+
+/// Generate bindings for WGSL shaders in a build script and write them to `OUT_DIR`.
+///
+/// Each shader is written to a file with the same relative path as the shader.
+/// The generated `mod.rs` defines a module for each shader and directory
+/// with the same names as the WGSL files and directories.
+/// This prints `cargo:rerun-if-changed` for every input and included file,
+/// and outputs are only written if their contents change.
+///
+/// ```no_run
+/// // build.rs
+/// use wgsl_to_wgpu::{Builder, MatrixVectorTypes, WriteOptions};
+///
+/// Builder::new()
+///     .shader_dir("src/shaders")
+///     .options(WriteOptions {
+///         derive_bytemuck_vertex: true,
+///         matrix_vector_types: MatrixVectorTypes::Glam,
+///         ..Default::default()
+///     })
+///     .build()
+///     .unwrap();
+/// ```
+///
+/// ```ignore
+/// // src/shaders.rs
+/// include!(concat!(env!("OUT_DIR"), "/mod.rs"));
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct Builder {
+    inputs: Vec<PathBuf>,
+    search_paths: Vec<PathBuf>,
+    options: WriteOptions,
+    out_dir: Option<PathBuf>,
+}
+
+impl Builder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add every `.wgsl` file in `dir` and its subdirectories.
+    pub fn shader_dir(mut self, dir: impl Into<PathBuf>) -> Self {
+        self.inputs.push(dir.into());
+        self
+    }
+
+    /// Add a single WGSL file.
+    pub fn shader(mut self, path: impl Into<PathBuf>) -> Self {
+        self.inputs.push(path.into());
+        self
+    }
+
+    /// Add a directory to search for `#include` files.
+    pub fn include_dir(mut self, dir: impl Into<PathBuf>) -> Self {
+        self.search_paths.push(dir.into());
+        self
+    }
+
+    /// The options for generating all shaders.
+    pub fn options(mut self, options: WriteOptions) -> Self {
+        self.options = options;
+        self
+    }
+
+    /// Write to `dir` instead of the `OUT_DIR` set by cargo for build scripts.
+    pub fn out_dir(mut self, dir: impl Into<PathBuf>) -> Self {
+        self.out_dir = Some(dir.into());
+        self
+    }
+
+    /// Generate the bindings for each shader and the `mod.rs` file.
+    pub fn build(&self) -> Result<(), BuildError> {
+        let out_dir = match &self.out_dir {
+            Some(dir) => dir.clone(),
+            None => std::env::var_os("OUT_DIR")
+                .map(PathBuf::from)
+                .ok_or(BuildError::MissingOutDir)?,
+        };
+
+        let set = ShaderSet {
+            inputs: self.inputs.clone(),
+            output: out_dir.clone(),
+            search_paths: self.search_paths.clone(),
+            options: self.options.clone(),
+            include_str: false,
+            reflection: false,
+        };
+        let outputs = dir_outputs(&set.inputs, &out_dir)?;
+        write_outputs(&set, &outputs)?;
+
+        let module = module_tree(&outputs, &out_dir)?;
+        let text = format!(
+            "// File automatically generated by wgsl_to_wgpu.\n{}",
+            crate::pretty_print(module)
+        );
+        write_if_changed(&out_dir.join("mod.rs"), &text)
+    }
+}
+
+fn write_outputs(set: &ShaderSet, outputs: &[(PathBuf, PathBuf)]) -> Result<(), BuildError> {
+    // Directories are checked for added or removed files.
+    for input in set.inputs.iter().filter(|i| i.is_dir()) {
+        println!("cargo:rerun-if-changed={}", input.display());
+    }
+
+    for (input, output) in outputs {
+        let composed = set.compose(input)?;
+        for file in &composed.files {
+            println!("cargo:rerun-if-changed={}", file.display());
+        }
+
+        create_output_dir(output)?;
+        let text = set.generate(&composed, output)?;
+        write_if_changed(output, &text)?;
+
+        #[cfg(feature = "reflect")]
+        if set.reflection {
+            let json = set.reflect(&composed)?;
+            write_if_changed(&output.with_extension("json"), &json)?;
+        }
+    }
+    Ok(())
+}
+
+// Avoid updating the modified time to avoid unnecessary recompilation.
+fn write_if_changed(path: &Path, text: &str) -> Result<(), BuildError> {
+    if std::fs::read_to_string(path).ok().as_deref() != Some(text) {
+        std::fs::write(path, text).map_err(|e| BuildError::WriteError {
+            path: path.to_owned(),
+            message: e.to_string(),
+        })?;
+    }
+    Ok(())
+}
+
+/// Nested modules that include each output file with a path relative to `out_dir`.
+fn module_tree(outputs: &[(PathBuf, PathBuf)], out_dir: &Path) -> Result<TokenStream, BuildError> {
+    #[derive(Default)]
+    struct Module {
+        file: Option<String>,
+        children: BTreeMap<String, Module>,
+    }
+
+    fn module_tokens(module: &Module) -> TokenStream {
+        let include = module.file.as_ref().map(|file| quote!(include!(#file);));
+        let children = module.children.iter().map(|(name, child)| {
+            let name = Ident::new(name, Span::call_site());
+            let child = module_tokens(child);
+            quote! {
+                pub mod #name {
+                    #child
+                }
+            }
+        });
+        quote!(#include #(#children)*)
+    }
+
+    let mut root = Module::default();
+    for (_, output) in outputs {
+        let relative = output.strip_prefix(out_dir).unwrap().with_extension("");
+
+        let mut module = &mut root;
+        for component in relative.components() {
+            let name = module_name(component.as_os_str()).ok_or_else(|| {
+                BuildError::InvalidModuleName {
+                    path: output.clone(),
+                }
+            })?;
+            module = module.children.entry(name).or_default();
+        }
+
+        // Use forward slashes so the generated code is the same on all platforms.
+        let components: Vec<_> = relative
+            .with_extension("rs")
+            .components()
+            .map(|c| c.as_os_str().to_string_lossy().into_owned())
+            .collect();
+        module.file = Some(components.join("/"));
+    }
+
+    Ok(module_tokens(&root))
+}
+
+fn module_name(name: &std::ffi::OsStr) -> Option<String> {
+    let name: String = name
+        .to_string_lossy()
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+        .collect();
+    // Keywords and names starting with digits are not valid identifiers.
+    syn::parse_str::<Ident>(&name).ok().map(|_| name)
+}
+
+/// The shader sets described by a `wgsl_to_wgpu.toml` configuration file.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Config {
+    pub shaders: Vec<ShaderSet>,
+}
+
+/// WGSL inputs that share the same output location and options.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ShaderSet {
+    /// WGSL files or directories to search recursively for `.wgsl` files.
+    pub inputs: Vec<PathBuf>,
+    /// The output `.rs` file for a single input file or the output directory otherwise.
+    pub output: PathBuf,
+    /// Additional directories to search for `#include` files.
+    pub search_paths: Vec<PathBuf>,
+    pub options: WriteOptions,
+    /// Use [ShaderSourceMode::IncludeStr] with the path to each input relative to its output.
+    pub include_str: bool,
+    /// Also write the [ShaderReflection](crate::ShaderReflection) of each shader as JSON
+    /// to the output path with the `.json` extension.
+    /// Requires the `reflect` feature.
+    pub reflection: bool,
+}
+
+/// Requires the `config` feature.
+#[cfg(feature = "config")]
+impl Config {
+    /// Load the configuration file at `path`.
+    pub fn from_file(path: impl AsRef<Path>) -> Result<Self, BuildError> {
+        let path = path.as_ref();
+        let text = std::fs::read_to_string(path).map_err(|e| BuildError::ReadError {
+            path: path.to_owned(),
+            message: e.to_string(),
+        })?;
+        let base_dir = path.parent().unwrap_or(Path::new(""));
+        Self::from_toml(&text, base_dir).map_err(|message| BuildError::ConfigError {
+            path: path.to_owned(),
+            message,
+        })
+    }
+
+    /// Parse the configuration from `text` with paths relative to `base_dir`.
+    pub fn from_toml(text: &str, base_dir: &Path) -> Result<Self, String> {
+        let file: ConfigFile = toml::from_str(text).map_err(|e| e.message().to_owned())?;
+
+        let shaders = file
+            .shaders
+            .into_iter()
+            .map(|set| {
+                let options = file.options.clone().merge(set.options);
+                if cfg!(not(feature = "reflect")) && options.reflection == Some(true) {
+                    return Err("reflection requires the reflect feature".to_owned());
+                }
+                let search_paths = options
+                    .include_dirs
+                    .iter()
+                    .flatten()
+                    .map(|p| base_dir.join(p))
+                    .collect();
+                Ok(ShaderSet {
+                    inputs: set.inputs.iter().map(|p| base_dir.join(p)).collect(),
+                    output: base_dir.join(set.output),
+                    search_paths,
+                    options: options.write_options(),
+                    include_str: options.source_mode == Some(SourceModeConfig::IncludeStr),
+                    reflection: options.reflection.unwrap_or(false),
+                })
+            })
+            .collect::<Result<_, _>>()?;
+        Ok(Self { shaders })
+    }
+}
+
+impl ShaderSet {
+    /// The input WGSL file and output Rust file for each shader in the set.
+    pub fn outputs(&self) -> Result<Vec<(PathBuf, PathBuf)>, BuildError> {
+        // A single file writes directly to the output path.
+        if let [input] = self.inputs.as_slice() {
+            if input.is_file() {
+                return Ok(vec![(input.clone(), self.output.clone())]);
+            }
+        }
+
+        dir_outputs(&self.inputs, &self.output)
+    }
+
+    /// Resolve the `#include` directives for `input` using the search paths and defines of the set.
+    pub fn compose(&self, input: &Path) -> Result<ComposedSource, BuildError> {
+        ComposedSource::new(input, &self.search_paths, &self.options.defines).map_err(|source| {
+            BuildError::ShaderError {
+                path: input.to_owned(),
+                source,
+            }
+        })
+    }
+
+    /// Generate the contents of `output` for the shader in `composed`.
+    ///
+    /// The parent directory of `output` must already exist if [ShaderSet::include_str] is `true`.
+    pub fn generate(&self, composed: &ComposedSource, output: &Path) -> Result<String, BuildError> {
+        let input = &composed.files[0];
+
+        let mut options = self.options.clone();
+        if self.include_str {
+            let path = relative_path(input, output_dir(output))?;
+            options.source_mode = ShaderSourceMode::IncludeStr(path);
+        }
+
+        let text = create_shader_module_composed(composed, options).map_err(|source| {
+            BuildError::ShaderError {
+                path: input.clone(),
+                source,
+            }
+        })?;
+        Ok(format!(
+            "// File automatically generated by wgsl_to_wgpu.\n// Changes made to this file will not be saved.\n{text}"
+        ))
+    }
+
+    /// Generate the JSON [ShaderReflection](crate::ShaderReflection) for the shader in `composed`.
+    ///
+    /// Requires the `reflect` feature.
+    #[cfg(feature = "reflect")]
+    pub fn reflect(&self, composed: &ComposedSource) -> Result<String, BuildError> {
+        crate::reflect(&composed.source, self.options.clone())
+            .map(|reflection| reflection.to_json())
+            .map_err(|source| BuildError::ShaderError {
+                path: composed.files[0].clone(),
+                source,
+            })
+    }
+}
+
+/// Create the parent directories for `output`.
+pub fn create_output_dir(output: &Path) -> Result<(), BuildError> {
+    let dir = output_dir(output);
+    std::fs::create_dir_all(dir).map_err(|e| BuildError::WriteError {
+        path: dir.to_owned(),
+        message: e.to_string(),
+    })
+}
+
+fn output_dir(output: &Path) -> &Path {
+    match output.parent() {
+        Some(parent) if parent != Path::new("") => parent,
+        _ => Path::new("."),
+    }
+}
+
+/// The input WGSL file and output Rust file in `output_dir` for each shader in `inputs`.
+fn dir_outputs(
+    inputs: &[PathBuf],
+    output_dir: &Path,
+) -> Result<Vec<(PathBuf, PathBuf)>, BuildError> {
+    let mut outputs = Vec::new();
+    for input in inputs {
+        if input.is_dir() {
+            // Preserve the directory structure to avoid name conflicts.
+            for file in wgsl_files(input)? {
+                let relative = file.strip_prefix(input).unwrap().with_extension("rs");
+                outputs.push((file, output_dir.join(relative)));
+            }
+        } else if input.is_file() {
+            let name = input.with_extension("rs");
+            outputs.push((input.clone(), output_dir.join(name.file_name().unwrap())));
+        } else {
+            return Err(BuildError::ReadError {
+                path: input.clone(),
+                message: "no such file or directory".to_owned(),
+            });
+        }
+    }
+
+    let mut inputs = BTreeMap::new();
+    for (input, output) in &outputs {
+        if let Some(first) = inputs.insert(output, input) {
+            return Err(BuildError::DuplicateOutput {
+                first: first.clone(),
+                second: input.clone(),
+                output: output.clone(),
+            });
+        }
+    }
+
+    Ok(outputs)
+}
+
+fn wgsl_files(dir: &Path) -> Result<Vec<PathBuf>, BuildError> {
+    let read_error = |e: std::io::Error| BuildError::ReadError {
+        path: dir.to_owned(),
+        message: e.to_string(),
+    };
+
+    let mut files = Vec::new();
+    for entry in std::fs::read_dir(dir).map_err(read_error)? {
+        let path = entry.map_err(read_error)?.path();
+        if path.is_dir() {
+            files.extend(wgsl_files(&path)?);
+        } else if path.extension().is_some_and(|e| e == "wgsl") {
+            files.push(path);
+        }
+    }
+    // Sort for consistent output between platforms.
+    files.sort();
+    Ok(files)
+}
+
+/// The path to `path` relative to the directory `base` for `include_str!`.
+fn relative_path(path: &Path, base: &Path) -> Result<String, BuildError> {
+    let canonicalize = |p: &Path| {
+        p.canonicalize().map_err(|e| BuildError::ReadError {
+            path: p.to_owned(),
+            message: e.to_string(),
+        })
+    };
+    let path = canonicalize(path)?;
+    let base = canonicalize(base)?;
+
+    let path_components: Vec<_> = path.components().collect();
+    let base_components: Vec<_> = base.components().collect();
+    let common = path_components
+        .iter()
+        .zip(&base_components)
+        .take_while(|(a, b)| a == b)
+        .count();
+
+    let relative: PathBuf = (common..base_components.len())
+        .map(|_| Component::ParentDir)
+        .chain(path_components[common..].iter().copied())
+        .collect();
+
+    // Use forward slashes so the generated code is the same on all platforms.
+    let components: Vec<_> = relative
+        .components()
+        .map(|c| c.as_os_str().to_string_lossy().into_owned())
+        .collect();
+    Ok(components.join("/"))
+}
+
+#[cfg(feature = "config")]
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ConfigFile {
+    #[serde(default)]
+    options: OptionsConfig,
+    #[serde(default)]
+    shaders: Vec<ShaderSetConfig>,
+}
+
+#[cfg(feature = "config")]
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ShaderSetConfig {
+    inputs: Vec<PathBuf>,
+    output: PathBuf,
+    #[serde(default)]
+    options: OptionsConfig,
+}
+
+#[cfg(feature = "config")]
+#[derive(Default, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct OptionsConfig {
+    derive_bytemuck_vertex: Option<bool>,
+    derive_bytemuck_host_shareable: Option<bool>,
+    derive_encase_host_shareable: Option<bool>,
+    derive_serde: Option<bool>,
+    matrix_vector_types: Option<MatrixVectorTypesConfig>,
+    constant_macros: Option<bool>,
+    source_mode: Option<SourceModeConfig>,
+    defines: Option<BTreeMap<String, DefineValue>>,
+    translations: Option<Vec<TranslationConfig>>,
+    type_overrides: Option<BTreeMap<String, String>>,
+    dynamic_bindings: Option<Vec<String>>,
+    include_dirs: Option<Vec<PathBuf>>,
+    rustfmt: Option<bool>,
+    reflection: Option<bool>,
+}
+
+#[cfg(feature = "config")]
+#[derive(Clone, Copy, PartialEq, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+enum MatrixVectorTypesConfig {
+    Rust,
+    RustOrdered,
+    Glam,
+    Nalgebra,
+    Mint,
+    Cgmath,
+    Ultraviolet,
+}
+
+#[cfg(feature = "config")]
+#[derive(Clone, Copy, PartialEq, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+enum SourceModeConfig {
+    Normalized,
+    Verbatim,
+    IncludeStr,
+}
+
+#[cfg(feature = "config")]
+#[derive(Clone, Copy, PartialEq, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+enum TranslationConfig {
+    Spirv,
+    Glsl,
+}
+
+#[cfg(feature = "config")]
+#[derive(Clone, Deserialize)]
+#[serde(untagged)]
+enum DefineValue {
+    String(String),
+    Integer(i64),
+    Bool(bool),
+}
+
+#[cfg(feature = "config")]
+impl OptionsConfig {
+    /// Replace options with the values set in `other`.
+    fn merge(self, other: Self) -> Self {
+        Self {
+            derive_bytemuck_vertex: other.derive_bytemuck_vertex.or(self.derive_bytemuck_vertex),
+            derive_bytemuck_host_shareable: other
+                .derive_bytemuck_host_shareable
+                .or(self.derive_bytemuck_host_shareable),
+            derive_encase_host_shareable: other
+                .derive_encase_host_shareable
+                .or(self.derive_encase_host_shareable),
+            derive_serde: other.derive_serde.or(self.derive_serde),
+            matrix_vector_types: other.matrix_vector_types.or(self.matrix_vector_types),
+            constant_macros: other.constant_macros.or(self.constant_macros),
+            source_mode: other.source_mode.or(self.source_mode),
+            defines: other.defines.or(self.defines),
+            translations: other.translations.or(self.translations),
+            type_overrides: other.type_overrides.or(self.type_overrides),
+            dynamic_bindings: other.dynamic_bindings.or(self.dynamic_bindings),
+            include_dirs: other.include_dirs.or(self.include_dirs),
+            rustfmt: other.rustfmt.or(self.rustfmt),
+            reflection: other.reflection.or(self.reflection),
+        }
+    }
+
+    fn write_options(&self) -> WriteOptions {
+        let defaults = WriteOptions::default();
+        WriteOptions {
+            derive_bytemuck_vertex: self
+                .derive_bytemuck_vertex
+                .unwrap_or(defaults.derive_bytemuck_vertex),
+            derive_bytemuck_host_shareable: self
+                .derive_bytemuck_host_shareable
+                .unwrap_or(defaults.derive_bytemuck_host_shareable),
+            derive_encase_host_shareable: self
+                .derive_encase_host_shareable
+                .unwrap_or(defaults.derive_encase_host_shareable),
+            derive_serde: self.derive_serde.unwrap_or(defaults.derive_serde),
+            matrix_vector_types: match self.matrix_vector_types {
+                Some(MatrixVectorTypesConfig::Rust) => MatrixVectorTypes::Rust { ordered: false },
+                Some(MatrixVectorTypesConfig::RustOrdered) => {
+                    MatrixVectorTypes::Rust { ordered: true }
+                }
+                Some(MatrixVectorTypesConfig::Glam) => MatrixVectorTypes::Glam,
+                Some(MatrixVectorTypesConfig::Nalgebra) => MatrixVectorTypes::Nalgebra,
+                Some(MatrixVectorTypesConfig::Mint) => MatrixVectorTypes::Mint,
+                Some(MatrixVectorTypesConfig::Cgmath) => MatrixVectorTypes::Cgmath,
+                Some(MatrixVectorTypesConfig::Ultraviolet) => MatrixVectorTypes::Ultraviolet,
+                None => defaults.matrix_vector_types,
+            },
+            constant_macros: self.constant_macros.unwrap_or(defaults.constant_macros),
+            // The path for IncludeStr depends on the input and output for each shader.
+            source_mode: match self.source_mode {
+                Some(SourceModeConfig::Verbatim) => ShaderSourceMode::Verbatim,
+                Some(SourceModeConfig::Normalized | SourceModeConfig::IncludeStr) | None => {
+                    ShaderSourceMode::Normalized
+                }
+            },
+            defines: match &self.defines {
+                Some(defines) => defines
+                    .iter()
+                    .map(|(name, value)| {
+                        let value = match value {
+                            DefineValue::String(s) => s.clone(),
+                            DefineValue::Integer(i) => i.to_string(),
+                            DefineValue::Bool(b) => b.to_string(),
+                        };
+                        (name.clone(), value)
+                    })
+                    .collect(),
+                None => defaults.defines,
+            },
+            translations: match &self.translations {
+                Some(translations) => Translations {
+                    spirv: translations.contains(&TranslationConfig::Spirv),
+                    glsl: translations.contains(&TranslationConfig::Glsl),
+                },
+                None => defaults.translations,
+            },
+            type_overrides: match &self.type_overrides {
+                Some(overrides) => overrides.clone().into_iter().collect(),
+                None => defaults.type_overrides,
+            },
+            dynamic_bindings: match &self.dynamic_bindings {
+                Some(bindings) => bindings.clone(),
+                None => defaults.dynamic_bindings,
+            },
+            rustfmt: self.rustfmt.unwrap_or(defaults.rustfmt),
+            ..defaults
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::assert_tokens_eq;
+    #[cfg(all(feature = "config", feature = "reflect"))]
+    use indoc::indoc;
+
+    #[cfg(all(feature = "config", feature = "reflect"))]
+    #[test]
+    fn config_from_toml() {
+        let config = Config::from_toml(
+            indoc! {r#"
+                [options]
+                derive_bytemuck_vertex = true
+                matrix_vector_types = "glam"
+                defines = { QUALITY = "high" }
+
+                [[shaders]]
+                inputs = ["src/shader.wgsl"]
+                output = "src/shader.rs"
+
+                [[shaders]]
+                inputs = ["shaders/materials", "shaders/sky.wgsl"]
+                output = "src/materials"
+
+                [shaders.options]
+                matrix_vector_types = "mint"
+                source_mode = "include-str"
+                include_dirs = ["shaders/common"]
+                defines = { SKINNING = 1, SHADOWS = true }
+                translations = ["spirv", "glsl"]
+                type_overrides = { Camera = "crate::camera::Camera" }
+                dynamic_bindings = ["transforms"]
+                reflection = true
+            "#},
+            Path::new("assets"),
+        )
+        .unwrap();
+
+        assert_eq!(
+            Config {
+                shaders: vec![
+                    ShaderSet {
+                        inputs: vec!["assets/src/shader.wgsl".into()],
+                        output: "assets/src/shader.rs".into(),
+                        search_paths: Vec::new(),
+                        options: WriteOptions {
+                            derive_bytemuck_vertex: true,
+                            matrix_vector_types: MatrixVectorTypes::Glam,
+                            defines: vec![("QUALITY".to_owned(), "high".to_owned())],
+                            ..Default::default()
+                        },
+                        include_str: false,
+                        reflection: false,
+                    },
+                    ShaderSet {
+                        inputs: vec![
+                            "assets/shaders/materials".into(),
+                            "assets/shaders/sky.wgsl".into()
+                        ],
+                        output: "assets/src/materials".into(),
+                        search_paths: vec!["assets/shaders/common".into()],
+                        options: WriteOptions {
+                            derive_bytemuck_vertex: true,
+                            matrix_vector_types: MatrixVectorTypes::Mint,
+                            defines: vec![
+                                ("SHADOWS".to_owned(), "true".to_owned()),
+                                ("SKINNING".to_owned(), "1".to_owned())
+                            ],
+                            translations: Translations {
+                                spirv: true,
+                                glsl: true,
+                            },
+                            type_overrides: vec![(
+                                "Camera".to_owned(),
+                                "crate::camera::Camera".to_owned()
+                            )],
+                            dynamic_bindings: vec!["transforms".to_owned()],
+                            ..Default::default()
+                        },
+                        include_str: true,
+                        reflection: true,
+                    }
+                ]
+            },
+            config
+        );
+    }
+
+    #[cfg(feature = "config")]
+    #[test]
+    fn config_from_toml_errors() {
+        let error = |text| Config::from_toml(text, Path::new("")).unwrap_err();
+
+        assert!(error("[options]\nderive_bytemuck = true").contains("derive_bytemuck"));
+        assert!(error("[options]\nmatrix_vector_types = \"cgmath2\"").contains("cgmath2"));
+        assert!(error("[[shaders]]\ninputs = [\"a.wgsl\"]").contains("output"));
+    }
+
+    #[cfg(feature = "config")]
+    #[test]
+    fn config_from_file_missing() {
+        assert!(matches!(
+            Config::from_file("missing/wgsl_to_wgpu.toml"),
+            Err(BuildError::ReadError { path, .. }) if path == Path::new("missing/wgsl_to_wgpu.toml")
+        ));
+    }
+
+    #[test]
+    fn shader_set_outputs() {
+        let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("src/data/compose");
+        let set = |inputs: Vec<PathBuf>| ShaderSet {
+            inputs,
+            output: "out".into(),
+            search_paths: Vec::new(),
+            options: WriteOptions::default(),
+            include_str: false,
+            reflection: false,
+        };
+
+        assert_eq!(
+            vec![(dir.join("main.wgsl"), PathBuf::from("out"))],
+            set(vec![dir.join("main.wgsl")]).outputs().unwrap()
+        );
+
+        let outputs = set(vec![dir.join("shared"), dir.join("main.wgsl")])
+            .outputs()
+            .unwrap();
+        assert_eq!(
+            vec![
+                (
+                    dir.join("shared/lighting.wgsl"),
+                    PathBuf::from("out/lighting.rs")
+                ),
+                (dir.join("main.wgsl"), PathBuf::from("out/main.rs")),
+            ],
+            outputs
+        );
+
+        assert!(matches!(
+            set(vec![dir.join("nothing")]).outputs(),
+            Err(BuildError::ReadError { .. })
+        ));
+    }
+
+    #[test]
+    fn shader_set_duplicate_outputs() {
+        let dir = std::env::temp_dir().join(format!(
+            "wgsl_to_wgpu_duplicate_outputs_{}",
+            std::process::id()
+        ));
+        for name in ["a", "b"] {
+            std::fs::create_dir_all(dir.join(name)).unwrap();
+            std::fs::write(dir.join(name).join("shader.wgsl"), "").unwrap();
+        }
+
+        let set = ShaderSet {
+            inputs: vec![dir.join("a"), dir.join("b")],
+            output: "out".into(),
+            search_paths: Vec::new(),
+            options: WriteOptions::default(),
+            include_str: false,
+            reflection: false,
+        };
+        let result = set.outputs();
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(
+            Err(BuildError::DuplicateOutput {
+                first: dir.join("a/shader.wgsl"),
+                second: dir.join("b/shader.wgsl"),
+                output: "out/shader.rs".into()
+            }),
+            result
+        );
+    }
+
+    #[test]
+    fn module_tree_nested() {
+        let outputs = [
+            ("shader.wgsl", "out/shader.rs"),
+            ("materials/pbr.wgsl", "out/materials/pbr.rs"),
+            (
+                "materials/toon-shading.wgsl",
+                "out/materials/toon-shading.rs",
+            ),
+            ("materials.wgsl", "out/materials.rs"),
+        ]
+        .map(|(input, output)| (PathBuf::from(input), PathBuf::from(output)));
+
+        assert_tokens_eq!(
+            quote! {
+                pub mod materials {
+                    include!("materials.rs");
+                    pub mod pbr {
+                        include!("materials/pbr.rs");
+                    }
+                    pub mod toon_shading {
+                        include!("materials/toon-shading.rs");
+                    }
+                }
+                pub mod shader {
+                    include!("shader.rs");
+                }
+            },
+            module_tree(&outputs, Path::new("out")).unwrap()
+        );
+    }
+
+    #[test]
+    fn module_tree_invalid_name() {
+        let outputs = [(PathBuf::from("mod.wgsl"), PathBuf::from("out/mod.rs"))];
+        assert_eq!(
+            Err(BuildError::InvalidModuleName {
+                path: "out/mod.rs".into()
+            }),
+            module_tree(&outputs, Path::new("out")).map(|_| ())
+        );
+    }
+
+    #[test]
+    fn builder_out_dir() {
+        let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("src/data/compose");
+        // Tests run in parallel and may also run from multiple processes.
+        let out_dir = std::env::temp_dir().join(format!(
+            "wgsl_to_wgpu_builder_out_dir_{}",
+            std::process::id()
+        ));
+        let _ = std::fs::remove_dir_all(&out_dir);
+
+        let builder = Builder::new()
+            .shader(dir.join("main.wgsl"))
+            .shader_dir(dir.join("shared"))
+            .include_dir(dir.join("shared"))
+            .out_dir(&out_dir);
+        builder.build().unwrap();
+
+        let module = std::fs::read_to_string(out_dir.join("mod.rs")).unwrap();
+        assert!(module.contains("pub mod main {"));
+        assert!(module.contains("pub mod lighting {"));
+        assert!(out_dir.join("main.rs").is_file());
+        assert!(out_dir.join("lighting.rs").is_file());
+
+        // Unchanged outputs are not rewritten.
+        let modified = || {
+            std::fs::metadata(out_dir.join("main.rs"))
+                .unwrap()
+                .modified()
+                .unwrap()
+        };
+        let before = modified();
+        builder.build().unwrap();
+        assert_eq!(before, modified());
+
+        std::fs::remove_dir_all(&out_dir).unwrap();
+    }
+
+    #[test]
+    fn builder_missing_out_dir() {
+        // OUT_DIR is only set for build scripts.
+        if std::env::var_os("OUT_DIR").is_none() {
+            assert_eq!(Err(BuildError::MissingOutDir), Builder::new().build());
+        }
+    }
+
+    #[test]
+    fn relative_path_include_str() {
+        let dir = Path::new(env!("CARGO_MANIFEST_DIR"));
+        let path = dir.join("src/data/compose/main.wgsl");
+        assert_eq!(
+            Ok("../src/data/compose/main.wgsl".to_owned()),
+            relative_path(&path, &dir.join("tests"))
+        );
+        assert_eq!(
+            Ok("main.wgsl".to_owned()),
+            relative_path(&path, &dir.join("src/data/compose"))
+        );
+    }
+}
@@ -0,0 +1,552 @@
+use std::{
+    borrow::Cow,
+    path::{Path, PathBuf},
+};
+
+use crate::CreateModuleError;
+
+/// WGSL source composed from a root file and the files it includes.
+///
+/// Lines like `#include "lighting.wgsl"` are replaced with the contents of the included file.
+/// Paths are resolved relative to the including file and then relative to each search path in order.
+/// Each file is included at most once, so shared code can be included from multiple files.
+///
+/// Lines are only included if the enclosing conditional directives evaluate to true for the defines.
+/// See [WriteOptions::defines](crate::WriteOptions::defines) for the supported directives.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ComposedSource {
+    /// The composed WGSL source with all includes resolved.
+    pub source: String,
+
+    /// The canonical path of every file used to compose the source starting with the root file.
+    pub files: Vec<PathBuf>,
+
+    /// The index into `files` and the 1-based line in that file for each line of `source`.
+    lines: Vec<(usize, usize)>,
+
+    /// `true` if any `#include` or conditional directive was applied.
+    has_directives: bool,
+}
+
+impl ComposedSource {
+    /// Read the file at `path` and recursively resolve its includes and conditional directives.
+    pub fn new(
+        path: impl AsRef<Path>,
+        search_paths: &[PathBuf],
+        defines: &[(String, String)],
+    ) -> Result<Self, CreateModuleError> {
+        let mut composed = Self {
+            source: String::new(),
+            files: Vec::new(),
+            lines: Vec::new(),
+            has_directives: false,
+        };
+        composed.include(path.as_ref(), search_paths, defines, &mut Vec::new())?;
+        Ok(composed)
+    }
+
+    /// `true` if [Self::source] differs from the root file due to `#include` or conditional directives.
+    pub fn is_preprocessed(&self) -> bool {
+        self.has_directives
+    }
+
+    /// The original file and 1-based line for the 1-based `line` in [Self::source].
+    pub fn location(&self, line: usize) -> Option<(&Path, usize)> {
+        let (file, line) = *self.lines.get(line.checked_sub(1)?)?;
+        Some((&self.files[file], line))
+    }
+
+    /// Prefix `message` with the original file, line, and column of `location` in [Self::source].
+    pub(crate) fn located_message(
+        &self,
+        location: Option<naga::SourceLocation>,
+        message: &str,
+    ) -> String {
+        match location.and_then(|l| Some((self.location(l.line_number as usize)?, l))) {
+            Some(((path, line), l)) => {
+                format!("{}:{line}:{}: {message}", path.display(), l.line_position)
+            }
+            None => message.to_owned(),
+        }
+    }
+
+    fn include(
+        &mut self,
+        path: &Path,
+        search_paths: &[PathBuf],
+        defines: &[(String, String)],
+        stack: &mut Vec<PathBuf>,
+    ) -> Result<(), CreateModuleError> {
+        let read_error = |e: std::io::Error| CreateModuleError::ReadError {
+            path: path.to_owned(),
+            message: e.to_string(),
+        };
+        let path = path.canonicalize().map_err(read_error)?;
+        let contents = std::fs::read_to_string(&path).map_err(read_error)?;
+
+        let file = self.files.len();
+        self.files.push(path.clone());
+        stack.push(path.clone());
+
+        let mut conditionals = Conditionals::default();
+        for (i, line) in contents.lines().enumerate() {
+            let line_number = i + 1;
+            let preprocess_error = |message: String| CreateModuleError::PreprocessError {
+                path: path.clone(),
+                line: line_number,
+                message,
+            };
+
+            let is_directive = conditionals
+                .process(line, line_number, defines)
+                .map_err(preprocess_error)?;
+            self.has_directives |= is_directive;
+            if is_directive || !conditionals.is_active() {
+                continue;
+            }
+
+            let Some(include) = line.trim_start().strip_prefix("#include") else {
+                self.source.push_str(line);
+                self.source.push('\n');
+                self.lines.push((file, line_number));
+                continue;
+            };
+            self.has_directives = true;
+
+            let include = include
+                .trim()
+                .strip_prefix('"')
+                .and_then(|s| s.strip_suffix('"'))
+                .ok_or_else(|| {
+                    preprocess_error("expected a quoted path after #include".to_owned())
+                })?;
+
+            let included = resolve_include(&path, include, search_paths)
+                .ok_or_else(|| {
+                    preprocess_error(format!(
+                        "could not find `{include}` relative to the file or any search path"
+                    ))
+                })?
+                .canonicalize()
+                .map_err(|e| preprocess_error(e.to_string()))?;
+
+            if let Some(start) = stack.iter().position(|p| p == &included) {
+                let cycle: Vec<_> = stack[start..]
+                    .iter()
+                    .chain(std::iter::once(&included))
+                    .map(|p| p.display().to_string())
+                    .collect();
+                return Err(preprocess_error(format!(
+                    "include cycle {}",
+                    cycle.join(" -> ")
+                )));
+            }
+
+            if !self.files.contains(&included) {
+                self.include(&included, search_paths, defines, stack)?;
+            }
+        }
+        conditionals
+            .finish()
+            .map_err(|(line, message)| CreateModuleError::PreprocessError {
+                path: path.clone(),
+                line,
+                message,
+            })?;
+
+        stack.pop();
+        Ok(())
+    }
+}
+
+/// Apply the conditional directives in `source` for `defines`.
+/// Directives and excluded lines are replaced with empty lines to preserve line numbers.
+/// Sources without directives are returned unchanged.
+pub fn preprocess<'a>(
+    source: &'a str,
+    defines: &[(String, String)],
+) -> Result<Cow<'a, str>, CreateModuleError> {
+    // Sources without a file use the same name as naga diagnostics.
+    let preprocess_error = |line, message| CreateModuleError::PreprocessError {
+        path: PathBuf::from("wgsl"),
+        line,
+        message,
+    };
+
+    let mut output = String::new();
+    let mut has_directives = false;
+    let mut conditionals = Conditionals::default();
+    for (i, line) in source.lines().enumerate() {
+        let line_number = i + 1;
+        let is_directive = conditionals
+            .process(line, line_number, defines)
+            .map_err(|e| preprocess_error(line_number, e))?;
+        has_directives |= is_directive;
+        if !is_directive && conditionals.is_active() {
+            if line.trim_start().starts_with("#include") {
+                return Err(preprocess_error(
+                    line_number,
+                    "#include requires a shader file path".to_owned(),
+                ));
+            }
+            output.push_str(line);
+        }
+        output.push('\n');
+    }
+    conditionals
+        .finish()
+        .map_err(|(line, message)| preprocess_error(line, message))?;
+
+    if has_directives {
+        Ok(Cow::Owned(output))
+    } else {
+        Ok(Cow::Borrowed(source))
+    }
+}
+
+/// Nested `#ifdef`, `#ifndef`, `#if`, `#else`, and `#endif` blocks.
+#[derive(Default)]
+struct Conditionals {
+    stack: Vec<Conditional>,
+}
+
+struct Conditional {
+    line: usize,
+    directive: &'static str,
+    value: bool,
+    parent_active: bool,
+    has_else: bool,
+}
+
+impl Conditionals {
+    fn is_active(&self) -> bool {
+        match self.stack.last() {
+            Some(c) => c.value && c.parent_active,
+            None => true,
+        }
+    }
+
+    /// Update the state for a conditional directive and return `true` if `line` is a directive.
+    fn process(
+        &mut self,
+        line: &str,
+        line_number: usize,
+        defines: &[(String, String)],
+    ) -> Result<bool, String> {
+        let line = line.trim();
+        let (directive, arguments) = line.split_once(char::is_whitespace).unwrap_or((line, ""));
+        let arguments = arguments.trim();
+        match directive {
+            "#ifdef" => {
+                let value = define(defines, identifier(directive, arguments)?).is_some();
+                self.push(line_number, "#ifdef", value);
+            }
+            "#ifndef" => {
+                let value = define(defines, identifier(directive, arguments)?).is_none();
+                self.push(line_number, "#ifndef", value);
+            }
+            "#if" => {
+                let value = evaluate(arguments, defines)?;
+                self.push(line_number, "#if", value);
+            }
+            "#else" => {
+                let conditional = self
+                    .stack
+                    .last_mut()
+                    .ok_or_else(|| "#else without a matching #if".to_owned())?;
+                if conditional.has_else {
+                    return Err("duplicate #else for the same #if".to_owned());
+                }
+                conditional.has_else = true;
+                conditional.value = !conditional.value;
+            }
+            "#endif" => {
+                self.stack
+                    .pop()
+                    .ok_or_else(|| "#endif without a matching #if".to_owned())?;
+            }
+            _ => return Ok(false),
+        }
+        Ok(true)
+    }
+
+    fn push(&mut self, line: usize, directive: &'static str, value: bool) {
+        let parent_active = self.is_active();
+        self.stack.push(Conditional {
+            line,
+            directive,
+            value,
+            parent_active,
+            has_else: false,
+        });
+    }
+
+    /// Check that every conditional was closed at the end of a file.
+    fn finish(&self) -> Result<(), (usize, String)> {
+        match self.stack.last() {
+            Some(c) => Err((c.line, format!("{} without a matching #endif", c.directive))),
+            None => Ok(()),
+        }
+    }
+}
+
+fn define<'a>(defines: &'a [(String, String)], name: &str) -> Option<&'a str> {
+    // Later defines override earlier defines with the same name.
+    defines
+        .iter()
+        .rev()
+        .find(|(n, _)| n == name)
+        .map(|(_, value)| value.as_str())
+}
+
+fn identifier<'a>(directive: &str, arguments: &'a str) -> Result<&'a str, String> {
+    if !arguments.is_empty() && !arguments.contains(char::is_whitespace) {
+        Ok(arguments)
+    } else {
+        Err(format!("expected a single name after {directive}"))
+    }
+}
+
+/// Evaluate `NAME`, `NAME == value`, or `NAME != value` for an `#if` directive.
+fn evaluate(expression: &str, defines: &[(String, String)]) -> Result<bool, String> {
+    let tokens: Vec<_> = expression.split_whitespace().collect();
+    match tokens.as_slice() {
+        [name] => Ok(define(defines, name).is_some_and(|v| v != "0" && v != "false")),
+        [name, "==", value] => Ok(define(defines, name) == Some(*value)),
+        [name, "!=", value] => Ok(define(defines, name) != Some(*value)),
+        _ => Err(format!(
+            "expected `NAME`, `NAME == value`, or `NAME != value` after #if but found `{expression}`"
+        )),
+    }
+}
+
+fn resolve_include(path: &Path, include: &str, search_paths: &[PathBuf]) -> Option<PathBuf> {
+    path.parent()
+        .into_iter()
+        .chain(search_paths.iter().map(|p| p.as_path()))
+        .map(|dir| dir.join(include))
+        .find(|p| p.is_file())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use indoc::indoc;
+
+    fn defines(defines: &[(&str, &str)]) -> Vec<(String, String)> {
+        defines
+            .iter()
+            .map(|(name, value)| (name.to_string(), value.to_string()))
+            .collect()
+    }
+
+    fn data_path(path: &str) -> PathBuf {
+        Path::new(env!("CARGO_MANIFEST_DIR"))
+            .join("src/data/compose")
+            .join(path)
+            .canonicalize()
+            .unwrap()
+    }
+
+    #[test]
+    fn compose_includes_each_file_once() {
+        let composed =
+            ComposedSource::new(data_path("main.wgsl"), &[data_path("shared")], &[]).unwrap();
+
+        assert_eq!(
+            vec![
+                data_path("main.wgsl"),
+                data_path("camera.wgsl"),
+                data_path("shared/lighting.wgsl")
+            ],
+            composed.files
+        );
+        assert_eq!(1, composed.source.matches("struct Camera").count());
+        assert!(!composed.source.contains("#include"));
+        assert!(composed.is_preprocessed());
+        naga::front::wgsl::parse_str(&composed.source).unwrap();
+    }
+
+    #[test]
+    fn compose_source_map() {
+        let composed =
+            ComposedSource::new(data_path("main.wgsl"), &[data_path("shared")], &[]).unwrap();
+
+        // camera.wgsl, lighting.wgsl without its include, and then main.wgsl after the includes.
+        assert_eq!(
+            Some((data_path("camera.wgsl").as_path(), 1)),
+            composed.location(1)
+        );
+        assert_eq!(
+            Some((data_path("shared/lighting.wgsl").as_path(), 2)),
+            composed.location(7)
+        );
+        assert_eq!(
+            Some((data_path("main.wgsl").as_path(), 3)),
+            composed.location(11)
+        );
+        assert_eq!(None, composed.location(0));
+        assert_eq!(None, composed.location(100));
+    }
+
+    #[test]
+    fn compose_missing_include() {
+        assert_eq!(
+            Err(CreateModuleError::PreprocessError {
+                path: data_path("missing.wgsl"),
+                line: 2,
+                message:
+                    "could not find `missing_include.wgsl` relative to the file or any search path"
+                        .to_owned()
+            }),
+            ComposedSource::new(data_path("missing.wgsl"), &[], &[])
+        );
+    }
+
+    #[test]
+    fn compose_include_cycle() {
+        let a = data_path("cycle_a.wgsl");
+        let b = data_path("cycle_b.wgsl");
+        assert_eq!(
+            Err(CreateModuleError::PreprocessError {
+                path: b.clone(),
+                line: 1,
+                message: format!(
+                    "include cycle {} -> {} -> {}",
+                    a.display(),
+                    b.display(),
+                    a.display()
+                )
+            }),
+            ComposedSource::new(&a, &[], &[])
+        );
+    }
+
+    #[test]
+    fn compose_conditional_include() {
+        let path = data_path("conditional.wgsl");
+
+        let composed = ComposedSource::new(&path, &[], &defines(&[("USE_CAMERA", "1")])).unwrap();
+        assert_eq!(vec![path.clone(), data_path("camera.wgsl")], composed.files);
+
+        let composed = ComposedSource::new(&path, &[], &[]).unwrap();
+        assert_eq!(vec![path.clone()], composed.files);
+        assert_eq!("\n@fragment\nfn fs_main() {}\n", composed.source);
+        assert_eq!(Some((path.as_path(), 5)), composed.location(2));
+        assert!(composed.is_preprocessed());
+
+        let composed = ComposedSource::new(data_path("camera.wgsl"), &[], &[]).unwrap();
+        assert!(!composed.is_preprocessed());
+    }
+
+    #[test]
+    fn preprocess_without_directives() {
+        let source = "fn main() {}";
+        assert!(matches!(preprocess(source, &[]), Ok(Cow::Borrowed(s)) if s == source));
+    }
+
+    #[test]
+    fn preprocess_ifdef_ifndef_else() {
+        let source = indoc! {"
+            #ifdef A
+            a
+            #else
+            not a
+            #endif
+            #ifndef B
+            not b
+            #endif
+        "};
+        assert_eq!(
+            "\na\n\n\n\n\nnot b\n\n",
+            preprocess(source, &defines(&[("A", "")])).unwrap()
+        );
+        assert_eq!(
+            "\n\n\nnot a\n\n\n\n\n",
+            preprocess(source, &defines(&[("B", "1")])).unwrap()
+        );
+    }
+
+    #[test]
+    fn preprocess_if_nested() {
+        let source = indoc! {"
+            #if QUALITY == high
+            #if SHADOWS
+            high shadows
+            #else
+            high
+            #endif
+            #endif
+            #if QUALITY != high
+            low
+            #endif
+        "};
+        let lines = |pairs| {
+            preprocess(source, &defines(pairs))
+                .unwrap()
+                .lines()
+                .filter(|l| !l.is_empty())
+                .map(|l| l.to_owned())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(
+            vec!["high shadows"],
+            lines(&[("QUALITY", "high"), ("SHADOWS", "1")])
+        );
+        assert_eq!(
+            vec!["high"],
+            lines(&[("QUALITY", "high"), ("SHADOWS", "0")])
+        );
+        assert_eq!(vec!["low"], lines(&[("QUALITY", "low"), ("SHADOWS", "1")]));
+        // Later defines override earlier defines.
+        assert_eq!(
+            vec!["low"],
+            lines(&[("QUALITY", "high"), ("QUALITY", "low")])
+        );
+    }
+
+    #[test]
+    fn preprocess_missing_endif() {
+        let source = "fn main() {}\n#ifdef A\n";
+        assert_eq!(
+            Err(CreateModuleError::PreprocessError {
+                path: PathBuf::from("wgsl"),
+                line: 2,
+                message: "#ifdef without a matching #endif".to_owned()
+            }),
+            preprocess(source, &[])
+        );
+    }
+
+    #[test]
+    fn preprocess_invalid_directives() {
+        let error = |source| match preprocess(source, &[]) {
+            Err(CreateModuleError::PreprocessError { line, message, .. }) => (line, message),
+            _ => panic!("expected a preprocess error"),
+        };
+        assert_eq!(
+            (1, "#endif without a matching #if".to_owned()),
+            error("#endif")
+        );
+        assert_eq!(
+            (3, "duplicate #else for the same #if".to_owned()),
+            error("#ifdef A\n#else\n#else\n#endif")
+        );
+        assert_eq!(
+            (1, "expected a single name after #ifdef".to_owned()),
+            error("#ifdef A B\n#endif")
+        );
+        assert_eq!(
+            (
+                1,
+                "expected `NAME`, `NAME == value`, or `NAME != value` after #if but found `A > 1`"
+                    .to_owned()
+            ),
+            error("#if A > 1\n#endif")
+        );
+        assert_eq!(
+            (2, "#include requires a shader file path".to_owned()),
+            error("\n#include \"camera.wgsl\"")
+        );
+    }
+}
@@ -1,40 +1,352 @@
 use naga::Override;
-use proc_macro2::{Span, TokenStream};
+use proc_macro2::{Literal, Span, TokenStream};
 use quote::quote;
 use syn::Ident;
 
 use crate::{
-    wgsl::{require_ordered_float, rust_type},
-    MatrixVectorTypes,
+    wgsl::{matrix_value, require_ordered_float, rust_scalar_type, rust_type, vector_value},
+    CreateModuleError, MatrixVectorTypes, WriteOptions,
 };
 
-pub fn consts(module: &naga::Module) -> Vec<TokenStream> {
+pub fn consts(
+    module: &naga::Module,
+    options: &WriteOptions,
+) -> Result<Vec<TokenStream>, CreateModuleError> {
     // Create matching Rust constants for WGSl constants.
-    module
-        .constants
-        .iter()
-        .filter_map(|(_, t)| -> Option<TokenStream> {
-            let name = Ident::new(t.name.as_ref()?, Span::call_site());
-
-            // TODO: Add support for f64 and f16 once naga supports them.
-            let type_and_value = match &module.global_expressions[t.init] {
-                naga::Expression::Literal(literal) => match literal {
-                    naga::Literal::F64(v) => Some(quote!(f32 = #v)),
-                    naga::Literal::F32(v) => Some(quote!(f32 = #v)),
-                    naga::Literal::U32(v) => Some(quote!(u32 = #v)),
-                    naga::Literal::I32(v) => Some(quote!(i32 = #v)),
-                    naga::Literal::U64(v) => Some(quote!(u64 = #v)),
-                    naga::Literal::Bool(v) => Some(quote!(bool = #v)),
-                    naga::Literal::I64(v) => Some(quote!(i64 = #v)),
-                    naga::Literal::AbstractInt(v) => Some(quote!(i64 = #v)),
-                    naga::Literal::AbstractFloat(v) => Some(quote!(f64 = #v)),
-                },
-                _ => None,
-            }?;
-
-            Some(quote!( pub const #name: #type_and_value;))
-        })
-        .collect()
+    let mut consts = Vec::new();
+    let mut macros = Vec::new();
+    for (_, t) in module.constants.iter() {
+        let Some(name) = t.name.as_ref() else {
+            continue;
+        };
+
+        // Abstract values take the concrete type of the constant.
+        let scalar = module.types[t.ty].inner.scalar();
+        let value = match const_value(module, t.init, scalar) {
+            Ok(value) => value,
+            Err(ConstError::Unsupported) => continue,
+            Err(ConstError::OutOfRange(message)) => {
+                return Err(CreateModuleError::InvalidConstant {
+                    name: name.clone(),
+                    message,
+                })
+            }
+        };
+
+        let name = Ident::new(name, Span::call_site());
+        let format = options.matrix_vector_types;
+        match value {
+            ConstValue::Scalar(scalar, value) => {
+                let ty = rust_scalar_type(&scalar);
+                if options.constant_macros {
+                    macros.extend(constant_macro(module, &name, t.init));
+                }
+                consts.push(quote!(pub const #name: #ty = #value;));
+            }
+            value => {
+                // Composite constants use the same types as the generated structs.
+                let ty = rust_type(module, &module.types[t.ty], format);
+                if let Some(value) = quote_const_value(module, &value, format) {
+                    consts.push(quote!(pub const #name: #ty = #value;));
+                }
+            }
+        }
+    }
+
+    // Macros use a separate module to keep the WGSL names without conflicting with the constants.
+    if !macros.is_empty() {
+        consts.push(quote! {
+            pub mod constant_macros {
+                #(#macros)*
+            }
+        });
+    }
+    Ok(consts)
+}
+
+fn constant_macro(
+    module: &naga::Module,
+    name: &Ident,
+    expr: naga::Handle<naga::Expression>,
+) -> Option<TokenStream> {
+    // Rust infers the type of unsuffixed literals from where the macro is used.
+    let value = match &module.global_expressions[expr] {
+        naga::Expression::Literal(literal) => match literal {
+            naga::Literal::F64(v) | naga::Literal::AbstractFloat(v) => Literal::f64_unsuffixed(*v),
+            naga::Literal::F32(v) => Literal::f32_unsuffixed(*v),
+            naga::Literal::U32(v) => Literal::u32_unsuffixed(*v),
+            naga::Literal::I32(v) => Literal::i32_unsuffixed(*v),
+            naga::Literal::U64(v) => Literal::u64_unsuffixed(*v),
+            naga::Literal::I64(v) | naga::Literal::AbstractInt(v) => Literal::i64_unsuffixed(*v),
+            naga::Literal::Bool(_) => return None,
+        },
+        naga::Expression::Constant(c) => {
+            return constant_macro(module, name, module.constants[*c].init)
+        }
+        _ => return None,
+    };
+
+    Some(quote! {
+        macro_rules! #name {
+            () => {
+                #value
+            };
+        }
+        pub(crate) use #name;
+    })
+}
+
+/// A fully evaluated constant expression.
+enum ConstValue {
+    Scalar(naga::Scalar, TokenStream),
+    Vector(naga::Scalar, Vec<TokenStream>),
+    /// The components for each column of the matrix.
+    Matrix(naga::Scalar, Vec<Vec<TokenStream>>),
+    Array(Vec<ConstValue>),
+    Struct(naga::Handle<naga::Type>, Vec<ConstValue>),
+}
+
+/// Reasons a constant expression can't be converted to a Rust value.
+enum ConstError {
+    /// The expression isn't supported, so the constant is skipped.
+    Unsupported,
+    /// An abstract value can't be represented by the concrete type of the constant.
+    OutOfRange(String),
+}
+
+/// Evaluate `expr` with `scalar` as the concrete type for abstract literals.
+fn const_value(
+    module: &naga::Module,
+    expr: naga::Handle<naga::Expression>,
+    scalar: Option<naga::Scalar>,
+) -> Result<ConstValue, ConstError> {
+    match &module.global_expressions[expr] {
+        naga::Expression::Literal(literal) => {
+            let value = match literal {
+                naga::Literal::F64(v) => quote!(#v),
+                naga::Literal::F32(v) => quote!(#v),
+                naga::Literal::U32(v) => quote!(#v),
+                naga::Literal::I32(v) => quote!(#v),
+                naga::Literal::U64(v) => quote!(#v),
+                naga::Literal::Bool(v) => quote!(#v),
+                naga::Literal::I64(v) => quote!(#v),
+                naga::Literal::AbstractInt(_) | naga::Literal::AbstractFloat(_) => {
+                    return abstract_value(literal, scalar)
+                }
+            };
+            Ok(ConstValue::Scalar(literal.scalar(), value))
+        }
+        naga::Expression::Constant(c) => {
+            let c = &module.constants[*c];
+            const_value(module, c.init, module.types[c.ty].inner.scalar())
+        }
+        naga::Expression::ZeroValue(ty) => zero_value(module, *ty).ok_or(ConstError::Unsupported),
+        naga::Expression::Splat { size, value } => match const_value(module, *value, scalar)? {
+            ConstValue::Scalar(scalar, value) => {
+                Ok(ConstValue::Vector(scalar, vec![value; *size as usize]))
+            }
+            _ => Err(ConstError::Unsupported),
+        },
+        naga::Expression::Compose { ty, components } => {
+            // Each component has the scalar type of the element or member it initializes.
+            let component_scalar = |i: usize| match &module.types[*ty].inner {
+                naga::TypeInner::Array { base, .. } => module.types[*base].inner.scalar(),
+                naga::TypeInner::Struct { members, .. } => {
+                    module.types[members.get(i)?.ty].inner.scalar()
+                }
+                inner => inner.scalar(),
+            };
+            let components = components
+                .iter()
+                .enumerate()
+                .map(|(i, c)| const_value(module, *c, component_scalar(i)))
+                .collect::<Result<Vec<_>, _>>()?;
+            match &module.types[*ty].inner {
+                naga::TypeInner::Vector { scalar, .. } => {
+                    // Vectors can be constructed from a mix of scalars and smaller vectors.
+                    let mut values = Vec::new();
+                    for component in components {
+                        match component {
+                            ConstValue::Scalar(_, value) => values.push(value),
+                            ConstValue::Vector(_, vector) => values.extend(vector),
+                            _ => return Err(ConstError::Unsupported),
+                        }
+                    }
+                    Ok(ConstValue::Vector(*scalar, values))
+                }
+                naga::TypeInner::Matrix { scalar, .. } => {
+                    let columns = components
+                        .into_iter()
+                        .map(|c| match c {
+                            ConstValue::Vector(_, column) => Ok(column),
+                            _ => Err(ConstError::Unsupported),
+                        })
+                        .collect::<Result<Vec<_>, _>>()?;
+                    Ok(ConstValue::Matrix(*scalar, columns))
+                }
+                naga::TypeInner::Array { .. } => Ok(ConstValue::Array(components)),
+                naga::TypeInner::Struct { .. } => Ok(ConstValue::Struct(*ty, components)),
+                _ => Err(ConstError::Unsupported),
+            }
+        }
+        _ => Err(ConstError::Unsupported),
+    }
+}
+
+/// Concretize an abstract literal to `scalar` like WGSL.
+/// Values without a known numeric type use the WGSL defaults of i32 and f32.
+fn abstract_value(
+    literal: &naga::Literal,
+    scalar: Option<naga::Scalar>,
+) -> Result<ConstValue, ConstError> {
+    let scalar = scalar.filter(|s| s.kind != naga::ScalarKind::Bool);
+    let out_of_range = |value: &dyn std::fmt::Display, ty: &str| {
+        ConstError::OutOfRange(format!("{value} can't be represented as {ty}"))
+    };
+
+    let (scalar, value) = match *literal {
+        naga::Literal::AbstractInt(v) => {
+            let scalar = scalar.unwrap_or(naga::Scalar::I32);
+            let value = match (scalar.kind, scalar.width) {
+                (naga::ScalarKind::Sint, 4) => {
+                    let v = i32::try_from(v).map_err(|_| out_of_range(&v, "i32"))?;
+                    quote!(#v)
+                }
+                (naga::ScalarKind::Uint, 4) => {
+                    let v = u32::try_from(v).map_err(|_| out_of_range(&v, "u32"))?;
+                    quote!(#v)
+                }
+                (naga::ScalarKind::Sint, 8) => quote!(#v),
+                (naga::ScalarKind::Uint, 8) => {
+                    let v = u64::try_from(v).map_err(|_| out_of_range(&v, "u64"))?;
+                    quote!(#v)
+                }
+                // Integers can also initialize floats.
+                (naga::ScalarKind::Float, _) => {
+                    return abstract_value(&naga::Literal::AbstractFloat(v as f64), Some(scalar))
+                }
+                _ => return Err(ConstError::Unsupported),
+            };
+            (scalar, value)
+        }
+        naga::Literal::AbstractFloat(v) => {
+            let scalar = scalar.unwrap_or(naga::Scalar::F32);
+            let value = match (scalar.kind, scalar.width) {
+                (naga::ScalarKind::Float, 8) => quote!(#v),
+                (naga::ScalarKind::Float, 4) => {
+                    // Rounding is allowed, but the value must be finite.
+                    let f = v as f32;
+                    if !f.is_finite() {
+                        return Err(out_of_range(&v, "f32"));
+                    }
+                    quote!(#f)
+                }
+                (naga::ScalarKind::Float, 2) => {
+                    if v.abs() > 65504.0 {
+                        return Err(out_of_range(&v, "f16"));
+                    }
+                    quote!(half::f16::from_f64_const(#v))
+                }
+                _ => return Err(ConstError::Unsupported),
+            };
+            (scalar, value)
+        }
+        _ => return Err(ConstError::Unsupported),
+    };
+    Ok(ConstValue::Scalar(scalar, value))
+}
+
+fn zero_value(module: &naga::Module, ty: naga::Handle<naga::Type>) -> Option<ConstValue> {
+    match &module.types[ty].inner {
+        naga::TypeInner::Scalar(scalar) => Some(ConstValue::Scalar(*scalar, zero_scalar(scalar)?)),
+        naga::TypeInner::Vector { size, scalar } => Some(ConstValue::Vector(
+            *scalar,
+            vec![zero_scalar(scalar)?; *size as usize],
+        )),
+        naga::TypeInner::Matrix {
+            columns,
+            rows,
+            scalar,
+        } => Some(ConstValue::Matrix(
+            *scalar,
+            vec![vec![zero_scalar(scalar)?; *rows as usize]; *columns as usize],
+        )),
+        naga::TypeInner::Array {
+            base,
+            size: naga::ArraySize::Constant(size),
+            ..
+        } => {
+            let elements = (0..size.get())
+                .map(|_| zero_value(module, *base))
+                .collect::<Option<Vec<_>>>()?;
+            Some(ConstValue::Array(elements))
+        }
+        naga::TypeInner::Struct { members, .. } => {
+            let members = members
+                .iter()
+                .map(|m| zero_value(module, m.ty))
+                .collect::<Option<Vec<_>>>()?;
+            Some(ConstValue::Struct(ty, members))
+        }
+        _ => None,
+    }
+}
+
+fn zero_scalar(scalar: &naga::Scalar) -> Option<TokenStream> {
+    match (scalar.kind, scalar.width) {
+        (naga::ScalarKind::Sint, 4) => Some(quote!(0i32)),
+        (naga::ScalarKind::Uint, 4) => Some(quote!(0u32)),
+        (naga::ScalarKind::Sint, 8) => Some(quote!(0i64)),
+        (naga::ScalarKind::Uint, 8) => Some(quote!(0u64)),
+        (naga::ScalarKind::Float, 2) => Some(quote!(half::f16::ZERO)),
+        (naga::ScalarKind::Float, 4) => Some(quote!(0f32)),
+        (naga::ScalarKind::Float, 8) => Some(quote!(0f64)),
+        (naga::ScalarKind::Bool, _) => Some(quote!(false)),
+        _ => None,
+    }
+}
+
+fn quote_const_value(
+    module: &naga::Module,
+    value: &ConstValue,
+    format: MatrixVectorTypes,
+) -> Option<TokenStream> {
+    match value {
+        ConstValue::Scalar(scalar, value) => match format {
+            MatrixVectorTypes::Rust { ordered: true } if require_ordered_float(scalar.kind) => {
+                Some(quote!(ordered_float::OrderedFloat(#value)))
+            }
+            _ => Some(value.clone()),
+        },
+        ConstValue::Vector(scalar, components) => Some(vector_value(*scalar, components, format)),
+        ConstValue::Matrix(scalar, columns) => matrix_value(*scalar, columns, format),
+        ConstValue::Array(elements) => {
+            let elements = elements
+                .iter()
+                .map(|e| quote_const_value(module, e, format))
+                .collect::<Option<Vec<_>>>()?;
+            Some(quote!([#(#elements),*]))
+        }
+        ConstValue::Struct(ty, values) => {
+            let ty = &module.types[*ty];
+            let name = Ident::new(ty.name.as_ref()?, Span::call_site());
+            let naga::TypeInner::Struct { members, .. } = &ty.inner else {
+                return None;
+            };
+            let fields = members
+                .iter()
+                .zip(values)
+                // Generated structs skip builtins since they don't require user specified data.
+                .filter(|(m, _)| !matches!(m.binding, Some(naga::Binding::BuiltIn(_))))
+                .map(|(m, v)| {
+                    let field = Ident::new(m.name.as_ref()?, Span::call_site());
+                    let value = quote_const_value(module, v, format)?;
+                    Some(quote!(#field: #value))
+                })
+                .collect::<Option<Vec<_>>>()?;
+            Some(quote!(#name { #(#fields),* }))
+        }
+    }
 }
 
 fn convert_overridable_constant_to_f64(ty: &naga::Type, value: TokenStream) -> TokenStream {
@@ -42,6 +354,9 @@ fn convert_overridable_constant_to_f64(ty: &naga::Type, value: TokenStream) -> T
         naga::TypeInner::Scalar(s) if s.kind == naga::ScalarKind::Bool => {
             quote!(if #value { 1f64 } else { 0f64 })
         }
+        naga::TypeInner::Scalar(s) if s.kind == naga::ScalarKind::Float && s.width == 2 => {
+            quote!(#value.into_inner().to_f64())
+        }
         naga::TypeInner::Scalar(s) if require_ordered_float(s.kind) => {
             quote!(#value.into_inner() as f64)
         }
@@ -92,6 +407,44 @@ pub fn pipeline_overridable_constants(module: &naga::Module) -> TokenStream {
         })
         .collect();
 
+    let keys: Vec<_> = overrides
+        .iter()
+        .map(|o| {
+            let name = o.name.as_ref().unwrap().to_uppercase();
+            let key_name = Ident::new(&format!("KEY_{name}"), Span::call_site());
+            let key = override_key(o);
+            let id = o.id.map(|id| {
+                let id_name = Ident::new(&format!("ID_{name}"), Span::call_site());
+                quote!(pub const #id_name: u16 = #id;)
+            });
+            quote! {
+                pub const #key_name: &'static str = #key;
+                #id
+            }
+        })
+        .collect();
+
+    // Defaults can depend on other overrides as long as those overrides also have defaults.
+    let defaults: Vec<_> = overrides
+        .iter()
+        .filter_map(|o| {
+            let name = Ident::new(
+                &format!("DEFAULT_{}", o.name.as_ref().unwrap().to_uppercase()),
+                Span::call_site(),
+            );
+            let ty = rust_scalar_type(&override_scalar(module, o)?);
+            let (_, value) = override_init_value(module, o.init?, &|h| {
+                let name = module.overrides[h].name.as_ref().unwrap().to_uppercase();
+                module.overrides[h].init?;
+                let default = Ident::new(&format!("DEFAULT_{name}"), Span::call_site());
+                Some(quote!(Self::#default))
+            })?;
+            Some(quote!(pub const #name: #ty = #value;))
+        })
+        .collect();
+
+    let resolved = resolved_override_constants(module, &overrides);
+
     // Create a Rust struct that can initialize the constants dictionary.
     quote! {
         #[derive(Clone, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
@@ -100,10 +453,194 @@ pub fn pipeline_overridable_constants(module: &naga::Module) -> TokenStream {
         }
 
         impl OverrideConstants {
+            #(#keys)*
+            #(#defaults)*
+
             pub fn constants(&self) -> std::collections::HashMap<String, f64> {
                 [#(#entries),*].into_iter().filter_map(|a| a).collect()
             }
         }
+
+        #resolved
+    }
+}
+
+fn resolved_override_constants(module: &naga::Module, overrides: &[&Override]) -> TokenStream {
+    let fields = overrides
+        .iter()
+        .map(|o| {
+            let name = Ident::new(o.name.as_ref().unwrap(), Span::call_site());
+            let ty = rust_scalar_type(&override_scalar(module, o)?);
+            Some(quote!(pub #name: #ty))
+        })
+        .collect::<Option<Vec<_>>>();
+
+    // Evaluate initializers in order using the values that will be used by the pipeline.
+    // naga sorts overrides so that initializers only depend on previous overrides.
+    let values = overrides
+        .iter()
+        .map(|o| {
+            let name = Ident::new(o.name.as_ref().unwrap(), Span::call_site());
+            let is_float = require_ordered_float(override_scalar(module, o)?.kind);
+            match o.init {
+                Some(init) => {
+                    let value = if is_float {
+                        quote!(self.#name.map(|v| v.into_inner()))
+                    } else {
+                        quote!(self.#name)
+                    };
+                    let depends_on_overrides = std::cell::Cell::new(false);
+                    let (_, default) = override_init_value(module, init, &|h| {
+                        depends_on_overrides.set(true);
+                        let name = module.overrides[h].name.as_ref().unwrap();
+                        let name = Ident::new(name, Span::call_site());
+                        Some(quote!(#name))
+                    })?;
+                    if depends_on_overrides.get() {
+                        Some(quote!(let #name = #value.unwrap_or_else(|| #default);))
+                    } else {
+                        Some(quote!(let #name = #value.unwrap_or(#default);))
+                    }
+                }
+                None if is_float => Some(quote!(let #name = self.#name.into_inner();)),
+                None => Some(quote!(let #name = self.#name;)),
+            }
+        })
+        .collect::<Option<Vec<_>>>();
+
+    // Skip the resolved values if any initializer isn't supported.
+    let (Some(fields), Some(values)) = (fields, values) else {
+        return TokenStream::new();
+    };
+    let names = overrides
+        .iter()
+        .map(|o| Ident::new(o.name.as_ref().unwrap(), Span::call_site()));
+
+    quote! {
+        /// The override values used by the pipeline after evaluating any defaults.
+        #[derive(Debug, Clone, Copy, PartialEq)]
+        pub struct ResolvedOverrideConstants {
+            #(#fields),*
+        }
+
+        impl OverrideConstants {
+            pub fn resolved(&self) -> ResolvedOverrideConstants {
+                #(#values)*
+                ResolvedOverrideConstants { #(#names),* }
+            }
+        }
+    }
+}
+
+fn override_scalar(module: &naga::Module, o: &Override) -> Option<naga::Scalar> {
+    match module.types[o.ty].inner {
+        naga::TypeInner::Scalar(scalar) => Some(scalar),
+        _ => None,
+    }
+}
+
+/// Convert an override initializer operand and add parentheses if needed.
+fn override_operand(
+    module: &naga::Module,
+    expr: naga::Handle<naga::Expression>,
+    override_value: &dyn Fn(naga::Handle<Override>) -> Option<TokenStream>,
+) -> Option<(naga::Scalar, TokenStream)> {
+    let (scalar, value) = override_init_value(module, expr, override_value)?;
+    match &module.global_expressions[expr] {
+        naga::Expression::Unary { .. }
+        | naga::Expression::Binary { .. }
+        | naga::Expression::Select { .. }
+        | naga::Expression::As { .. } => Some((scalar, quote!((#value)))),
+        _ => Some((scalar, value)),
+    }
+}
+
+/// Convert an override initializer to an equivalent Rust expression.
+/// References to other overrides are replaced using `override_value`.
+fn override_init_value(
+    module: &naga::Module,
+    expr: naga::Handle<naga::Expression>,
+    override_value: &dyn Fn(naga::Handle<Override>) -> Option<TokenStream>,
+) -> Option<(naga::Scalar, TokenStream)> {
+    match &module.global_expressions[expr] {
+        naga::Expression::Literal(_)
+        | naga::Expression::Constant(_)
+        | naga::Expression::ZeroValue(_) => match const_value(module, expr, None).ok()? {
+            ConstValue::Scalar(scalar, value) => Some((scalar, value)),
+            _ => None,
+        },
+        naga::Expression::Override(h) => Some((
+            override_scalar(module, &module.overrides[*h])?,
+            override_value(*h)?,
+        )),
+        naga::Expression::Unary { op, expr } => {
+            let (scalar, value) = override_operand(module, *expr, override_value)?;
+            let value = match op {
+                naga::UnaryOperator::Negate => quote!(-#value),
+                naga::UnaryOperator::LogicalNot | naga::UnaryOperator::BitwiseNot => {
+                    quote!(!#value)
+                }
+            };
+            Some((scalar, value))
+        }
+        naga::Expression::Binary { op, left, right } => {
+            let (scalar, left) = override_operand(module, *left, override_value)?;
+            let (_, right) = override_operand(module, *right, override_value)?;
+            let (op, scalar) = match op {
+                naga::BinaryOperator::Add => (quote!(+), scalar),
+                naga::BinaryOperator::Subtract => (quote!(-), scalar),
+                naga::BinaryOperator::Multiply => (quote!(*), scalar),
+                naga::BinaryOperator::Divide => (quote!(/), scalar),
+                naga::BinaryOperator::Modulo => (quote!(%), scalar),
+                naga::BinaryOperator::Equal => (quote!(==), naga::Scalar::BOOL),
+                naga::BinaryOperator::NotEqual => (quote!(!=), naga::Scalar::BOOL),
+                naga::BinaryOperator::Less => (quote!(<), naga::Scalar::BOOL),
+                naga::BinaryOperator::LessEqual => (quote!(<=), naga::Scalar::BOOL),
+                naga::BinaryOperator::Greater => (quote!(>), naga::Scalar::BOOL),
+                naga::BinaryOperator::GreaterEqual => (quote!(>=), naga::Scalar::BOOL),
+                naga::BinaryOperator::And => (quote!(&), scalar),
+                naga::BinaryOperator::ExclusiveOr => (quote!(^), scalar),
+                naga::BinaryOperator::InclusiveOr => (quote!(|), scalar),
+                naga::BinaryOperator::LogicalAnd => (quote!(&&), naga::Scalar::BOOL),
+                naga::BinaryOperator::LogicalOr => (quote!(||), naga::Scalar::BOOL),
+                naga::BinaryOperator::ShiftLeft => (quote!(<<), scalar),
+                naga::BinaryOperator::ShiftRight => (quote!(>>), scalar),
+            };
+            Some((scalar, quote!(#left #op #right)))
+        }
+        naga::Expression::Select {
+            condition,
+            accept,
+            reject,
+        } => {
+            let (_, condition) = override_init_value(module, *condition, override_value)?;
+            let (scalar, accept) = override_init_value(module, *accept, override_value)?;
+            let (_, reject) = override_init_value(module, *reject, override_value)?;
+            Some((scalar, quote!(if #condition { #accept } else { #reject })))
+        }
+        naga::Expression::As {
+            expr,
+            kind,
+            convert: Some(width),
+        } => {
+            let (source, value) = override_operand(module, *expr, override_value)?;
+            let scalar = naga::Scalar {
+                kind: *kind,
+                width: *width,
+            };
+            let ty = rust_scalar_type(&scalar);
+            let value = match (source.kind, scalar.kind) {
+                (_, naga::ScalarKind::Bool) => {
+                    let zero = zero_scalar(&source)?;
+                    quote!(#value != #zero)
+                }
+                // Rust only supports casting booleans to integers.
+                (naga::ScalarKind::Bool, _) => quote!(#value as u8 as #ty),
+                _ => quote!(#value as #ty),
+            };
+            Some((scalar, value))
+        }
+        _ => None,
     }
 }
 
@@ -139,7 +676,7 @@ mod tests {
 
         let module = naga::front::wgsl::parse_str(source).unwrap();
 
-        let consts = consts(&module);
+        let consts = consts(&module, &WriteOptions::default()).unwrap();
         let actual = quote!(#(#consts)*);
 
         assert_tokens_eq!(
@@ -153,6 +690,355 @@ mod tests {
         );
     }
 
+    #[test]
+    fn write_global_constants_64bit() {
+        let source = indoc! {r#"
+            const DOUBLE_CONST = 0.1lf;
+            const TYPED_DOUBLE_CONST: f64 = 2.0;
+            const LONG_CONST = 5li;
+            const UNSIGNED_LONG_CONST = 6lu;
+
+            @fragment
+            fn main() {}
+        "#};
+
+        let module = naga::front::wgsl::parse_str(source).unwrap();
+
+        let consts = consts(&module, &WriteOptions::default()).unwrap();
+        let actual = quote!(#(#consts)*);
+
+        assert_tokens_eq!(
+            quote! {
+                pub const DOUBLE_CONST: f64 = 0.1f64;
+                pub const TYPED_DOUBLE_CONST: f64 = 2f64;
+                pub const LONG_CONST: i64 = 5i64;
+                pub const UNSIGNED_LONG_CONST: u64 = 6u64;
+            },
+            actual
+        );
+    }
+
+    fn parse_abstract(source: &str) -> naga::Module {
+        // naga concretizes abstract constants while parsing, so replace the literals.
+        let mut module = naga::front::wgsl::parse_str(source).unwrap();
+        for (_, e) in module.global_expressions.iter_mut() {
+            if let naga::Expression::Literal(literal) = e {
+                *literal = match *literal {
+                    naga::Literal::I32(v) => naga::Literal::AbstractInt(v as i64 * 1000000000),
+                    naga::Literal::U32(v) => naga::Literal::AbstractInt(v as i64 * 1000000000),
+                    naga::Literal::I64(v) => naga::Literal::AbstractInt(v * 1000000000),
+                    naga::Literal::F64(v) => naga::Literal::AbstractFloat(v),
+                    literal => literal,
+                };
+            }
+        }
+        module
+    }
+
+    #[test]
+    fn write_global_constants_abstract() {
+        let module = parse_abstract(indoc! {r#"
+            const UNSIGNED: u32 = 3u;
+            const LONG: i64 = 5li;
+            const FLOAT: f32 = 2;
+            const DOUBLE: f64 = 0.1lf;
+            const VALUES: array<u32, 2> = array(1u, 2u);
+
+            @fragment
+            fn main() {}
+        "#});
+
+        let consts = consts(&module, &WriteOptions::default()).unwrap();
+        let actual = quote!(#(#consts)*);
+
+        assert_tokens_eq!(
+            quote! {
+                pub const UNSIGNED: u32 = 3000000000u32;
+                pub const LONG: i64 = 5000000000i64;
+                pub const FLOAT: f32 = 2f32;
+                pub const DOUBLE: f64 = 0.1f64;
+                pub const VALUES: [u32; 2] = [1000000000u32, 2000000000u32];
+            },
+            actual
+        );
+    }
+
+    #[test]
+    fn write_global_constants_abstract_usage() {
+        let source = indoc! {r#"
+            const COUNT = 4;
+            const SCALE = 0.5;
+
+            @group(0) @binding(0) var<storage, read_write> values: array<u32>;
+
+            @compute @workgroup_size(1)
+            fn main() {
+                values[0] = u32(COUNT) * u32(SCALE);
+            }
+        "#};
+
+        let module = naga::front::wgsl::parse_str(source).unwrap();
+        let consts = consts(&module, &WriteOptions::default()).unwrap();
+        let actual = quote!(#(#consts)*);
+
+        // naga concretizes abstract constants where they are declared,
+        // so uses in the shader don't change the default type.
+        assert_tokens_eq!(
+            quote! {
+                pub const COUNT: i32 = 4i32;
+                pub const SCALE: f32 = 0.5f32;
+            },
+            actual
+        );
+    }
+
+    #[test]
+    fn write_global_constants_abstract_out_of_range() {
+        let module = parse_abstract(indoc! {r#"
+            const SIGNED: i32 = 3i;
+
+            @fragment
+            fn main() {}
+        "#});
+
+        assert!(matches!(
+            consts(&module, &WriteOptions::default()),
+            Err(CreateModuleError::InvalidConstant { name, message })
+                if name == "SIGNED" && message == "3000000000 can't be represented as i32"
+        ));
+    }
+
+    #[test]
+    fn write_global_constants_macros() {
+        let source = indoc! {r#"
+            const INT_CONST = 12;
+            const FLOAT_CONST = 0.5;
+            const ALIAS_CONST = INT_CONST;
+            const BOOL_CONST = true;
+
+            @fragment
+            fn main() {}
+        "#};
+
+        let module = naga::front::wgsl::parse_str(source).unwrap();
+
+        let consts = consts(
+            &module,
+            &WriteOptions {
+                constant_macros: true,
+                ..Default::default()
+            },
+        )
+        .unwrap();
+        let actual = quote!(#(#consts)*);
+
+        assert_tokens_eq!(
+            quote! {
+                pub const INT_CONST: i32 = 12i32;
+                pub const FLOAT_CONST: f32 = 0.5f32;
+                pub const ALIAS_CONST: i32 = 12i32;
+                pub const BOOL_CONST: bool = true;
+                pub mod constant_macros {
+                    macro_rules! INT_CONST {
+                        () => {
+                            12
+                        };
+                    }
+                    pub(crate) use INT_CONST;
+                    macro_rules! FLOAT_CONST {
+                        () => {
+                            0.5
+                        };
+                    }
+                    pub(crate) use FLOAT_CONST;
+                    macro_rules! ALIAS_CONST {
+                        () => {
+                            12
+                        };
+                    }
+                    pub(crate) use ALIAS_CONST;
+                }
+            },
+            actual
+        );
+    }
+
+    #[test]
+    fn write_global_constants_macros_case() {
+        let source = indoc! {r#"
+            const SCALE = 2;
+            const Scale = 3.0;
+
+            @fragment
+            fn main() {}
+        "#};
+
+        let module = naga::front::wgsl::parse_str(source).unwrap();
+
+        let consts = consts(
+            &module,
+            &WriteOptions {
+                constant_macros: true,
+                ..Default::default()
+            },
+        )
+        .unwrap();
+        let actual = quote!(#(#consts)*);
+
+        // Names that only differ in case should still have separate macros.
+        assert_tokens_eq!(
+            quote! {
+                pub const SCALE: i32 = 2i32;
+                pub const Scale: f32 = 3f32;
+                pub mod constant_macros {
+                    macro_rules! SCALE {
+                        () => {
+                            2
+                        };
+                    }
+                    pub(crate) use SCALE;
+                    macro_rules! Scale {
+                        () => {
+                            3.0
+                        };
+                    }
+                    pub(crate) use Scale;
+                }
+            },
+            actual
+        );
+    }
+
+    #[test]
+    fn write_composite_constants_rust() {
+        let source = indoc! {r#"
+            struct Light {
+                color: vec3<f32>,
+                intensity: f32,
+            }
+
+            const OFFSET = vec3<f32>(1.0, 2.0, 3.0);
+            const ONES = vec4<u32>(1u);
+            const IDENTITY = mat2x2<f32>(vec2(1.0, 0.0), vec2(0.0, 1.0));
+            const SHEAR = mat2x3<f32>(vec3(1.0, 2.0, 3.0), vec3(4.0, 5.0, 6.0));
+            const WEIGHTS = array<i32, 3>(1, 2, 3);
+            const ZERO = vec2<i32>();
+            const LIGHT = Light(OFFSET, 0.5);
+            const ZERO_LIGHT = Light();
+
+            @fragment
+            fn main() {}
+        "#};
+
+        let module = naga::front::wgsl::parse_str(source).unwrap();
+
+        let consts = consts(&module, &WriteOptions::default()).unwrap();
+        let actual = quote!(#(#consts)*);
+
+        assert_tokens_eq!(
+            quote! {
+                pub const OFFSET: [f32; 3] = [1f32, 2f32, 3f32];
+                pub const ONES: [u32; 4] = [1u32, 1u32, 1u32, 1u32];
+                pub const IDENTITY: [[f32; 2]; 2] = [[1f32, 0f32], [0f32, 1f32]];
+                pub const SHEAR: [[f32; 2]; 3] = [[1f32, 2f32], [3f32, 4f32], [5f32, 6f32]];
+                pub const WEIGHTS: [i32; 3] = [1i32, 2i32, 3i32];
+                pub const ZERO: [i32; 2] = [0i32, 0i32];
+                pub const LIGHT: Light = Light {
+                    color: [1f32, 2f32, 3f32],
+                    intensity: 0.5f32
+                };
+                pub const ZERO_LIGHT: Light = Light {
+                    color: [0f32, 0f32, 0f32],
+                    intensity: 0f32
+                };
+            },
+            actual
+        );
+    }
+
+    #[test]
+    fn zero_scalar_f16() {
+        // The WGSL frontend doesn't parse f16 yet, so check the scalar directly.
+        let scalar = naga::Scalar {
+            kind: naga::ScalarKind::Float,
+            width: 2,
+        };
+        assert_tokens_eq!(quote!(half::f16::ZERO), zero_scalar(&scalar).unwrap());
+    }
+
+    #[test]
+    fn write_composite_constants_glam() {
+        let source = indoc! {r#"
+            const OFFSET = vec3<f32>(1.0, 2.0, 3.0);
+            const IDENTITY = mat2x2<f32>(vec2(1.0, 0.0), vec2(0.0, 1.0));
+            const ZERO = mat2x3<f32>();
+            const SIGNS = vec2<bool>(true, false);
+
+            @fragment
+            fn main() {}
+        "#};
+
+        let module = naga::front::wgsl::parse_str(source).unwrap();
+
+        let consts = consts(
+            &module,
+            &WriteOptions {
+                matrix_vector_types: MatrixVectorTypes::Glam,
+                ..Default::default()
+            },
+        )
+        .unwrap();
+        let actual = quote!(#(#consts)*);
+
+        // Non square matrices use Rust arrays filled in column major order.
+        assert_tokens_eq!(
+            quote! {
+                pub const OFFSET: glam::Vec3 = glam::Vec3::new(1f32, 2f32, 3f32);
+                pub const IDENTITY: glam::Mat2 = glam::Mat2::from_cols(
+                    glam::Vec2::new(1f32, 0f32),
+                    glam::Vec2::new(0f32, 1f32)
+                );
+                pub const ZERO: [[f32; 2]; 3] = [[0f32, 0f32], [0f32, 0f32], [0f32, 0f32]];
+                pub const SIGNS: [bool; 2] = [true, false];
+            },
+            actual
+        );
+    }
+
+    #[test]
+    fn write_composite_constants_mint() {
+        let source = indoc! {r#"
+            const OFFSET = vec2<f32>(1.0, 2.0);
+            const IDENTITY = mat2x2<f32>(vec2(1.0, 0.0), vec2(0.0, 1.0));
+
+            @fragment
+            fn main() {}
+        "#};
+
+        let module = naga::front::wgsl::parse_str(source).unwrap();
+
+        let consts = consts(
+            &module,
+            &WriteOptions {
+                matrix_vector_types: MatrixVectorTypes::Mint,
+                ..Default::default()
+            },
+        )
+        .unwrap();
+        let actual = quote!(#(#consts)*);
+
+        assert_tokens_eq!(
+            quote! {
+                pub const OFFSET: mint::Vector2<f32> = mint::Vector2 { x: 1f32, y: 2f32 };
+                pub const IDENTITY: mint::ColumnMatrix2<f32> = mint::ColumnMatrix2 {
+                    x: mint::Vector2 { x: 1f32, y: 0f32 },
+                    y: mint::Vector2 { x: 0f32, y: 1f32 }
+                };
+            },
+            actual
+        );
+    }
+
     #[test]
     fn write_pipeline_overrideable_constants() {
         let source = indoc! {r#"
@@ -233,6 +1119,82 @@ mod tests {
         );
     }
 
+    #[test]
+    fn write_pipeline_overrideable_constants_defaults() {
+        let source = indoc! {r#"
+            override scale: f32 = 1.0;
+            @id(3) override count: i32;
+            override total: i32 = count * 2;
+            override enabled: bool = scale > 0.5;
+
+            @fragment
+            fn main() {}
+        "#};
+
+        let module = naga::front::wgsl::parse_str(source).unwrap();
+
+        let actual = pipeline_overridable_constants(&module);
+
+        assert_tokens_eq!(
+            quote! {
+                #[derive(Clone, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+                pub struct OverrideConstants {
+                    pub scale: Option<ordered_float::OrderedFloat<f32>>,
+                    pub count: i32,
+                    pub total: Option<i32>,
+                    pub enabled: Option<bool>
+                }
+
+                impl OverrideConstants {
+                    pub const KEY_SCALE: &'static str = "scale";
+                    pub const KEY_COUNT: &'static str = "3";
+                    pub const ID_COUNT: u16 = 3u16;
+                    pub const KEY_TOTAL: &'static str = "total";
+                    pub const KEY_ENABLED: &'static str = "enabled";
+                    pub const DEFAULT_SCALE: f32 = 1f32;
+                    pub const DEFAULT_ENABLED: bool = Self::DEFAULT_SCALE > 0.5f32;
+
+                    pub fn constants(&self) -> std::collections::HashMap<String, f64> {
+                        [
+                            self.scale.map(|v| ("scale".to_owned(), v.into_inner() as f64)),
+                            Some(("3".to_owned(), self.count as f64)),
+                            self.total.map(|v| ("total".to_owned(), v as f64)),
+                            self.enabled.map(|v| ("enabled".to_owned(), if v { 1f64 } else { 0f64 }))
+                        ]
+                        .into_iter()
+                        .filter_map(|a| a)
+                        .collect()
+                    }
+                }
+
+                /// The override values used by the pipeline after evaluating any defaults.
+                #[derive(Debug, Clone, Copy, PartialEq)]
+                pub struct ResolvedOverrideConstants {
+                    pub scale: f32,
+                    pub count: i32,
+                    pub total: i32,
+                    pub enabled: bool
+                }
+
+                impl OverrideConstants {
+                    pub fn resolved(&self) -> ResolvedOverrideConstants {
+                        let scale = self.scale.map(|v| v.into_inner()).unwrap_or(1f32);
+                        let count = self.count;
+                        let total = self.total.unwrap_or_else(|| count * 2i32);
+                        let enabled = self.enabled.unwrap_or_else(|| scale > 0.5f32);
+                        ResolvedOverrideConstants {
+                            scale,
+                            count,
+                            total,
+                            enabled
+                        }
+                    }
+                }
+            },
+            actual
+        );
+    }
+
     #[test]
     fn write_pipeline_overrideable_constants_empty() {
         let source = indoc! {r#"
@@ -0,0 +1,6 @@
+struct Camera {
+    view_dir: vec4<f32>,
+}
+
+@group(0) @binding(0)
+var<uniform> camera: Camera;
@@ -0,0 +1,6 @@
+#ifdef USE_CAMERA
+#include "camera.wgsl"
+#endif
+
+@fragment
+fn fs_main() {}
@@ -0,0 +1,7 @@
+#include "camera.wgsl"
+
+@fragment
+fn fs_main() -> @location(0) vec4<f32> {
+    let scale: f32 = camera.view_dir.xyz;
+    return camera.view_dir * scale;
+}
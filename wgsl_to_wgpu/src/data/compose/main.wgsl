@@ -0,0 +1,7 @@
+#include "camera.wgsl"
+#include "lighting.wgsl"
+
+@fragment
+fn fs_main(@location(0) normal: vec3<f32>) -> @location(0) vec4<f32> {
+    return vec4(lighting(normal), 1.0);
+}
@@ -0,0 +1,2 @@
+#include "camera.wgsl"
+#include "missing_include.wgsl"
@@ -0,0 +1,5 @@
+#include "../camera.wgsl"
+
+fn lighting(normal: vec3<f32>) -> vec3<f32> {
+    return vec3(max(dot(normal, camera.view_dir.xyz), 0.0));
+}
@@ -0,0 +1,74 @@
+#[repr(C)]
+#[derive(Debug, Copy, Clone, PartialEq, encase::ShaderType)]
+pub struct Scalars {
+    pub a: u32,
+    pub b: i32,
+    pub c: f32,
+}
+#[repr(C)]
+#[derive(Debug, Copy, Clone, PartialEq, encase::ShaderType)]
+pub struct VectorsU32 {
+    pub a: cgmath::Vector2<u32>,
+    pub b: cgmath::Vector3<u32>,
+    pub c: cgmath::Vector4<u32>,
+}
+#[repr(C)]
+#[derive(Debug, Copy, Clone, PartialEq, encase::ShaderType)]
+pub struct VectorsI32 {
+    pub a: cgmath::Vector2<i32>,
+    pub b: cgmath::Vector3<i32>,
+    pub c: cgmath::Vector4<i32>,
+}
+#[repr(C)]
+#[derive(Debug, Copy, Clone, PartialEq, encase::ShaderType)]
+pub struct VectorsF32 {
+    pub a: cgmath::Vector2<f32>,
+    pub b: cgmath::Vector3<f32>,
+    pub c: cgmath::Vector4<f32>,
+}
+#[repr(C)]
+#[derive(Debug, Copy, Clone, PartialEq, encase::ShaderType)]
+pub struct VectorsF64 {
+    pub a: cgmath::Vector2<f64>,
+    pub b: cgmath::Vector3<f64>,
+    pub c: cgmath::Vector4<f64>,
+}
+#[repr(C)]
+#[derive(Debug, Copy, Clone, PartialEq, encase::ShaderType)]
+pub struct MatricesF32 {
+    pub a: cgmath::Matrix4<f32>,
+    pub b: [[f32; 4]; 3],
+    pub c: [[f32; 4]; 2],
+    pub d: [[f32; 3]; 4],
+    pub e: cgmath::Matrix3<f32>,
+    pub f: [[f32; 3]; 2],
+    pub g: [[f32; 2]; 4],
+    pub h: [[f32; 2]; 3],
+    pub i: cgmath::Matrix2<f32>,
+}
+#[repr(C)]
+#[derive(Debug, Copy, Clone, PartialEq, encase::ShaderType)]
+pub struct MatricesF64 {
+    pub a: cgmath::Matrix4<f64>,
+    pub b: [[f64; 4]; 3],
+    pub c: [[f64; 4]; 2],
+    pub d: [[f64; 3]; 4],
+    pub e: cgmath::Matrix3<f64>,
+    pub f: [[f64; 3]; 2],
+    pub g: [[f64; 2]; 4],
+    pub h: [[f64; 2]; 3],
+    pub i: cgmath::Matrix2<f64>,
+}
+#[repr(C)]
+#[derive(Debug, Copy, Clone, PartialEq, encase::ShaderType)]
+pub struct StaticArrays {
+    pub a: [u32; 5],
+    pub b: [f32; 3],
+    pub c: [cgmath::Matrix4<f32>; 512],
+}
+#[repr(C)]
+#[derive(Debug, Copy, Clone, PartialEq, encase::ShaderType)]
+pub struct Nested {
+    pub a: MatricesF32,
+    pub b: MatricesF64,
+}
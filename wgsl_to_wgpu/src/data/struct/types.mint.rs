@@ -0,0 +1,74 @@
+#[repr(C)]
+#[derive(Debug, Copy, Clone, PartialEq, encase::ShaderType)]
+pub struct Scalars {
+    pub a: u32,
+    pub b: i32,
+    pub c: f32,
+}
+#[repr(C)]
+#[derive(Debug, Copy, Clone, PartialEq, encase::ShaderType)]
+pub struct VectorsU32 {
+    pub a: mint::Vector2<u32>,
+    pub b: mint::Vector3<u32>,
+    pub c: mint::Vector4<u32>,
+}
+#[repr(C)]
+#[derive(Debug, Copy, Clone, PartialEq, encase::ShaderType)]
+pub struct VectorsI32 {
+    pub a: mint::Vector2<i32>,
+    pub b: mint::Vector3<i32>,
+    pub c: mint::Vector4<i32>,
+}
+#[repr(C)]
+#[derive(Debug, Copy, Clone, PartialEq, encase::ShaderType)]
+pub struct VectorsF32 {
+    pub a: mint::Vector2<f32>,
+    pub b: mint::Vector3<f32>,
+    pub c: mint::Vector4<f32>,
+}
+#[repr(C)]
+#[derive(Debug, Copy, Clone, PartialEq, encase::ShaderType)]
+pub struct VectorsF64 {
+    pub a: mint::Vector2<f64>,
+    pub b: mint::Vector3<f64>,
+    pub c: mint::Vector4<f64>,
+}
+#[repr(C)]
+#[derive(Debug, Copy, Clone, PartialEq, encase::ShaderType)]
+pub struct MatricesF32 {
+    pub a: mint::ColumnMatrix4<f32>,
+    pub b: mint::ColumnMatrix3x4<f32>,
+    pub c: mint::ColumnMatrix2x4<f32>,
+    pub d: mint::ColumnMatrix4x3<f32>,
+    pub e: mint::ColumnMatrix3<f32>,
+    pub f: mint::ColumnMatrix2x3<f32>,
+    pub g: mint::ColumnMatrix4x2<f32>,
+    pub h: mint::ColumnMatrix3x2<f32>,
+    pub i: mint::ColumnMatrix2<f32>,
+}
+#[repr(C)]
+#[derive(Debug, Copy, Clone, PartialEq, encase::ShaderType)]
+pub struct MatricesF64 {
+    pub a: mint::ColumnMatrix4<f64>,
+    pub b: mint::ColumnMatrix3x4<f64>,
+    pub c: mint::ColumnMatrix2x4<f64>,
+    pub d: mint::ColumnMatrix4x3<f64>,
+    pub e: mint::ColumnMatrix3<f64>,
+    pub f: mint::ColumnMatrix2x3<f64>,
+    pub g: mint::ColumnMatrix4x2<f64>,
+    pub h: mint::ColumnMatrix3x2<f64>,
+    pub i: mint::ColumnMatrix2<f64>,
+}
+#[repr(C)]
+#[derive(Debug, Copy, Clone, PartialEq, encase::ShaderType)]
+pub struct StaticArrays {
+    pub a: [u32; 5],
+    pub b: [f32; 3],
+    pub c: [mint::ColumnMatrix4<f32>; 512],
+}
+#[repr(C)]
+#[derive(Debug, Copy, Clone, PartialEq, encase::ShaderType)]
+pub struct Nested {
+    pub a: MatricesF32,
+    pub b: MatricesF64,
+}
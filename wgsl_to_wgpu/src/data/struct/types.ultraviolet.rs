@@ -0,0 +1,74 @@
+#[repr(C)]
+#[derive(Debug, Copy, Clone, PartialEq, encase::ShaderType)]
+pub struct Scalars {
+    pub a: u32,
+    pub b: i32,
+    pub c: f32,
+}
+#[repr(C)]
+#[derive(Debug, Copy, Clone, PartialEq, encase::ShaderType)]
+pub struct VectorsU32 {
+    pub a: ultraviolet::UVec2,
+    pub b: ultraviolet::UVec3,
+    pub c: ultraviolet::UVec4,
+}
+#[repr(C)]
+#[derive(Debug, Copy, Clone, PartialEq, encase::ShaderType)]
+pub struct VectorsI32 {
+    pub a: ultraviolet::IVec2,
+    pub b: ultraviolet::IVec3,
+    pub c: ultraviolet::IVec4,
+}
+#[repr(C)]
+#[derive(Debug, Copy, Clone, PartialEq, encase::ShaderType)]
+pub struct VectorsF32 {
+    pub a: ultraviolet::Vec2,
+    pub b: ultraviolet::Vec3,
+    pub c: ultraviolet::Vec4,
+}
+#[repr(C)]
+#[derive(Debug, Copy, Clone, PartialEq, encase::ShaderType)]
+pub struct VectorsF64 {
+    pub a: ultraviolet::DVec2,
+    pub b: ultraviolet::DVec3,
+    pub c: ultraviolet::DVec4,
+}
+#[repr(C)]
+#[derive(Debug, Copy, Clone, PartialEq, encase::ShaderType)]
+pub struct MatricesF32 {
+    pub a: ultraviolet::Mat4,
+    pub b: [[f32; 4]; 3],
+    pub c: [[f32; 4]; 2],
+    pub d: [[f32; 3]; 4],
+    pub e: ultraviolet::Mat3,
+    pub f: [[f32; 3]; 2],
+    pub g: [[f32; 2]; 4],
+    pub h: [[f32; 2]; 3],
+    pub i: ultraviolet::Mat2,
+}
+#[repr(C)]
+#[derive(Debug, Copy, Clone, PartialEq, encase::ShaderType)]
+pub struct MatricesF64 {
+    pub a: ultraviolet::DMat4,
+    pub b: [[f64; 4]; 3],
+    pub c: [[f64; 4]; 2],
+    pub d: [[f64; 3]; 4],
+    pub e: ultraviolet::DMat3,
+    pub f: [[f64; 3]; 2],
+    pub g: [[f64; 2]; 4],
+    pub h: [[f64; 2]; 3],
+    pub i: ultraviolet::DMat2,
+}
+#[repr(C)]
+#[derive(Debug, Copy, Clone, PartialEq, encase::ShaderType)]
+pub struct StaticArrays {
+    pub a: [u32; 5],
+    pub b: [f32; 3],
+    pub c: [ultraviolet::Mat4; 512],
+}
+#[repr(C)]
+#[derive(Debug, Copy, Clone, PartialEq, encase::ShaderType)]
+pub struct Nested {
+    pub a: MatricesF32,
+    pub b: MatricesF64,
+}
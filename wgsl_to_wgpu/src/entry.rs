@@ -3,31 +3,62 @@ use proc_macro2::{Literal, Span, TokenStream};
 use quote::quote;
 use syn::Ident;
 
-use crate::wgsl::vertex_entry_structs;
+use crate::{reflect::VertexInputReflection, wgsl::vertex_entry_structs, CreateModuleError};
+
+/// A color target written by a fragment entry.
+pub struct FragmentTarget {
+    pub location: u32,
+    /// The struct member name for struct outputs.
+    pub name: Option<String>,
+    pub kind: naga::ScalarKind,
+    /// `true` for the output used as the second source for dual source blending.
+    pub second_blend_source: bool,
+}
+
+pub fn fragment_targets(module: &Module, f: &Function) -> Vec<FragmentTarget> {
+    let scalar_kind = |ty: naga::Handle<naga::Type>| match &module.types[ty].inner {
+        naga::TypeInner::Scalar(scalar) | naga::TypeInner::Vector { scalar, .. } => scalar.kind,
+        _ => naga::ScalarKind::Float,
+    };
 
-pub fn fragment_target_count(module: &Module, f: &Function) -> usize {
     match &f.result {
         Some(r) => match &r.binding {
-            Some(b) => {
-                // Builtins don't have render targets.
-                if matches!(b, naga::Binding::Location { .. }) {
-                    1
-                } else {
-                    0
-                }
-            }
+            // Builtins don't have render targets.
+            Some(naga::Binding::Location {
+                location,
+                second_blend_source,
+                ..
+            }) => vec![FragmentTarget {
+                location: *location,
+                name: None,
+                kind: scalar_kind(r.ty),
+                second_blend_source: *second_blend_source,
+            }],
+            Some(naga::Binding::BuiltIn(_)) => Vec::new(),
             None => {
                 // Fragment functions should return a single variable or a struct.
                 match &module.types[r.ty].inner {
                     naga::TypeInner::Struct { members, .. } => members
                         .iter()
-                        .filter(|m| matches!(m.binding, Some(naga::Binding::Location { .. })))
-                        .count(),
-                    _ => 0,
+                        .filter_map(|m| match m.binding {
+                            Some(naga::Binding::Location {
+                                location,
+                                second_blend_source,
+                                ..
+                            }) => Some(FragmentTarget {
+                                location,
+                                name: m.name.clone(),
+                                kind: scalar_kind(m.ty),
+                                second_blend_source,
+                            }),
+                            _ => None,
+                        })
+                        .collect(),
+                    _ => Vec::new(),
                 }
             }
         },
-        None => 0,
+        None => Vec::new(),
     }
 }
 
@@ -42,69 +73,339 @@ pub fn vertex_inputs(
         .collect()
 }
 
-pub fn entry_point_constants(module: &naga::Module) -> TokenStream {
-    let fragment_state = {
-        let (variants, entries): (Vec<TokenStream>, Vec<TokenStream>) = module
+pub fn entry_point_constants(module: &naga::Module) -> Result<TokenStream, CreateModuleError> {
+    let vertex_entries: Vec<_> = module
+        .entry_points
+        .iter()
+        .filter(|e| e.stage == naga::ShaderStage::Vertex)
+        .collect();
+
+    // Every fragment entry should be usable with at least one vertex entry.
+    if let Some(vertex) = vertex_entries.first() {
+        for fragment in module
             .entry_points
             .iter()
             .filter(|e| e.stage == naga::ShaderStage::Fragment)
-            .map(|e| {
-                let name = e.name.as_str();
-                let variant_name = Ident::new(name, Span::call_site());
-                let num_targets = fragment_target_count(module, &e.function);
-                (
-                    quote! {#variant_name {
-                        targets: [Option<wgpu::ColorTargetState>; #num_targets],
-                    }},
-                    quote! {
-                        Self::#variant_name { targets } => (#name, targets)
-                    },
-                )
-            })
-            .unzip();
+        {
+            if !vertex_entries
+                .iter()
+                .any(|v| interface_mismatch(module, v, fragment).is_none())
+            {
+                return Err(interface_mismatch(module, vertex, fragment).unwrap());
+            }
+        }
+    }
 
-        quote! {
-            #[derive(Clone, Debug, PartialEq, Eq, Hash)]
-            pub enum FragmentEntry {
-                #(#variants),*
-            }
-            impl FragmentEntry {
-                pub fn entry_point_and_targets(&self) -> (&'static str, &[Option<wgpu::ColorTargetState>]) {
-                    match self {
-                        #(#entries,)*
-                        _ => unreachable!(),
+    let mut fragment_states = Vec::new();
+    for vertex in &vertex_entries {
+        let fragments: Vec<_> = compatible_fragment_entries(module, vertex).collect();
+
+        // An empty enum could never be used to create a fragment stage.
+        if !fragments.is_empty() {
+            let enum_name = fragment_entry_name(vertex);
+            fragment_states.push(fragment_entry_enum(module, &enum_name, &fragments));
+        }
+    }
+
+    // Use a shorter name for the common case of a single vertex entry.
+    let fragment_entry_alias = match vertex_entries[..] {
+        [vertex] if !fragment_states.is_empty() => {
+            let enum_name = fragment_entry_name(vertex);
+            quote!(pub type FragmentEntry = #enum_name;)
+        }
+        _ => TokenStream::new(),
+    };
+
+    let target_error = if fragment_states.is_empty() {
+        TokenStream::new()
+    } else {
+        incompatible_target_error()
+    };
+
+    Ok(quote! {
+        #(#fragment_states)*
+        #fragment_entry_alias
+        #target_error
+    })
+}
+
+/// An enum with a variant containing the color targets for each entry in `fragments`.
+fn fragment_entry_enum(
+    module: &naga::Module,
+    enum_name: &Ident,
+    fragments: &[&naga::EntryPoint],
+) -> TokenStream {
+    let mut variants = Vec::new();
+    let mut entries = Vec::new();
+    let mut sample_types = Vec::new();
+    let mut target_indices = Vec::new();
+    for e in fragments {
+        let name = e.name.as_str();
+        let variant_name = Ident::new(name, Span::call_site());
+        let mut targets = fragment_targets(module, &e.function);
+        targets.sort_by_key(|t| (t.location, t.second_blend_source));
+
+        // Targets are indexed by location, so sparse locations have unused targets.
+        // The second blend source shares the target of the first blend source.
+        let num_targets = targets
+            .iter()
+            .map(|t| t.location as usize + 1)
+            .max()
+            .unwrap_or_default();
+
+        let docs = targets.iter().map(|target| {
+            let location = target.location;
+            let attributes = if target.second_blend_source {
+                format!("@location({location}) @second_blend_source")
+            } else {
+                format!("@location({location})")
+            };
+            let output = match &target.name {
+                Some(member) => format!("{attributes} {member}"),
+                None => attributes,
+            };
+            let doc = if target.second_blend_source {
+                format!(" - `{location}`: `{output}` is the second source for dual source blending")
+            } else {
+                let kind = scalar_kind_description(target.kind);
+                format!(" - `{location}`: `{output}` requires a format with {kind} values")
+            };
+            quote!(#[doc = #doc])
+        });
+        variants.push(quote! {
+            #[doc = " The color targets for each fragment output:"]
+            #(#docs)*
+            #variant_name {
+                targets: [Option<wgpu::ColorTargetState>; #num_targets],
+            }
+        });
+        entries.push(quote! {
+            Self::#variant_name { targets } => (#name, targets)
+        });
+
+        let types = (0..num_targets).map(|i| {
+            match targets
+                .iter()
+                .find(|t| t.location as usize == i && !t.second_blend_source)
+            {
+                Some(target) => {
+                    let sample_type = sample_type(target.kind);
+                    quote!(Some(#sample_type))
+                }
+                None => quote!(None),
+            }
+        });
+        sample_types.push(quote! {
+            Self::#variant_name { .. } => &[#(#types),*]
+        });
+
+        for target in targets.iter().filter(|t| !t.second_blend_source) {
+            if let Some(member) = &target.name {
+                let i = target.location as usize;
+                let index_name = Ident::new(
+                    &format!("{name}_{member}").to_uppercase(),
+                    Span::call_site(),
+                );
+                let doc = format!(" The index in `targets` for `{name}` output `{member}`.");
+                target_indices.push(quote! {
+                    #[doc = #doc]
+                    pub const #index_name: usize = #i;
+                });
+            }
+        }
+    }
+
+    quote! {
+        #[derive(Clone, Debug, PartialEq, Eq, Hash)]
+        pub enum #enum_name {
+            #(#variants),*
+        }
+        impl #enum_name {
+            #(#target_indices)*
+
+            pub fn entry_point_and_targets(&self) -> (&'static str, &[Option<wgpu::ColorTargetState>]) {
+                match self {
+                    #(#entries,)*
+                    _ => unreachable!(),
+                }
+            }
+
+            /// Check that each target format can store the scalar type of its fragment output.
+            ///
+            /// Returns an error if a float output targets an integer format
+            /// or an integer output targets a format without the same integer sample type.
+            pub fn validate_targets(&self) -> Result<(), IncompatibleTargetError> {
+                let (entry_point, targets) = self.entry_point_and_targets();
+                let sample_types: &[Option<wgpu::TextureSampleType>] = match self {
+                    #(#sample_types,)*
+                    _ => unreachable!(),
+                };
+                for (i, (target, expected)) in targets.iter().zip(sample_types).enumerate() {
+                    if let (Some(target), Some(expected)) = (target, expected) {
+                        let compatible = matches!(
+                            (target.format.sample_type(None, None), expected),
+                            (Some(wgpu::TextureSampleType::Float { .. }), wgpu::TextureSampleType::Float { .. })
+                                | (Some(wgpu::TextureSampleType::Sint), wgpu::TextureSampleType::Sint)
+                                | (Some(wgpu::TextureSampleType::Uint), wgpu::TextureSampleType::Uint)
+                        );
+                        if !compatible {
+                            return Err(IncompatibleTargetError {
+                                entry_point,
+                                target: i,
+                                format: target.format,
+                                expected: *expected,
+                            });
+                        }
                     }
                 }
+                Ok(())
             }
         }
-    };
+    }
+}
 
+fn incompatible_target_error() -> TokenStream {
     quote! {
-        // #vertex_state
-        #fragment_state
+        /// A color target format that can't store the scalar type of its fragment output.
+        #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+        pub struct IncompatibleTargetError {
+            pub entry_point: &'static str,
+            pub target: usize,
+            pub format: wgpu::TextureFormat,
+            pub expected: wgpu::TextureSampleType,
+        }
+
+        impl std::fmt::Display for IncompatibleTargetError {
+            fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                write!(
+                    f,
+                    "target {} for {} has format {:?} which is not compatible with {:?} outputs",
+                    self.target, self.entry_point, self.format, self.expected
+                )
+            }
+        }
+
+        impl std::error::Error for IncompatibleTargetError {}
+    }
+}
+
+fn scalar_kind_description(kind: naga::ScalarKind) -> &'static str {
+    match kind {
+        naga::ScalarKind::Sint => "signed integer",
+        naga::ScalarKind::Uint => "unsigned integer",
+        _ => "float",
+    }
+}
+
+fn sample_type(kind: naga::ScalarKind) -> TokenStream {
+    match kind {
+        naga::ScalarKind::Sint => quote!(wgpu::TextureSampleType::Sint),
+        naga::ScalarKind::Uint => quote!(wgpu::TextureSampleType::Uint),
+        _ => quote!(wgpu::TextureSampleType::Float { filterable: false }),
+    }
+}
+
+/// The type for the fragment entries that can be paired with `vertex`.
+pub fn fragment_entry_name(vertex: &naga::EntryPoint) -> Ident {
+    Ident::new(&format!("FragmentEntry_{}", vertex.name), Span::call_site())
+}
+
+pub fn compatible_fragment_entries<'a>(
+    module: &'a naga::Module,
+    vertex: &'a naga::EntryPoint,
+) -> impl Iterator<Item = &'a naga::EntryPoint> {
+    module
+        .entry_points
+        .iter()
+        .filter(|e| e.stage == naga::ShaderStage::Fragment)
+        .filter(move |e| interface_mismatch(module, vertex, e).is_none())
+}
+
+/// Check that the vertex outputs provide every location read by the fragment inputs.
+fn interface_mismatch(
+    module: &naga::Module,
+    vertex: &naga::EntryPoint,
+    fragment: &naga::EntryPoint,
+) -> Option<CreateModuleError> {
+    let mut outputs = Vec::new();
+    if let Some(result) = &vertex.function.result {
+        add_location_bindings(&mut outputs, module, result.ty, result.binding.as_ref());
     }
+
+    let mut inputs = Vec::new();
+    for argument in &fragment.function.arguments {
+        add_location_bindings(&mut inputs, module, argument.ty, argument.binding.as_ref());
+    }
+
+    inputs.into_iter().find_map(|(location, input)| {
+        let error = match outputs.iter().find(|(l, _)| *l == location) {
+            Some((_, output)) if *output == input => return None,
+            Some(_) => CreateModuleError::MismatchedFragmentInput {
+                vertex: vertex.name.clone(),
+                fragment: fragment.name.clone(),
+                location,
+            },
+            None => CreateModuleError::MissingFragmentInput {
+                vertex: vertex.name.clone(),
+                fragment: fragment.name.clone(),
+                location,
+            },
+        };
+        Some(error)
+    })
 }
 
-pub fn vertex_struct_methods(module: &naga::Module) -> TokenStream {
-    let structs = vertex_input_structs(module);
+/// The type and interpolation for each location in a struct or single binding.
+type LocationBinding<'a> = (
+    &'a naga::TypeInner,
+    Option<naga::Interpolation>,
+    Option<naga::Sampling>,
+);
+
+fn add_location_bindings<'a>(
+    bindings: &mut Vec<(u32, LocationBinding<'a>)>,
+    module: &'a naga::Module,
+    ty: naga::Handle<naga::Type>,
+    binding: Option<&naga::Binding>,
+) {
+    match binding {
+        Some(naga::Binding::Location {
+            location,
+            interpolation,
+            sampling,
+            ..
+        }) => bindings.push((
+            *location,
+            (&module.types[ty].inner, *interpolation, *sampling),
+        )),
+        Some(naga::Binding::BuiltIn(_)) => (),
+        None => {
+            if let naga::TypeInner::Struct { members, .. } = &module.types[ty].inner {
+                for member in members {
+                    add_location_bindings(bindings, module, member.ty, member.binding.as_ref());
+                }
+            }
+        }
+    }
+}
+
+pub fn vertex_struct_methods(vertex_inputs: &[VertexInputReflection]) -> TokenStream {
+    let structs = vertex_input_structs(vertex_inputs);
     quote!(#(#structs)*)
 }
 
-fn vertex_input_structs(module: &naga::Module) -> Vec<TokenStream> {
-    let vertex_inputs = crate::wgsl::get_vertex_input_structs(module);
+fn vertex_input_structs(vertex_inputs: &[VertexInputReflection]) -> Vec<TokenStream> {
     vertex_inputs.iter().map(|input|  {
-        let name = &input.type_name;
+        let name = Ident::new(&input.name, Span::call_site());
 
-        let count = Literal::usize_unsuffixed(input.fields.len());
+        let count = Literal::usize_unsuffixed(input.attributes.len());
         let attributes: Vec<_> = input
-            .fields
+            .attributes
             .iter()
-            .map(|(location, m)| {
-                let field_name: TokenStream = m.name.as_ref().unwrap().parse().unwrap();
-                let location = Literal::usize_unsuffixed(*location as usize);
-                let format = crate::wgsl::vertex_format(&module.types[m.ty]);
-                // TODO: Will the debug implementation always work with the macro?
-                let format = Ident::new(&format!("{format:?}"), Span::call_site());
+            .map(|attribute| {
+                let field_name: TokenStream = attribute.name.parse().unwrap();
+                let location = Literal::usize_unsuffixed(attribute.location as usize);
+                let format = Ident::new(&format!("{:?}", attribute.format), Span::call_site());
 
                 quote! {
                     wgpu::VertexAttribute {
@@ -144,8 +445,447 @@ fn vertex_input_structs(module: &naga::Module) -> Vec<TokenStream> {
 
 #[cfg(test)]
 mod test {
-    
+    use super::*;
+
+    use crate::assert_tokens_eq;
+    use indoc::indoc;
+
+    #[test]
+    fn write_fragment_entries() {
+        let source = indoc! {r#"
+            struct FragmentOutput {
+                @location(0) color: vec4<f32>,
+                @location(1) id: u32,
+            }
 
-    
-    
+            @vertex
+            fn vs_main() -> @builtin(position) vec4<f32> {
+                return vec4(0.0);
+            }
+
+            @fragment
+            fn fs_main() -> FragmentOutput {
+                return FragmentOutput(vec4(0.0), 0u);
+            }
+
+            @fragment
+            fn fs_single() -> @location(0) vec4<i32> {
+                return vec4(0);
+            }
+        "#};
+
+        let module = naga::front::wgsl::parse_str(source).unwrap();
+        let actual = entry_point_constants(&module).unwrap();
+        let target_error = incompatible_target_error();
+
+        assert_tokens_eq!(
+            quote! {
+                #[derive(Clone, Debug, PartialEq, Eq, Hash)]
+                pub enum FragmentEntry_vs_main {
+                    #[doc = " The color targets for each fragment output:"]
+                    #[doc = " - `0`: `@location(0) color` requires a format with float values"]
+                    #[doc = " - `1`: `@location(1) id` requires a format with unsigned integer values"]
+                    fs_main {
+                        targets: [Option<wgpu::ColorTargetState>; 2usize],
+                    },
+                    #[doc = " The color targets for each fragment output:"]
+                    #[doc = " - `0`: `@location(0)` requires a format with signed integer values"]
+                    fs_single {
+                        targets: [Option<wgpu::ColorTargetState>; 1usize],
+                    }
+                }
+                impl FragmentEntry_vs_main {
+                    #[doc = " The index in `targets` for `fs_main` output `color`."]
+                    pub const FS_MAIN_COLOR: usize = 0usize;
+                    #[doc = " The index in `targets` for `fs_main` output `id`."]
+                    pub const FS_MAIN_ID: usize = 1usize;
+
+                    pub fn entry_point_and_targets(&self) -> (&'static str, &[Option<wgpu::ColorTargetState>]) {
+                        match self {
+                            Self::fs_main { targets } => ("fs_main", targets),
+                            Self::fs_single { targets } => ("fs_single", targets),
+                            _ => unreachable!(),
+                        }
+                    }
+
+                    /// Check that each target format can store the scalar type of its fragment output.
+                    ///
+                    /// Returns an error if a float output targets an integer format
+                    /// or an integer output targets a format without the same integer sample type.
+                    pub fn validate_targets(&self) -> Result<(), IncompatibleTargetError> {
+                        let (entry_point, targets) = self.entry_point_and_targets();
+                        let sample_types: &[Option<wgpu::TextureSampleType>] = match self {
+                            Self::fs_main { .. } => &[
+                                Some(wgpu::TextureSampleType::Float { filterable: false }),
+                                Some(wgpu::TextureSampleType::Uint)
+                            ],
+                            Self::fs_single { .. } => &[Some(wgpu::TextureSampleType::Sint)],
+                            _ => unreachable!(),
+                        };
+                        for (i, (target, expected)) in targets.iter().zip(sample_types).enumerate() {
+                            if let (Some(target), Some(expected)) = (target, expected) {
+                                let compatible = matches!(
+                                    (target.format.sample_type(None, None), expected),
+                                    (Some(wgpu::TextureSampleType::Float { .. }), wgpu::TextureSampleType::Float { .. })
+                                        | (Some(wgpu::TextureSampleType::Sint), wgpu::TextureSampleType::Sint)
+                                        | (Some(wgpu::TextureSampleType::Uint), wgpu::TextureSampleType::Uint)
+                                );
+                                if !compatible {
+                                    return Err(IncompatibleTargetError {
+                                        entry_point,
+                                        target: i,
+                                        format: target.format,
+                                        expected: *expected,
+                                    });
+                                }
+                            }
+                        }
+                        Ok(())
+                    }
+                }
+                pub type FragmentEntry = FragmentEntry_vs_main;
+                #target_error
+            },
+            actual
+        );
+    }
+
+    #[test]
+    fn write_fragment_entries_vertex_only() {
+        let source = indoc! {r#"
+            @vertex
+            fn vs_main() -> @builtin(position) vec4<f32> {
+                return vec4(0.0);
+            }
+        "#};
+
+        let module = naga::front::wgsl::parse_str(source).unwrap();
+        let actual = entry_point_constants(&module).unwrap();
+
+        assert_tokens_eq!(quote!(), actual);
+    }
+
+    #[test]
+    fn write_fragment_entries_sparse_dual_source() {
+        let source = indoc! {r#"
+            struct SparseOutput {
+                @location(2) normal: vec4<f32>,
+                @location(0) color: vec4<f32>,
+            }
+
+            struct DualSourceOutput {
+                @location(0) color: vec4<f32>,
+                @location(0) @second_blend_source blend: vec4<f32>,
+            }
+
+            @vertex
+            fn vs_main() -> @builtin(position) vec4<f32> {
+                return vec4(0.0);
+            }
+
+            @fragment
+            fn fs_sparse() -> SparseOutput {
+                return SparseOutput(vec4(0.0), vec4(1.0));
+            }
+
+            @fragment
+            fn fs_dual() -> DualSourceOutput {
+                return DualSourceOutput(vec4(0.0), vec4(1.0));
+            }
+        "#};
+
+        let module = naga::front::wgsl::parse_str(source).unwrap();
+        let actual = entry_point_constants(&module).unwrap();
+        let target_error = incompatible_target_error();
+
+        assert_tokens_eq!(
+            quote! {
+                #[derive(Clone, Debug, PartialEq, Eq, Hash)]
+                pub enum FragmentEntry_vs_main {
+                    #[doc = " The color targets for each fragment output:"]
+                    #[doc = " - `0`: `@location(0) color` requires a format with float values"]
+                    #[doc = " - `2`: `@location(2) normal` requires a format with float values"]
+                    fs_sparse {
+                        targets: [Option<wgpu::ColorTargetState>; 3usize],
+                    },
+                    #[doc = " The color targets for each fragment output:"]
+                    #[doc = " - `0`: `@location(0) color` requires a format with float values"]
+                    #[doc = " - `0`: `@location(0) @second_blend_source blend` is the second source for dual source blending"]
+                    fs_dual {
+                        targets: [Option<wgpu::ColorTargetState>; 1usize],
+                    }
+                }
+                impl FragmentEntry_vs_main {
+                    #[doc = " The index in `targets` for `fs_sparse` output `color`."]
+                    pub const FS_SPARSE_COLOR: usize = 0usize;
+                    #[doc = " The index in `targets` for `fs_sparse` output `normal`."]
+                    pub const FS_SPARSE_NORMAL: usize = 2usize;
+                    #[doc = " The index in `targets` for `fs_dual` output `color`."]
+                    pub const FS_DUAL_COLOR: usize = 0usize;
+
+                    pub fn entry_point_and_targets(&self) -> (&'static str, &[Option<wgpu::ColorTargetState>]) {
+                        match self {
+                            Self::fs_sparse { targets } => ("fs_sparse", targets),
+                            Self::fs_dual { targets } => ("fs_dual", targets),
+                            _ => unreachable!(),
+                        }
+                    }
+
+                    /// Check that each target format can store the scalar type of its fragment output.
+                    ///
+                    /// Returns an error if a float output targets an integer format
+                    /// or an integer output targets a format without the same integer sample type.
+                    pub fn validate_targets(&self) -> Result<(), IncompatibleTargetError> {
+                        let (entry_point, targets) = self.entry_point_and_targets();
+                        let sample_types: &[Option<wgpu::TextureSampleType>] = match self {
+                            Self::fs_sparse { .. } => &[
+                                Some(wgpu::TextureSampleType::Float { filterable: false }),
+                                None,
+                                Some(wgpu::TextureSampleType::Float { filterable: false })
+                            ],
+                            Self::fs_dual { .. } => &[Some(wgpu::TextureSampleType::Float { filterable: false })],
+                            _ => unreachable!(),
+                        };
+                        for (i, (target, expected)) in targets.iter().zip(sample_types).enumerate() {
+                            if let (Some(target), Some(expected)) = (target, expected) {
+                                let compatible = matches!(
+                                    (target.format.sample_type(None, None), expected),
+                                    (Some(wgpu::TextureSampleType::Float { .. }), wgpu::TextureSampleType::Float { .. })
+                                        | (Some(wgpu::TextureSampleType::Sint), wgpu::TextureSampleType::Sint)
+                                        | (Some(wgpu::TextureSampleType::Uint), wgpu::TextureSampleType::Uint)
+                                );
+                                if !compatible {
+                                    return Err(IncompatibleTargetError {
+                                        entry_point,
+                                        target: i,
+                                        format: target.format,
+                                        expected: *expected,
+                                    });
+                                }
+                            }
+                        }
+                        Ok(())
+                    }
+                }
+                pub type FragmentEntry = FragmentEntry_vs_main;
+                #target_error
+            },
+            actual
+        );
+    }
+
+    #[test]
+    fn write_fragment_entries_per_vertex_entry() {
+        let source = indoc! {r#"
+            struct ColorOutput {
+                @builtin(position) position: vec4<f32>,
+                @location(0) color: vec4<f32>,
+            }
+
+            @vertex
+            fn vs_color() -> ColorOutput {
+                return ColorOutput(vec4(0.0), vec4(1.0));
+            }
+
+            @vertex
+            fn vs_depth() -> @builtin(position) vec4<f32> {
+                return vec4(0.0);
+            }
+
+            @fragment
+            fn fs_color(@location(0) color: vec4<f32>) -> @location(0) vec4<f32> {
+                return color;
+            }
+
+            @fragment
+            fn fs_white() -> @location(0) vec4<f32> {
+                return vec4(1.0);
+            }
+        "#};
+
+        let module = naga::front::wgsl::parse_str(source).unwrap();
+        let actual = entry_point_constants(&module).unwrap();
+        let target_error = incompatible_target_error();
+
+        assert_tokens_eq!(
+            quote! {
+                #[derive(Clone, Debug, PartialEq, Eq, Hash)]
+                pub enum FragmentEntry_vs_color {
+                    #[doc = " The color targets for each fragment output:"]
+                    #[doc = " - `0`: `@location(0)` requires a format with float values"]
+                    fs_color {
+                        targets: [Option<wgpu::ColorTargetState>; 1usize],
+                    },
+                    #[doc = " The color targets for each fragment output:"]
+                    #[doc = " - `0`: `@location(0)` requires a format with float values"]
+                    fs_white {
+                        targets: [Option<wgpu::ColorTargetState>; 1usize],
+                    }
+                }
+                impl FragmentEntry_vs_color {
+                    pub fn entry_point_and_targets(&self) -> (&'static str, &[Option<wgpu::ColorTargetState>]) {
+                        match self {
+                            Self::fs_color { targets } => ("fs_color", targets),
+                            Self::fs_white { targets } => ("fs_white", targets),
+                            _ => unreachable!(),
+                        }
+                    }
+
+                    /// Check that each target format can store the scalar type of its fragment output.
+                    ///
+                    /// Returns an error if a float output targets an integer format
+                    /// or an integer output targets a format without the same integer sample type.
+                    pub fn validate_targets(&self) -> Result<(), IncompatibleTargetError> {
+                        let (entry_point, targets) = self.entry_point_and_targets();
+                        let sample_types: &[Option<wgpu::TextureSampleType>] = match self {
+                            Self::fs_color { .. } => &[Some(wgpu::TextureSampleType::Float { filterable: false })],
+                            Self::fs_white { .. } => &[Some(wgpu::TextureSampleType::Float { filterable: false })],
+                            _ => unreachable!(),
+                        };
+                        for (i, (target, expected)) in targets.iter().zip(sample_types).enumerate() {
+                            if let (Some(target), Some(expected)) = (target, expected) {
+                                let compatible = matches!(
+                                    (target.format.sample_type(None, None), expected),
+                                    (Some(wgpu::TextureSampleType::Float { .. }), wgpu::TextureSampleType::Float { .. })
+                                        | (Some(wgpu::TextureSampleType::Sint), wgpu::TextureSampleType::Sint)
+                                        | (Some(wgpu::TextureSampleType::Uint), wgpu::TextureSampleType::Uint)
+                                );
+                                if !compatible {
+                                    return Err(IncompatibleTargetError {
+                                        entry_point,
+                                        target: i,
+                                        format: target.format,
+                                        expected: *expected,
+                                    });
+                                }
+                            }
+                        }
+                        Ok(())
+                    }
+                }
+                #[derive(Clone, Debug, PartialEq, Eq, Hash)]
+                pub enum FragmentEntry_vs_depth {
+                    #[doc = " The color targets for each fragment output:"]
+                    #[doc = " - `0`: `@location(0)` requires a format with float values"]
+                    fs_white {
+                        targets: [Option<wgpu::ColorTargetState>; 1usize],
+                    }
+                }
+                impl FragmentEntry_vs_depth {
+                    pub fn entry_point_and_targets(&self) -> (&'static str, &[Option<wgpu::ColorTargetState>]) {
+                        match self {
+                            Self::fs_white { targets } => ("fs_white", targets),
+                            _ => unreachable!(),
+                        }
+                    }
+
+                    /// Check that each target format can store the scalar type of its fragment output.
+                    ///
+                    /// Returns an error if a float output targets an integer format
+                    /// or an integer output targets a format without the same integer sample type.
+                    pub fn validate_targets(&self) -> Result<(), IncompatibleTargetError> {
+                        let (entry_point, targets) = self.entry_point_and_targets();
+                        let sample_types: &[Option<wgpu::TextureSampleType>] = match self {
+                            Self::fs_white { .. } => &[Some(wgpu::TextureSampleType::Float { filterable: false })],
+                            _ => unreachable!(),
+                        };
+                        for (i, (target, expected)) in targets.iter().zip(sample_types).enumerate() {
+                            if let (Some(target), Some(expected)) = (target, expected) {
+                                let compatible = matches!(
+                                    (target.format.sample_type(None, None), expected),
+                                    (Some(wgpu::TextureSampleType::Float { .. }), wgpu::TextureSampleType::Float { .. })
+                                        | (Some(wgpu::TextureSampleType::Sint), wgpu::TextureSampleType::Sint)
+                                        | (Some(wgpu::TextureSampleType::Uint), wgpu::TextureSampleType::Uint)
+                                );
+                                if !compatible {
+                                    return Err(IncompatibleTargetError {
+                                        entry_point,
+                                        target: i,
+                                        format: target.format,
+                                        expected: *expected,
+                                    });
+                                }
+                            }
+                        }
+                        Ok(())
+                    }
+                }
+                #target_error
+            },
+            actual
+        );
+    }
+
+    #[test]
+    fn write_fragment_entries_fragment_only() {
+        let source = indoc! {r#"
+            @fragment
+            fn fs_main() -> @location(0) vec4<f32> {
+                return vec4(1.0);
+            }
+        "#};
+
+        let module = naga::front::wgsl::parse_str(source).unwrap();
+        let actual = entry_point_constants(&module).unwrap().to_string();
+
+        // Fragment entries can only be used in render pipelines with a vertex entry.
+        assert!(actual.is_empty(), "{actual}");
+    }
+
+    #[test]
+    fn write_fragment_entries_missing_input() {
+        let source = indoc! {r#"
+            @vertex
+            fn vs_main() -> @builtin(position) vec4<f32> {
+                return vec4(0.0);
+            }
+
+            @fragment
+            fn fs_main(@location(1) uv: vec2<f32>) -> @location(0) vec4<f32> {
+                return vec4(uv, 0.0, 1.0);
+            }
+        "#};
+
+        let module = naga::front::wgsl::parse_str(source).unwrap();
+
+        assert_eq!(
+            Err(CreateModuleError::MissingFragmentInput {
+                vertex: "vs_main".to_owned(),
+                fragment: "fs_main".to_owned(),
+                location: 1
+            }),
+            entry_point_constants(&module).map(|t| t.to_string())
+        );
+    }
+
+    #[test]
+    fn write_fragment_entries_mismatched_input() {
+        let source = indoc! {r#"
+            struct VertexOutput {
+                @builtin(position) position: vec4<f32>,
+                @location(0) @interpolate(flat) id: u32,
+            }
+
+            @vertex
+            fn vs_main() -> VertexOutput {
+                return VertexOutput(vec4(0.0), 0u);
+            }
+
+            @fragment
+            fn fs_main(@location(0) id: f32) -> @location(0) vec4<f32> {
+                return vec4(id);
+            }
+        "#};
+
+        let module = naga::front::wgsl::parse_str(source).unwrap();
+
+        assert_eq!(
+            Err(CreateModuleError::MismatchedFragmentInput {
+                vertex: "vs_main".to_owned(),
+                fragment: "fs_main".to_owned(),
+                location: 0
+            }),
+            entry_point_constants(&module).map(|t| t.to_string())
+        );
+    }
 }
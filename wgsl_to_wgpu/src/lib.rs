@@ -33,7 +33,9 @@
 extern crate wgpu_types as wgpu;
 
 use std::{
+    borrow::Cow,
     io::Write,
+    path::{Path, PathBuf},
     process::{Command, Stdio},
 };
 
@@ -46,12 +48,25 @@ use syn::Ident;
 use thiserror::Error;
 
 mod bindgroup;
+pub mod build;
+mod compose;
 mod consts;
 mod entry;
 mod structs;
 mod shader;
 mod wgsl;
+mod permutation;
 mod pipeline_layout;
+pub mod reflect;
+mod translate;
+#[cfg(feature = "verify")]
+mod verify;
+
+pub use build::Builder;
+pub use compose::ComposedSource;
+pub use reflect::{reflect, ShaderReflection};
+#[cfg(feature = "verify")]
+pub use verify::{verify, Diff};
 
 /// Errors while generating Rust source for a WGSl shader module.
 #[derive(Debug, PartialEq, Eq, Error)]
@@ -65,11 +80,91 @@ pub enum CreateModuleError {
     /// Each binding resource must be associated with exactly one binding index.
     #[error("duplicate binding found with index `{binding}`")]
     DuplicateBinding { binding: u32 },
+
+    /// Each fragment entry must have its inputs provided by at least one vertex entry.
+    #[error("fragment entry `{fragment}` reads location {location} that is not written by vertex entry `{vertex}`")]
+    MissingFragmentInput {
+        vertex: String,
+        fragment: String,
+        location: u32,
+    },
+
+    /// Vertex outputs and fragment inputs with the same location must have the same type and interpolation.
+    #[error("fragment entry `{fragment}` reads location {location} with a different type or interpolation than vertex entry `{vertex}`")]
+    MismatchedFragmentInput {
+        vertex: String,
+        fragment: String,
+        location: u32,
+    },
+
+    /// The shader source must be parsed successfully by naga.
+    #[error("failed to parse the shader: {message}")]
+    ParseError { message: String },
+
+    /// Each shader file and included file must be readable.
+    #[error("failed to read {}: {message}", path.display())]
+    ReadError { path: PathBuf, message: String },
+
+    /// Preprocessor directives like `#include` must be valid and resolve to existing files.
+    #[error("{}:{line}: {message}", path.display())]
+    PreprocessError {
+        path: PathBuf,
+        line: usize,
+        message: String,
+    },
+
+    /// The module must pass naga validation with [WriteOptions::validation_flags]
+    /// and [WriteOptions::capabilities].
+    #[error("shader validation failed: {message}")]
+    ValidationError { message: String },
+
+    /// Structs with a runtime-sized array field require [WriteOptions::derive_encase_host_shareable]
+    /// and can't derive bytemuck.
+    #[error("struct `{name}` is not supported: {message}")]
+    UnsupportedRuntimeArray { name: String, message: String },
+
+    /// Struct members using [WriteOptions::matrix_vector_types] must match the WGSL memory layout
+    /// of host shareable structs and implement any derived traits.
+    #[error("struct member `{name}` is not supported: {message}")]
+    UnsupportedMatrixVectorType { name: String, message: String },
+
+    /// Each type in [WriteOptions::type_overrides] must be a non vertex input struct
+    /// with a valid Rust type path.
+    #[error("invalid type override for `{name}`: {message}")]
+    InvalidTypeOverride { name: String, message: String },
+
+    /// Each vertex input attribute must have a type supported by [wgpu::VertexFormat].
+    #[error("vertex attribute `{attribute}` of type `{ty}` has no matching vertex format")]
+    UnsupportedVertexFormat { attribute: String, ty: String },
+
+    /// Abstract numeric constants must be representable by their concrete type.
+    #[error("invalid constant `{name}`: {message}")]
+    InvalidConstant { name: String, message: String },
+
+    /// [ShaderSourceMode::IncludeStr] includes the original file,
+    /// so the source can't be changed by defines, `#include`, or conditional directives.
+    #[error("ShaderSourceMode::IncludeStr can't be used with {message}")]
+    IncludeStrPreprocessed { message: String },
+
+    /// Each [Permutation] must have a unique name that is a valid Rust module name.
+    #[error("invalid permutation `{name}`: {message}")]
+    InvalidPermutation { name: String, message: String },
+
+    /// Each binding in [WriteOptions::dynamic_bindings] must be a uniform or storage buffer binding.
+    #[error("`{name}` is not a uniform or storage buffer binding")]
+    InvalidDynamicBinding { name: String },
+
+    /// The module must be supported by the naga backend for each language in [WriteOptions::translations].
+    #[error("failed to translate the shader to {language}: {message}")]
+    TranslationError {
+        language: &'static str,
+        message: String,
+    },
 }
 
 /// Options for configuring the generated bindings to work with additional dependencies.
 /// Use [WriteOptions::default] for only requiring WGPU itself.
-#[derive(Debug, Copy, Clone, PartialEq, Eq, Default)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct WriteOptions {
     /// Derive [bytemuck::Pod](https://docs.rs/bytemuck/latest/bytemuck/trait.Pod.html#)
     /// and [bytemuck::Zeroable](https://docs.rs/bytemuck/latest/bytemuck/trait.Zeroable.html#)
@@ -100,6 +195,55 @@ pub struct WriteOptions {
     /// The format to use for matrix and vector types.
     pub matrix_vector_types: MatrixVectorTypes,
 
+    /// Generate an untyped macro like `constant_macros::INT_CONST!()` for each integer and float constant
+    /// in addition to the typed Rust constant when `true`.
+    /// The macro expands to an unsuffixed literal, so Rust infers its type from where it's used.
+    /// The typed constants always use the declared type or the default `i32` or `f32` for abstract WGSL constants
+    /// and don't depend on how the constant is used in the shader.
+    pub constant_macros: bool,
+
+    /// The naga validation checks to run before generating any code.
+    /// Defaults to [naga::valid::ValidationFlags::all].
+    pub validation_flags: naga::valid::ValidationFlags,
+
+    /// The shader capabilities allowed during validation.
+    /// Defaults to [naga::valid::Capabilities::all].
+    /// Restrict this to the capabilities of the target devices
+    /// to report unsupported shaders when generating code instead of when creating the shader module.
+    pub capabilities: naga::valid::Capabilities,
+
+    /// The WGSL source used for `Shader::SOURCE` when creating the shader module.
+    /// GLSL and SPIR-V shaders always embed the original source.
+    pub source_mode: ShaderSourceMode,
+
+    /// Defines for conditional directives in WGSL sources as pairs of names and values.
+    ///
+    /// Lines are only included if the enclosing conditional directives evaluate to true.
+    /// The supported directives are `#ifdef NAME`, `#ifndef NAME`, `#else`, `#endif`,
+    /// and `#if` with `NAME`, `NAME == value`, or `NAME != value`.
+    /// `#if NAME` is true if `NAME` is defined with any value other than `0` or `false`.
+    ///
+    /// [ShaderSourceMode::Verbatim] embeds the source after applying the directives.
+    /// [ShaderSourceMode::IncludeStr] includes the original file and does not support directives.
+    pub defines: Vec<(String, String)>,
+
+    /// The languages to translate the shader to when generating the bindings.
+    /// This catches backend translation errors at build time.
+    pub translations: Translations,
+
+    /// Rust types to use instead of generating structs as pairs of WGSL struct names
+    /// and Rust type paths like `("Camera", "crate::camera::Camera")`.
+    ///
+    /// The generated code defines a type alias with the struct name, so the Rust type
+    /// must match the WGSL memory layout and implement any traits required by the other options.
+    /// The size of the Rust type is checked at compile time for [WriteOptions::derive_bytemuck_host_shareable].
+    /// Vertex input structs can't be overridden.
+    pub type_overrides: Vec<(String, String)>,
+
+    /// The names of uniform and storage buffer bindings that use dynamic offsets.
+    /// Setting a bind group with dynamic bindings requires the offsets in binding order.
+    pub dynamic_bindings: Vec<String>,
+
     // TODO: Remove this and all text output. The current obstacle is testing. Instead, let's just always parse the golden files and compare token streams.
     //
     /// Format the generated code with the `rustfmt` formatter used for `cargo fmt`.
@@ -110,6 +254,27 @@ pub struct WriteOptions {
     pub rustfmt: bool,
 }
 
+impl Default for WriteOptions {
+    fn default() -> Self {
+        Self {
+            derive_bytemuck_vertex: false,
+            derive_bytemuck_host_shareable: false,
+            derive_encase_host_shareable: false,
+            derive_serde: false,
+            matrix_vector_types: MatrixVectorTypes::default(),
+            constant_macros: false,
+            validation_flags: naga::valid::ValidationFlags::all(),
+            capabilities: naga::valid::Capabilities::all(),
+            source_mode: ShaderSourceMode::default(),
+            defines: Vec::new(),
+            translations: Translations::default(),
+            type_overrides: Vec::new(),
+            dynamic_bindings: Vec::new(),
+            rustfmt: false,
+        }
+    }
+}
+
 /// The format to use for matrix and vector types.
 /// Note that the generated types for the same WGSL type may differ in size or alignment.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -123,6 +288,23 @@ pub enum MatrixVectorTypes {
 
     /// `nalgebra` types like `nalgebra::SVector<f64, 4>` or `nalgebra::SMatrix<f32, 2, 3>`.
     Nalgebra,
+
+    /// `mint` types like `mint::Vector4<f32>` or `mint::ColumnMatrix2x3<f32>`.
+    /// This is intended for interop at API boundaries and supports all vector and matrix sizes.
+    /// `mint` types don't implement bytemuck traits,
+    /// and matrices with three rows in host shareable structs require [WriteOptions::derive_encase_host_shareable].
+    Mint,
+
+    /// `cgmath` types like `cgmath::Vector4<f32>` or `cgmath::Matrix4<f32>`.
+    /// Types not representable by `cgmath` like `mat2x3<f32>` will use the output from [MatrixVectorTypes::Rust].
+    /// `cgmath` types don't implement bytemuck traits,
+    /// and `mat3x3` in host shareable structs requires [WriteOptions::derive_encase_host_shareable].
+    Cgmath,
+
+    /// `ultraviolet` types like `ultraviolet::Vec4` or `ultraviolet::DMat4`.
+    /// Types not representable by `ultraviolet` like `mat2x3<f32>` will use the output from [MatrixVectorTypes::Rust].
+    /// `mat3x3` in host shareable structs requires [WriteOptions::derive_encase_host_shareable].
+    Ultraviolet,
 }
 
 impl Default for MatrixVectorTypes {
@@ -131,13 +313,63 @@ impl Default for MatrixVectorTypes {
     }
 }
 
+/// The WGSL source code used to create the shader module at runtime.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub enum ShaderSourceMode {
+    /// Embed the WGSL written by naga from the parsed module.
+    /// This removes comments and may rename or reorder items.
+    #[default]
+    Normalized,
+
+    /// Embed the original WGSL source text.
+    /// Line numbers in wgpu shader errors will match the original file.
+    ///
+    /// [create_shader_module_tokens] does not have access to the source text
+    /// and uses [ShaderSourceMode::Normalized] instead.
+    Verbatim,
+
+    /// Include the WGSL source with `include_str!` using a path relative to the generated Rust file.
+    /// Line numbers in wgpu shader errors will match the original file,
+    /// and the source does not need to be regenerated to apply changes that don't affect the bindings.
+    ///
+    /// The included file is used as is, so this can't be combined with [WriteOptions::defines]
+    /// or with `#include` and conditional directives.
+    IncludeStr(String),
+}
+
+/// A named combination of defines for [create_shader_permutations].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Permutation<'a> {
+    /// The name of the generated Rust module like `skinned` or `static_mesh`.
+    /// Names must be unique and can't be Rust keywords.
+    pub name: &'a str,
+
+    /// Defines for this permutation applied after [WriteOptions::defines].
+    pub defines: &'a [(&'a str, &'a str)],
+}
+
+/// Languages to translate the shader to ahead of time using the naga backends.
+/// Each language requires enabling the corresponding feature for wgsl_to_wgpu.
+/// Pipeline overridable constants are not supported.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Translations {
+    /// Generate `Shader::SOURCE_SPIRV` and `Shader::new_spirv` when `true`.
+    /// Requires the `spv-out` feature.
+    pub spirv: bool,
+
+    /// Generate `Shader::source_glsl` for each entry point when `true`.
+    /// Requires the `glsl-out` feature.
+    pub glsl: bool,
+}
+
 // TODO: Show how to convert a naga module back to wgsl.
 /// Generates a Rust module for a WGSL shader embedded as a string literal.
 ///
 /// # Examples
 /// This function is intended to be called at build time such as in a build script.
 /// The source string does not need to be from an actual file on disk.
-/// This allows applying build time operations like preprocessor defines.
+/// This allows applying other build time operations before generating the bindings.
+/// Conditional directives like `#ifdef` are applied using [WriteOptions::defines].
 /**
 ```rust no_run
 // build.rs
@@ -164,36 +396,390 @@ pub fn create_shader_module(
     wgsl_source: &str,
     options: WriteOptions,
 ) -> Result<String, CreateModuleError> {
-    let module = naga::front::wgsl::parse_str(wgsl_source).unwrap();
+    let output = create_shader_module_source_tokens(wgsl_source, options.clone())?;
+    Ok(format_output(output, &options))
+}
 
-    let output = create_shader_module_tokens(&module, options)?;
+/// Generates the tokens for [create_shader_module] without formatting.
+/// This is intended for procedural macros.
+pub fn create_shader_module_source_tokens(
+    wgsl_source: &str,
+    options: WriteOptions,
+) -> Result<TokenStream, CreateModuleError> {
+    let (_, output) = wgsl_module_tokens(wgsl_source, &options.defines, &options)?;
+    Ok(output)
+}
 
-    if options.rustfmt {
-        Ok(pretty_print_rustfmt(output))
-    } else {
-        Ok(pretty_print(output))
+/// Generates a Rust module containing a `pub mod` for each permutation of a WGSL shader.
+///
+/// Each permutation applies its defines after [WriteOptions::defines].
+/// Struct and bind group types that are identical in every permutation
+/// are only defined once in the parent module and reexported by each permutation module.
+/// This allows using the same uniform buffers and bind groups with any permutation.
+///
+/// # Examples
+/**
+```rust no_run
+// build.rs
+# let wgsl_source = String::new();
+use wgsl_to_wgpu::{create_shader_permutations, Permutation, WriteOptions};
+
+// Generates `shader::skinned` and `shader::static_mesh`.
+let text = create_shader_permutations(
+    &wgsl_source,
+    &[
+        Permutation {
+            name: "skinned",
+            defines: &[("SKINNING", "1")],
+        },
+        Permutation {
+            name: "static_mesh",
+            defines: &[],
+        },
+    ],
+    WriteOptions::default(),
+)
+.unwrap();
+std::fs::write("src/shader.rs", text.as_bytes()).unwrap();
+```
+ */
+pub fn create_shader_permutations(
+    wgsl_source: &str,
+    permutations: &[Permutation],
+    options: WriteOptions,
+) -> Result<String, CreateModuleError> {
+    permutation::check_names(permutations.iter().map(|p| p.name))?;
+
+    let mut modules = Vec::new();
+    for permutation in permutations {
+        let defines: Vec<_> = options
+            .defines
+            .iter()
+            .cloned()
+            .chain(
+                permutation
+                    .defines
+                    .iter()
+                    .map(|(name, value)| (name.to_string(), value.to_string())),
+            )
+            .collect();
+        let (module, tokens) = wgsl_module_tokens(wgsl_source, &defines, &options)?;
+        modules.push(permutation::PermutationModule {
+            name: permutation.name,
+            shareable_types: permutation::shareable_types(&module),
+            tokens,
+        });
     }
+
+    let output = permutation::permutation_modules(modules)?;
+    Ok(format_output(output, &options))
+}
+
+fn parse_wgsl(
+    source: &str,
+    message: impl FnOnce(&naga::front::wgsl::ParseError) -> String,
+) -> Result<naga::Module, CreateModuleError> {
+    naga::front::wgsl::parse_str(source)
+        .map_err(|e| CreateModuleError::ParseError { message: message(&e) })
+}
+
+/// The included file for [ShaderSourceMode::IncludeStr] only matches the module without preprocessing.
+fn check_include_str(
+    options: &WriteOptions,
+    has_defines: bool,
+    is_preprocessed: bool,
+) -> Result<(), CreateModuleError> {
+    if let ShaderSourceMode::IncludeStr(_) = options.source_mode {
+        if has_defines {
+            return Err(CreateModuleError::IncludeStrPreprocessed {
+                message: "defines".to_owned(),
+            });
+        }
+        if is_preprocessed {
+            return Err(CreateModuleError::IncludeStrPreprocessed {
+                message: "#include or conditional directives".to_owned(),
+            });
+        }
+    }
+    Ok(())
+}
+
+fn wgsl_module_tokens(
+    wgsl_source: &str,
+    defines: &[(String, String)],
+    options: &WriteOptions,
+) -> Result<(naga::Module, TokenStream), CreateModuleError> {
+    let wgsl_source = compose::preprocess(wgsl_source, defines)?;
+    check_include_str(
+        options,
+        !defines.is_empty(),
+        matches!(wgsl_source, Cow::Owned(_)),
+    )?;
+
+    // Include the source location in errors when the source is available.
+    let module = parse_wgsl(&wgsl_source, |e| e.emit_to_string(&wgsl_source))?;
+
+    let module_info =
+        validate_module(&module, options).map_err(|e| CreateModuleError::ValidationError {
+            message: e.emit_to_string(&wgsl_source),
+        })?;
+
+    let shader_source = shader::shader_source(
+        &module,
+        &module_info,
+        Some(&wgsl_source),
+        &options.source_mode,
+    );
+    let output = module_tokens(
+        &module,
+        &module_info,
+        shader::ShaderSource::wgsl(shader_source),
+        options,
+    )?;
+
+    Ok((module, output))
+}
+
+/// Generates a Rust module for a WGSL file and any files it includes with `#include "path"`.
+/// See [ComposedSource] for how includes are resolved.
+///
+/// This returns the generated module and the files used to compose the shader
+/// starting with the file at `path`.
+/// Parse and validation errors are reported with the original file and line.
+///
+/// # Examples
+/**
+```rust no_run
+// build.rs
+// Includes are resolved relative to each file and then in "shaders/common".
+let (text, files) = wgsl_to_wgpu::create_shader_module_from_path(
+    "src/shader.wgsl",
+    &["shaders/common".into()],
+    wgsl_to_wgpu::WriteOptions::default(),
+)
+.unwrap();
+for file in files {
+    println!("cargo:rerun-if-changed={}", file.display());
+}
+std::fs::write("src/shader.rs", text.as_bytes()).unwrap();
+```
+ */
+pub fn create_shader_module_from_path(
+    path: impl AsRef<Path>,
+    search_paths: &[PathBuf],
+    options: WriteOptions,
+) -> Result<(String, Vec<PathBuf>), CreateModuleError> {
+    let composed = ComposedSource::new(path, search_paths, &options.defines)?;
+    let output = create_shader_module_composed(&composed, options)?;
+    Ok((output, composed.files))
+}
+
+/// Generates a Rust module for WGSL source composed with [ComposedSource].
+/// Parse and validation errors are reported with the original file and line.
+pub fn create_shader_module_composed(
+    composed: &ComposedSource,
+    options: WriteOptions,
+) -> Result<String, CreateModuleError> {
+    let output = create_shader_module_composed_tokens(composed, options.clone())?;
+    Ok(format_output(output, &options))
+}
+
+/// Generates the tokens for [create_shader_module_composed] without formatting.
+/// This is intended for procedural macros.
+pub fn create_shader_module_composed_tokens(
+    composed: &ComposedSource,
+    options: WriteOptions,
+) -> Result<TokenStream, CreateModuleError> {
+    check_include_str(
+        &options,
+        !options.defines.is_empty(),
+        composed.is_preprocessed(),
+    )?;
+
+    let source = &composed.source;
+    let module = parse_wgsl(source, |e| {
+        composed.located_message(e.location(source), e.message())
+    })?;
+
+    let module_info =
+        validate_module(&module, &options).map_err(|e| CreateModuleError::ValidationError {
+            message: composed.located_message(e.location(source), &error_message(&e)),
+        })?;
+
+    let shader_source =
+        shader::shader_source(&module, &module_info, Some(source), &options.source_mode);
+    module_tokens(
+        &module,
+        &module_info,
+        shader::ShaderSource::wgsl(shader_source),
+        &options,
+    )
+}
+
+/// Generates a Rust module for a GLSL shader for a single shader `stage`.
+///
+/// Each of the `defines` is applied like `#define name value`
+/// when parsing the source and when creating the shader module at runtime.
+/// The original source is embedded in `Shader::SOURCE` and used with
+/// [wgpu::ShaderSource::Glsl](https://docs.rs/wgpu/latest/wgpu/enum.ShaderSource.html#variant.Glsl),
+/// which requires the `glsl` feature for wgpu.
+///
+/// Requires the `glsl-in` feature.
+#[cfg(feature = "glsl-in")]
+pub fn create_shader_module_from_glsl(
+    glsl_source: &str,
+    stage: naga::ShaderStage,
+    defines: &[(&str, &str)],
+    options: WriteOptions,
+) -> Result<String, CreateModuleError> {
+    let glsl_options = naga::front::glsl::Options {
+        stage,
+        defines: defines
+            .iter()
+            .map(|(name, value)| (name.to_string(), value.to_string()))
+            .collect(),
+    };
+    let module = naga::front::glsl::Frontend::default()
+        .parse(&glsl_options, glsl_source)
+        .map_err(|e| CreateModuleError::ParseError {
+            message: e.emit_to_string(glsl_source),
+        })?;
+
+    let module_info =
+        validate_module(&module, &options).map_err(|e| CreateModuleError::ValidationError {
+            message: e.emit_to_string_with_path(glsl_source, "glsl"),
+        })?;
+
+    let output = module_tokens(
+        &module,
+        &module_info,
+        shader::ShaderSource::glsl(glsl_source, stage, defines),
+        &options,
+    )?;
+
+    Ok(format_output(output, &options))
+}
+
+/// Generates a Rust module for a SPIR-V shader from the little-endian bytes of a `.spv` file.
+///
+/// The original words are embedded in `Shader::SOURCE` and used with
+/// [wgpu::ShaderSource::SpirV](https://docs.rs/wgpu/latest/wgpu/enum.ShaderSource.html#variant.SpirV),
+/// which requires the `spirv` feature for wgpu.
+///
+/// Requires the `spv-in` feature.
+#[cfg(feature = "spv-in")]
+pub fn create_shader_module_from_spirv(
+    spirv_source: &[u8],
+    options: WriteOptions,
+) -> Result<String, CreateModuleError> {
+    // Match the coordinate space used by wgpu when creating the shader module.
+    let spirv_options = naga::front::spv::Options {
+        adjust_coordinate_space: false,
+        ..Default::default()
+    };
+    let module = naga::front::spv::parse_u8_slice(spirv_source, &spirv_options).map_err(|e| {
+        CreateModuleError::ParseError {
+            message: error_message(&e),
+        }
+    })?;
+
+    let module_info =
+        validate_module(&module, &options).map_err(|e| CreateModuleError::ValidationError {
+            message: error_message(&e),
+        })?;
+
+    // The parser already checked that the length is a multiple of 4.
+    let words: Vec<_> = spirv_source
+        .chunks_exact(4)
+        .map(|c| u32::from_le_bytes(c.try_into().unwrap()))
+        .collect();
+    let output = module_tokens(
+        &module,
+        &module_info,
+        shader::ShaderSource::spirv(&words),
+        &options,
+    )?;
+
+    Ok(format_output(output, &options))
 }
 
 pub fn create_shader_module_tokens(
     module: &naga::Module,
     options: WriteOptions,
+) -> Result<TokenStream, CreateModuleError> {
+    let module_info =
+        validate_module(module, &options).map_err(|e| CreateModuleError::ValidationError {
+            message: error_message(&e),
+        })?;
+
+    let shader_source = shader::shader_source(module, &module_info, None, &options.source_mode);
+    module_tokens(
+        module,
+        &module_info,
+        shader::ShaderSource::wgsl(shader_source),
+        &options,
+    )
+}
+
+fn validate_module(
+    module: &naga::Module,
+    options: &WriteOptions,
+) -> Result<naga::valid::ModuleInfo, naga::WithSpan<naga::valid::ValidationError>> {
+    naga::valid::Validator::new(options.validation_flags, options.capabilities).validate(module)
+}
+
+fn format_output(output: TokenStream, options: &WriteOptions) -> String {
+    if options.rustfmt {
+        pretty_print_rustfmt(output)
+    } else {
+        pretty_print(output)
+    }
+}
+
+fn error_message(error: &dyn std::error::Error) -> String {
+    let mut message = error.to_string();
+    let mut source = error.source();
+    while let Some(e) = source {
+        message += &format!(": {e}");
+        source = e.source();
+    }
+    message
+}
+
+fn module_tokens(
+    module: &naga::Module,
+    module_info: &naga::valid::ModuleInfo,
+    shader_source: shader::ShaderSource,
+    options: &WriteOptions,
 ) -> Result<TokenStream, CreateModuleError> {
     let bind_group_data = get_bind_group_data(&module)?;
+    bindgroup::check_dynamic_bindings(&bind_group_data, &options.dynamic_bindings)?;
+    structs::check_type_overrides(module, options)?;
     let shader_stages = wgsl::shader_stages(&module);
 
     // Write all the structs, including uniforms and entry function inputs.
-    let structs = structs::structs(&module, options);
-    let consts = consts::consts(&module);
-    let (bind_groups_module, bind_groups) = bind_groups_module(&bind_group_data, shader_stages);
-    let vertex_module = vertex_struct_methods(&module);
-    let entry_point_constants = entry_point_constants(&module);
+    let structs = structs::structs(&module, options)?;
+    let consts = consts::consts(&module, options)?;
+    let (bind_groups_module, bind_groups) =
+        bind_groups_module(&bind_group_data, shader_stages, &options.dynamic_bindings);
+    let reflection = ShaderReflection::from_module(module)?;
+    let vertex_module = vertex_struct_methods(&reflection.vertex_inputs);
+    let entry_point_constants = entry_point_constants(&module)?;
 
     let push_constant_range = push_constant_range(&module, shader_stages);
 
     let override_constants = pipeline_overridable_constants(&module);
 
-    let shader_definition = shader::define_shader(module, &bind_groups, push_constant_range);
+    let translated_sources =
+        translate::translated_sources(module, module_info, options.translations)?;
+    let shader_definition = shader::define_shader(
+        module,
+        module_info,
+        shader_source,
+        translated_sources,
+        &bind_groups,
+        push_constant_range,
+    );
     let pipeline_layout = pipeline_layout::define_pipeline_layout(module, &bind_groups);
 
     Ok(quote! {
@@ -291,6 +877,22 @@ fn quote_shader_stages(stages: wgpu::ShaderStages) -> TokenStream {
     }
 }
 
+fn quote_features(features: wgpu::Features) -> TokenStream {
+    let components: Vec<_> = features
+        .iter_names()
+        .map(|(name, _)| {
+            let name = Ident::new(name, Span::call_site());
+            quote!(wgpu::Features::#name)
+        })
+        .collect();
+
+    if let Some((first, remaining)) = components.split_first() {
+        quote!(#first #(.union(#remaining))*)
+    } else {
+        quote!(wgpu::Features::empty())
+    }
+}
+
 // Tokenstreams can't be compared directly using PartialEq.
 // Use pretty_print to normalize the formatting and compare strings.
 // Use a colored diff output to make differences easier to see.
@@ -421,7 +1023,9 @@ mod test {
             @group(3) @binding(0) var<uniform> d: mat4x4<f32>;
 
             @vertex
-            fn vs_main() -> @builtin(position) vec4<f32> {}
+            fn vs_main() -> @builtin(position) vec4<f32> {
+                return vec4(0.0);
+            }
 
             @fragment
             fn fs_main() {}
@@ -468,6 +1072,308 @@ mod test {
         ));
     }
 
+    #[test]
+    fn create_shader_module_validation_error() {
+        let source = indoc! {r#"
+            @vertex
+            fn main() -> @builtin(position) vec4<f32> {}
+        "#};
+
+        let result = create_shader_module(source, WriteOptions::default());
+        match result {
+            Err(CreateModuleError::ValidationError { message }) => {
+                assert!(message.contains("Entry point main at Vertex is invalid"));
+                assert!(message.contains("does not match the function return value"));
+            }
+            _ => panic!("expected a validation error"),
+        }
+    }
+
+    #[test]
+    fn create_shader_module_validation_flags() {
+        let source = indoc! {r#"
+            @vertex
+            fn main() -> @builtin(position) vec4<f32> {}
+        "#};
+
+        let result = create_shader_module(
+            source,
+            WriteOptions {
+                validation_flags: naga::valid::ValidationFlags::empty(),
+                ..Default::default()
+            },
+        );
+        assert!(result.is_ok());
+    }
+
+    #[test]
+    fn create_shader_module_tokens_capabilities() {
+        let source = indoc! {r#"
+            var<push_constant> constants: vec4<f32>;
+
+            @fragment
+            fn main() -> @location(0) vec4<f32> {
+                return constants;
+            }
+        "#};
+
+        let module = naga::front::wgsl::parse_str(source).unwrap();
+        let result = create_shader_module_tokens(
+            &module,
+            WriteOptions {
+                capabilities: naga::valid::Capabilities::empty(),
+                ..Default::default()
+            },
+        );
+        match result {
+            Err(CreateModuleError::ValidationError { message }) => {
+                assert!(message.contains("PUSH_CONSTANT"), "{message}");
+            }
+            _ => panic!("expected a validation error"),
+        }
+    }
+
+    #[test]
+    fn create_shader_module_defines() {
+        let source = indoc! {r#"
+            @fragment
+            fn main() -> @location(0) vec4<f32> {
+            #ifdef RED
+                return vec4(1.0, 0.0, 0.0, 1.0);
+            #else
+                return vec4(1.0);
+            #endif
+            }
+        "#};
+
+        let actual = create_shader_module(
+            source,
+            WriteOptions {
+                defines: vec![("RED".to_owned(), "1".to_owned())],
+                source_mode: ShaderSourceMode::Verbatim,
+                ..Default::default()
+            },
+        )
+        .unwrap();
+        assert!(actual.contains(r#""@fragment\nfn main() -> @location(0) vec4<f32> {\n\n    return vec4(1.0, 0.0, 0.0, 1.0);\n\n\n\n}\n""#));
+    }
+
+    #[test]
+    fn create_shader_module_include_str_preprocessed() {
+        let source = indoc! {r#"
+            @fragment
+            fn main() -> @location(0) vec4<f32> {
+            #ifdef RED
+                return vec4(1.0, 0.0, 0.0, 1.0);
+            #endif
+                return vec4(0.0);
+            }
+        "#};
+        let options = WriteOptions {
+            source_mode: ShaderSourceMode::IncludeStr("shader.wgsl".to_owned()),
+            ..Default::default()
+        };
+
+        assert_eq!(
+            Err(CreateModuleError::IncludeStrPreprocessed {
+                message: "#include or conditional directives".to_owned()
+            }),
+            create_shader_module(source, options.clone())
+        );
+        assert_eq!(
+            Err(CreateModuleError::IncludeStrPreprocessed {
+                message: "defines".to_owned()
+            }),
+            create_shader_module(
+                "@compute @workgroup_size(1) fn main() {}",
+                WriteOptions {
+                    defines: vec![("RED".to_owned(), "1".to_owned())],
+                    ..options
+                }
+            )
+        );
+    }
+
+    #[test]
+    fn create_shader_module_from_path_include_str() {
+        let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("src/data/compose");
+        let options = WriteOptions {
+            source_mode: ShaderSourceMode::IncludeStr("shader.wgsl".to_owned()),
+            ..Default::default()
+        };
+
+        assert!(
+            create_shader_module_from_path(dir.join("camera.wgsl"), &[], options.clone()).is_ok()
+        );
+        assert!(matches!(
+            create_shader_module_from_path(dir.join("main.wgsl"), &[dir.join("shared")], options),
+            Err(CreateModuleError::IncludeStrPreprocessed { .. })
+        ));
+    }
+
+    #[test]
+    fn create_shader_module_parse_error() {
+        let result = create_shader_module("fn main() {", WriteOptions::default());
+        assert!(matches!(result, Err(CreateModuleError::ParseError { .. })));
+    }
+
+    #[test]
+    fn create_shader_module_workgroup_size_override() {
+        let source = indoc! {r#"
+            override block_size: u32 = 64u;
+
+            @compute
+            @workgroup_size(block_size, 2)
+            fn main_1() {}
+        "#};
+        // naga only supports constant expressions for workgroup sizes.
+        let result = create_shader_module(source, WriteOptions::default());
+        assert!(matches!(
+            result,
+            Err(CreateModuleError::ParseError { message }) if message.contains("override-expression")
+        ));
+    }
+
+    #[test]
+    fn create_shader_module_from_path_includes() {
+        let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("src/data/compose");
+        let (actual, files) = create_shader_module_from_path(
+            dir.join("main.wgsl"),
+            &[dir.join("shared")],
+            WriteOptions::default(),
+        )
+        .unwrap();
+        assert!(actual.contains("pub struct Camera"));
+        assert!(actual.contains("fn lighting"));
+        assert_eq!(
+            vec![
+                dir.join("main.wgsl").canonicalize().unwrap(),
+                dir.join("camera.wgsl").canonicalize().unwrap(),
+                dir.join("shared/lighting.wgsl").canonicalize().unwrap(),
+            ],
+            files
+        );
+    }
+
+    #[test]
+    fn create_shader_module_from_path_error_location() {
+        let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("src/data/compose");
+        let path = dir.join("invalid.wgsl").canonicalize().unwrap();
+        let result = create_shader_module_from_path(&path, &[], WriteOptions::default());
+        match result {
+            Err(CreateModuleError::ParseError { message }) => {
+                let prefix = format!("{}:5:", path.display());
+                assert!(message.starts_with(&prefix), "{message}");
+            }
+            _ => panic!("expected a parse error"),
+        }
+    }
+
+    #[cfg(feature = "glsl-in")]
+    #[test]
+    fn create_shader_module_from_glsl_compute() {
+        let source = indoc! {r#"
+            #version 450
+            layout(local_size_x = SIZE) in;
+
+            layout(set = 0, binding = 0) buffer Data {
+                float values[];
+            };
+
+            void main() {
+                values[gl_GlobalInvocationID.x] *= 2.0;
+            }
+        "#};
+
+        // Runtime-sized arrays require encase.
+        assert!(matches!(
+            create_shader_module_from_glsl(
+                source,
+                naga::ShaderStage::Compute,
+                &[("SIZE", "64")],
+                WriteOptions::default(),
+            ),
+            Err(CreateModuleError::UnsupportedRuntimeArray { .. })
+        ));
+
+        let actual = create_shader_module_from_glsl(
+            source,
+            naga::ShaderStage::Compute,
+            &[("SIZE", "64")],
+            WriteOptions {
+                derive_encase_host_shareable: true,
+                ..Default::default()
+            },
+        )
+        .unwrap();
+        assert!(actual.contains("wgpu::ShaderSource::Glsl"));
+        assert!(actual.contains("wgpu::naga::ShaderStage::Compute"));
+        assert!(actual.contains(r#"("SIZE", "64")"#));
+        // The anonymous buffer block has no name.
+        assert!(
+            actual.contains("binding_0_0: wgpu::BufferBinding"),
+            "{actual}"
+        );
+    }
+
+    #[cfg(feature = "glsl-in")]
+    #[test]
+    fn create_shader_module_from_glsl_parse_error() {
+        let result = create_shader_module_from_glsl(
+            "void main() {",
+            naga::ShaderStage::Compute,
+            &[],
+            WriteOptions::default(),
+        );
+        assert!(matches!(result, Err(CreateModuleError::ParseError { .. })));
+    }
+
+    #[cfg(all(feature = "spv-in", feature = "spv-out"))]
+    #[test]
+    fn create_shader_module_from_spirv_compute() {
+        let source = indoc! {r#"
+            @group(0) @binding(0)
+            var<storage, read_write> values: array<f32>;
+
+            @compute @workgroup_size(64)
+            fn main(@builtin(global_invocation_id) id: vec3<u32>) {
+                values[id.x] *= 2.0;
+            }
+        "#};
+
+        let module = naga::front::wgsl::parse_str(source).unwrap();
+        let module_info = validate_module(&module, &WriteOptions::default()).unwrap();
+        let words = naga::back::spv::write_vec(
+            &module,
+            &module_info,
+            &naga::back::spv::Options::default(),
+            None,
+        )
+        .unwrap();
+        let bytes: Vec<_> = words.iter().flat_map(|w| w.to_le_bytes()).collect();
+
+        let options = WriteOptions {
+            derive_encase_host_shareable: true,
+            ..Default::default()
+        };
+        let actual = create_shader_module_from_spirv(&bytes, options).unwrap();
+        assert!(actual.contains("wgpu::ShaderSource::SpirV"));
+        assert!(actual.contains("pub const SOURCE: &'static [u32]"));
+        // SPIR-V structs and members may not have names.
+        assert!(actual.contains("pub member_0: Vec<f32>"), "{actual}");
+    }
+
+    #[cfg(feature = "spv-in")]
+    #[test]
+    fn create_shader_module_from_spirv_incomplete_data() {
+        assert_eq!(
+            Err(CreateModuleError::ParseError {
+                message: "incomplete data".to_owned()
+            }),
+            create_shader_module_from_spirv(&[3, 2, 35], WriteOptions::default())
+        );
+    }
+
     #[test]
     fn write_vertex_module_empty() {
         let source = indoc! {r#"
@@ -476,7 +1382,8 @@ mod test {
         "#};
 
         let module = naga::front::wgsl::parse_str(source).unwrap();
-        let actual = vertex_struct_methods(&module);
+        let reflection = ShaderReflection::from_module(&module).unwrap();
+        let actual = vertex_struct_methods(&reflection.vertex_inputs);
 
         assert_tokens_eq!(quote!(), actual);
     }
@@ -496,7 +1403,8 @@ mod test {
         "#};
 
         let module = naga::front::wgsl::parse_str(source).unwrap();
-        let actual = vertex_struct_methods(&module);
+        let reflection = ShaderReflection::from_module(&module).unwrap();
+        let actual = vertex_struct_methods(&reflection.vertex_inputs);
 
         assert_tokens_eq!(
             quote! {
@@ -553,7 +1461,8 @@ mod test {
         "#};
 
         let module = naga::front::wgsl::parse_str(source).unwrap();
-        let actual = vertex_struct_methods(&module);
+        let reflection = ShaderReflection::from_module(&module).unwrap();
+        let actual = vertex_struct_methods(&reflection.vertex_inputs);
 
         assert_tokens_eq!(
             quote! {
@@ -611,7 +1520,8 @@ mod test {
         "#};
 
         let module = naga::front::wgsl::parse_str(source).unwrap();
-        let actual = vertex_struct_methods(&module);
+        let reflection = ShaderReflection::from_module(&module).unwrap();
+        let actual = vertex_struct_methods(&reflection.vertex_inputs);
 
         assert_tokens_eq!(
             quote! {
@@ -668,7 +1578,8 @@ mod test {
         "#};
 
         let module = naga::front::wgsl::parse_str(source).unwrap();
-        let actual = vertex_struct_methods(&module);
+        let reflection = ShaderReflection::from_module(&module).unwrap();
+        let actual = vertex_struct_methods(&reflection.vertex_inputs);
 
         assert_tokens_eq!(
             quote! {
@@ -745,4 +1656,20 @@ mod test {
             quote_shader_stages(wgpu::ShaderStages::all())
         );
     }
+
+    #[test]
+    fn quote_all_features() {
+        assert_tokens_eq!(
+            quote!(wgpu::Features::empty()),
+            quote_features(wgpu::Features::empty())
+        );
+        assert_tokens_eq!(
+            quote!(wgpu::Features::SHADER_F16),
+            quote_features(wgpu::Features::SHADER_F16)
+        );
+        assert_tokens_eq!(
+            quote!(wgpu::Features::SHADER_F16.union(wgpu::Features::PUSH_CONSTANTS)),
+            quote_features(wgpu::Features::SHADER_F16 | wgpu::Features::PUSH_CONSTANTS)
+        );
+    }
 }
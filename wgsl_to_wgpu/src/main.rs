@@ -0,0 +1,334 @@
+//! Generate Rust bindings for WGSL shaders outside of build scripts.
+//!
+//! This is intended for projects that check the generated bindings into version control.
+//! Use `--check` in CI to fail if the generated files are out of date.
+use std::{
+    path::{Path, PathBuf},
+    process::ExitCode,
+};
+
+use wgsl_to_wgpu::{
+    build::{create_output_dir, Config, ShaderSet},
+    MatrixVectorTypes, ShaderSourceMode, WriteOptions,
+};
+
+const USAGE: &str = "\
+Generate typesafe Rust bindings from WGSL shaders to wgpu.
+
+Usage: wgsl_to_wgpu [OPTIONS] --output <PATH> <INPUTS>...
+       wgsl_to_wgpu [--check] --config <PATH>
+
+Arguments:
+  <INPUTS>...  WGSL files or directories to search recursively for .wgsl files
+
+Options:
+  -o, --output <PATH>                 The output .rs file for a single input file
+                                      or the output directory for multiple inputs
+  -c, --config <PATH>                 Generate the shaders described by a wgsl_to_wgpu.toml file
+      --check                         Exit with an error instead of writing if any output is out of date
+  -I, --include-dir <DIR>             An additional directory to search for #include files
+  -D, --define <NAME[=VALUE]>         A define for conditional directives
+      --derive-bytemuck-vertex        Derive bytemuck traits for vertex input structs
+      --derive-bytemuck-host-shareable
+                                      Derive bytemuck traits for uniform and storage buffer structs
+      --derive-encase-host-shareable  Derive encase::ShaderType for uniform and storage buffer structs
+      --derive-serde                  Derive serde traits for structs
+      --matrix-vector-types <TYPES>   rust, rust-ordered, glam, nalgebra, mint, cgmath, or ultraviolet
+      --constant-macros               Generate untyped macros for numeric constants
+      --source-mode <MODE>            normalized, verbatim, or include-str
+      --spirv, --glsl                 Translate the shader to other languages when generating
+      --rustfmt                       Format the output with rustfmt
+      --reflection                    Also write a JSON reflection manifest next to each output
+  -h, --help                          Print help";
+
+#[derive(Debug, PartialEq)]
+struct Args {
+    shaders: Shaders,
+    check: bool,
+}
+
+#[derive(Debug, PartialEq)]
+enum Shaders {
+    Config(PathBuf),
+    Set(ShaderSet),
+}
+
+fn main() -> ExitCode {
+    let args = match parse_args(std::env::args().skip(1)) {
+        Ok(Some(args)) => args,
+        Ok(None) => {
+            println!("{USAGE}");
+            return ExitCode::SUCCESS;
+        }
+        Err(e) => {
+            eprintln!("error: {e}\n\n{USAGE}");
+            return ExitCode::FAILURE;
+        }
+    };
+
+    match run(&args) {
+        Ok(true) => ExitCode::SUCCESS,
+        Ok(false) => {
+            eprintln!("error: generated bindings are out of date");
+            ExitCode::FAILURE
+        }
+        Err(e) => {
+            eprintln!("error: {e}");
+            ExitCode::FAILURE
+        }
+    }
+}
+
+/// Parse the arguments after the executable name or `None` for `--help`.
+fn parse_args(args: impl IntoIterator<Item = String>) -> Result<Option<Args>, String> {
+    let mut inputs = Vec::new();
+    let mut output = None;
+    let mut config = None;
+    let mut check = false;
+    let mut search_paths = Vec::new();
+    let mut include_str = false;
+    let mut reflection = false;
+    let mut options = WriteOptions::default();
+
+    let mut args = args.into_iter();
+    while let Some(arg) = args.next() {
+        let mut value = |name: &str| {
+            args.next()
+                .ok_or_else(|| format!("missing value for {name}"))
+        };
+        match arg.as_str() {
+            "-h" | "--help" => return Ok(None),
+            "-o" | "--output" => output = Some(PathBuf::from(value(&arg)?)),
+            "-c" | "--config" => config = Some(PathBuf::from(value(&arg)?)),
+            "--check" => check = true,
+            "-I" | "--include-dir" => search_paths.push(PathBuf::from(value(&arg)?)),
+            "-D" | "--define" => {
+                let define = value(&arg)?;
+                let (name, value) = define.split_once('=').unwrap_or((&define, ""));
+                options.defines.push((name.to_owned(), value.to_owned()));
+            }
+            "--derive-bytemuck-vertex" => options.derive_bytemuck_vertex = true,
+            "--derive-bytemuck-host-shareable" => options.derive_bytemuck_host_shareable = true,
+            "--derive-encase-host-shareable" => options.derive_encase_host_shareable = true,
+            "--derive-serde" => options.derive_serde = true,
+            "--matrix-vector-types" => {
+                options.matrix_vector_types = match value(&arg)?.as_str() {
+                    "rust" => MatrixVectorTypes::Rust { ordered: false },
+                    "rust-ordered" => MatrixVectorTypes::Rust { ordered: true },
+                    "glam" => MatrixVectorTypes::Glam,
+                    "nalgebra" => MatrixVectorTypes::Nalgebra,
+                    "mint" => MatrixVectorTypes::Mint,
+                    "cgmath" => MatrixVectorTypes::Cgmath,
+                    "ultraviolet" => MatrixVectorTypes::Ultraviolet,
+                    v => return Err(format!("unrecognized matrix vector types `{v}`")),
+                }
+            }
+            "--constant-macros" => options.constant_macros = true,
+            "--source-mode" => match value(&arg)?.as_str() {
+                "normalized" => options.source_mode = ShaderSourceMode::Normalized,
+                "verbatim" => options.source_mode = ShaderSourceMode::Verbatim,
+                // The path depends on the input and output for each shader.
+                "include-str" => include_str = true,
+                v => return Err(format!("unrecognized source mode `{v}`")),
+            },
+            "--spirv" => options.translations.spirv = true,
+            "--glsl" => options.translations.glsl = true,
+            "--rustfmt" => options.rustfmt = true,
+            "--reflection" => reflection = true,
+            a if a.starts_with('-') => return Err(format!("unrecognized option `{a}`")),
+            _ => inputs.push(PathBuf::from(arg)),
+        }
+    }
+
+    if let Some(config) = config {
+        // The config file already describes the inputs, outputs, and options.
+        let has_shader_args = !inputs.is_empty()
+            || output.is_some()
+            || !search_paths.is_empty()
+            || include_str
+            || reflection
+            || options != WriteOptions::default();
+        if has_shader_args {
+            return Err("--config can only be combined with --check".to_owned());
+        }
+        return Ok(Some(Args {
+            shaders: Shaders::Config(config),
+            check,
+        }));
+    }
+
+    if inputs.is_empty() {
+        return Err("expected at least one input".to_owned());
+    }
+    let output = output.ok_or_else(|| "missing required option --output".to_owned())?;
+
+    Ok(Some(Args {
+        shaders: Shaders::Set(ShaderSet {
+            inputs,
+            output,
+            search_paths,
+            options,
+            include_str,
+            reflection,
+        }),
+        check,
+    }))
+}
+
+/// Write or check each output and return `false` if any output is out of date.
+fn run(args: &Args) -> Result<bool, String> {
+    let sets = match &args.shaders {
+        Shaders::Config(path) => Config::from_file(path).map_err(|e| e.to_string())?.shaders,
+        Shaders::Set(set) => vec![set.clone()],
+    };
+
+    let mut up_to_date = true;
+    for set in &sets {
+        for (input, output) in set.outputs().map_err(|e| e.to_string())? {
+            let composed = set.compose(&input).map_err(|e| e.to_string())?;
+
+            if !args.check {
+                create_output_dir(&output).map_err(|e| e.to_string())?;
+            } else if !output.is_file() {
+                eprintln!("{} does not exist", output.display());
+                up_to_date = false;
+                continue;
+            }
+
+            let text = set
+                .generate(&composed, &output)
+                .map_err(|e| e.to_string())?;
+            up_to_date &= write_or_check(&output, &text, args.check)?;
+
+            if set.reflection {
+                let json = set.reflect(&composed).map_err(|e| e.to_string())?;
+                up_to_date &= write_or_check(&output.with_extension("json"), &json, args.check)?;
+            }
+        }
+    }
+    Ok(up_to_date)
+}
+
+/// Write `text` to `path` or check that `path` contains `text` and return `false` if it doesn't.
+fn write_or_check(path: &Path, text: &str, check: bool) -> Result<bool, String> {
+    if check {
+        if std::fs::read_to_string(path).ok().as_deref() != Some(text) {
+            eprintln!("{} is out of date", path.display());
+            return Ok(false);
+        }
+    } else {
+        std::fs::write(path, text)
+            .map_err(|e| format!("failed to write {}: {e}", path.display()))?;
+    }
+    Ok(true)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use wgsl_to_wgpu::Translations;
+
+    fn args(args: &[&str]) -> Result<Option<Args>, String> {
+        parse_args(args.iter().map(|a| a.to_string()))
+    }
+
+    #[test]
+    fn parse_args_options() {
+        let actual = args(&[
+            "shaders",
+            "-o",
+            "src/shaders",
+            "--check",
+            "-I",
+            "shaders/common",
+            "-D",
+            "SKINNING",
+            "--define",
+            "QUALITY=high",
+            "--derive-bytemuck-vertex",
+            "--derive-encase-host-shareable",
+            "--matrix-vector-types",
+            "glam",
+            "--source-mode",
+            "verbatim",
+            "--spirv",
+            "--reflection",
+            "extra.wgsl",
+        ])
+        .unwrap()
+        .unwrap();
+
+        assert_eq!(
+            Args {
+                shaders: Shaders::Set(ShaderSet {
+                    inputs: vec!["shaders".into(), "extra.wgsl".into()],
+                    output: "src/shaders".into(),
+                    search_paths: vec!["shaders/common".into()],
+                    options: WriteOptions {
+                        derive_bytemuck_vertex: true,
+                        derive_encase_host_shareable: true,
+                        matrix_vector_types: MatrixVectorTypes::Glam,
+                        source_mode: ShaderSourceMode::Verbatim,
+                        defines: vec![
+                            ("SKINNING".to_owned(), String::new()),
+                            ("QUALITY".to_owned(), "high".to_owned())
+                        ],
+                        translations: Translations {
+                            spirv: true,
+                            ..Default::default()
+                        },
+                        ..Default::default()
+                    },
+                    include_str: false,
+                    reflection: true,
+                }),
+                check: true,
+            },
+            actual
+        );
+    }
+
+    #[test]
+    fn parse_args_config() {
+        assert_eq!(
+            Ok(Some(Args {
+                shaders: Shaders::Config("wgsl_to_wgpu.toml".into()),
+                check: true,
+            })),
+            args(&["--check", "--config", "wgsl_to_wgpu.toml"])
+        );
+    }
+
+    #[test]
+    fn parse_args_help() {
+        assert_eq!(Ok(None), args(&["shader.wgsl", "--help"]));
+    }
+
+    #[test]
+    fn parse_args_errors() {
+        assert_eq!(
+            Err("missing required option --output".to_owned()),
+            args(&["shader.wgsl"])
+        );
+        assert_eq!(
+            Err("expected at least one input".to_owned()),
+            args(&["-o", "shader.rs"])
+        );
+        assert_eq!(
+            Err("missing value for --output".to_owned()),
+            args(&["shader.wgsl", "--output"])
+        );
+        assert_eq!(
+            Err("unrecognized option `--derive-bytemuck`".to_owned()),
+            args(&["shader.wgsl", "--derive-bytemuck"])
+        );
+        assert_eq!(
+            Err("unrecognized source mode `raw`".to_owned()),
+            args(&["shader.wgsl", "--source-mode", "raw"])
+        );
+        assert_eq!(
+            Err("--config can only be combined with --check".to_owned()),
+            args(&["-c", "wgsl_to_wgpu.toml", "--derive-serde"])
+        );
+    }
+}
@@ -0,0 +1,383 @@
+use std::collections::BTreeSet;
+
+use proc_macro2::{Span, TokenStream, TokenTree};
+use quote::{quote, ToTokens};
+use syn::{Ident, Item};
+
+use crate::{bindgroup::get_bind_group_data, CreateModuleError};
+
+/// The generated code for a single permutation.
+pub struct PermutationModule<'a> {
+    pub name: &'a str,
+    pub shareable_types: BTreeSet<String>,
+    pub tokens: TokenStream,
+}
+
+/// The struct and bind group types that can be shared with other permutations.
+pub fn shareable_types(module: &naga::Module) -> BTreeSet<String> {
+    let structs = module.types.iter().filter_map(|(_, t)| match t.inner {
+        naga::TypeInner::Struct { .. } => t.name.clone(),
+        _ => None,
+    });
+    let bind_groups = get_bind_group_data(module)
+        .map(|groups| groups.into_keys().collect())
+        .unwrap_or_else(|_| Vec::new())
+        .into_iter()
+        .flat_map(|group| {
+            [
+                format!("BindGroup{group}"),
+                format!("BindGroupLayout{group}"),
+            ]
+        });
+
+    structs.chain(bind_groups).collect()
+}
+
+/// Check that each permutation name is a unique Rust module name.
+pub fn check_names<'a>(names: impl IntoIterator<Item = &'a str>) -> Result<(), CreateModuleError> {
+    let mut unique = BTreeSet::new();
+    for name in names {
+        let error = |message: &str| CreateModuleError::InvalidPermutation {
+            name: name.to_owned(),
+            message: message.to_owned(),
+        };
+        if !is_module_name(name) {
+            return Err(error("the name is not a valid Rust identifier"));
+        }
+        if !unique.insert(name) {
+            return Err(error("another permutation has the same name"));
+        }
+    }
+    Ok(())
+}
+
+fn is_module_name(name: &str) -> bool {
+    // Keywords, raw identifiers, and names starting with digits are not valid module names.
+    !name.starts_with("r#") && syn::parse_str::<Ident>(name).is_ok()
+}
+
+/// A `pub mod` for each permutation with types that are identical in every permutation
+/// moved to the parent module.
+pub fn permutation_modules(
+    permutations: Vec<PermutationModule>,
+) -> Result<TokenStream, CreateModuleError> {
+    let files = permutations
+        .iter()
+        .map(|p| {
+            syn::parse2(p.tokens.clone()).map_err(|e| CreateModuleError::InvalidPermutation {
+                name: p.name.to_owned(),
+                message: format!("failed to parse the generated code: {e}"),
+            })
+        })
+        .collect::<Result<Vec<syn::File>, _>>()?;
+
+    let shared = shared_types(&permutations, &files);
+
+    // Take the shared items from the first permutation since they are identical.
+    let shared_items = files.first().into_iter().flat_map(|file| {
+        file.items
+            .iter()
+            .filter(|item| item_type_name(item).is_some_and(|name| shared.contains(&name)))
+    });
+
+    let shared_names: Vec<_> = shared
+        .iter()
+        .map(|name| Ident::new(name, Span::call_site()))
+        .collect();
+    let shared_use = if shared_names.is_empty() {
+        quote!()
+    } else {
+        quote!(pub use super::{#(#shared_names),*};)
+    };
+
+    let modules = permutations.iter().zip(&files).map(|(permutation, file)| {
+        let name = Ident::new(permutation.name, Span::call_site());
+        let items = file
+            .items
+            .iter()
+            .filter(|item| !item_type_name(item).is_some_and(|name| shared.contains(&name)));
+        quote! {
+            pub mod #name {
+                #shared_use
+                #(#items)*
+            }
+        }
+    });
+
+    Ok(quote! {
+        #(#shared_items)*
+        #(#modules)*
+    })
+}
+
+fn shared_types(permutations: &[PermutationModule], files: &[syn::File]) -> BTreeSet<String> {
+    let Some((first, remaining)) = permutations.split_first() else {
+        return BTreeSet::new();
+    };
+
+    // Types must have identical definitions and impls in every permutation.
+    let mut shared: BTreeSet<String> = first
+        .shareable_types
+        .iter()
+        .filter(|name| remaining.iter().all(|p| p.shareable_types.contains(*name)))
+        .filter(|name| {
+            let first_items = type_items(&files[0], name);
+            !first_items.is_empty()
+                && files[1..]
+                    .iter()
+                    .all(|f| type_items(f, name) == first_items)
+        })
+        .cloned()
+        .collect();
+
+    // Shared types can't refer to items that are still defined in each permutation module.
+    let defined_names: BTreeSet<String> = files
+        .iter()
+        .flat_map(|f| f.items.iter().filter_map(item_name))
+        .collect();
+    loop {
+        let unshared: Vec<_> = shared
+            .iter()
+            .filter(|name| {
+                let mut idents = BTreeSet::new();
+                for item in &files[0].items {
+                    if item_type_name(item).as_ref() == Some(*name) {
+                        collect_idents(item.to_token_stream(), &mut idents);
+                    }
+                }
+                idents
+                    .iter()
+                    .any(|i| defined_names.contains(i) && !shared.contains(i))
+            })
+            .cloned()
+            .collect();
+        if unshared.is_empty() {
+            break shared;
+        }
+        for name in unshared {
+            shared.remove(&name);
+        }
+    }
+}
+
+/// The definition and impls for the type `name` as strings.
+fn type_items(file: &syn::File, name: &str) -> Vec<String> {
+    file.items
+        .iter()
+        .filter(|item| item_type_name(item).as_deref() == Some(name))
+        .map(|item| item.to_token_stream().to_string())
+        .collect()
+}
+
+/// The name of the type defined or implemented by `item`.
+fn item_type_name(item: &Item) -> Option<String> {
+    match item {
+        Item::Struct(s) => Some(s.ident.to_string()),
+        Item::Enum(e) => Some(e.ident.to_string()),
+        Item::Impl(i) => match i.self_ty.as_ref() {
+            syn::Type::Path(p) if p.qself.is_none() => p.path.get_ident().map(|i| i.to_string()),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+fn item_name(item: &Item) -> Option<String> {
+    match item {
+        Item::Struct(s) => Some(s.ident.to_string()),
+        Item::Enum(e) => Some(e.ident.to_string()),
+        Item::Type(t) => Some(t.ident.to_string()),
+        Item::Const(c) => Some(c.ident.to_string()),
+        Item::Fn(f) => Some(f.sig.ident.to_string()),
+        Item::Mod(m) => Some(m.ident.to_string()),
+        Item::Macro(m) => m.ident.as_ref().map(|i| i.to_string()),
+        _ => None,
+    }
+}
+
+fn collect_idents(tokens: TokenStream, idents: &mut BTreeSet<String>) {
+    for token in tokens {
+        match token {
+            TokenTree::Ident(i) => {
+                idents.insert(i.to_string());
+            }
+            TokenTree::Group(g) => collect_idents(g.stream(), idents),
+            _ => (),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{assert_tokens_eq, create_shader_permutations, Permutation, WriteOptions};
+    use indoc::indoc;
+
+    const SOURCE: &str = indoc! {r#"
+        struct Camera {
+            view_projection: mat4x4<f32>,
+        }
+
+        struct Skinning {
+        #ifdef SKINNING
+            transforms: array<mat4x4<f32>, 64>,
+        #else
+            transform: mat4x4<f32>,
+        #endif
+        }
+
+        @group(0) @binding(0)
+        var<uniform> camera: Camera;
+
+        @group(1) @binding(0)
+        var<uniform> skinning: Skinning;
+
+        @vertex
+        fn vs_main(@location(0) position: vec3<f32>) -> @builtin(position) vec4<f32> {
+        #ifdef SKINNING
+            let transform = skinning.transforms[0];
+        #else
+            let transform = skinning.transform;
+        #endif
+            return camera.view_projection * transform * vec4(position, 1.0);
+        }
+    "#};
+
+    fn permutations() -> syn::File {
+        let text = create_shader_permutations(
+            SOURCE,
+            &[
+                Permutation {
+                    name: "skinned",
+                    defines: &[("SKINNING", "1")],
+                },
+                Permutation {
+                    name: "static_mesh",
+                    defines: &[],
+                },
+            ],
+            WriteOptions::default(),
+        )
+        .unwrap();
+        syn::parse_file(&text).unwrap()
+    }
+
+    fn top_level_types(file: &syn::File) -> Vec<String> {
+        file.items
+            .iter()
+            .filter_map(|item| match item {
+                Item::Struct(s) => Some(s.ident.to_string()),
+                _ => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn permutation_modules_share_identical_types() {
+        let file = permutations();
+
+        // Only Skinning differs since bind groups don't depend on the buffer types.
+        assert_eq!(
+            vec![
+                "Camera",
+                "BindGroupLayout0",
+                "BindGroup0",
+                "BindGroupLayout1",
+                "BindGroup1"
+            ],
+            top_level_types(&file)
+        );
+
+        let modules: Vec<_> = file
+            .items
+            .iter()
+            .filter_map(|item| match item {
+                Item::Mod(m) => Some(m),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(2, modules.len());
+        assert_eq!("skinned", modules[0].ident.to_string());
+        assert_eq!("static_mesh", modules[1].ident.to_string());
+
+        for module in modules {
+            let items = &module.content.as_ref().unwrap().1;
+            let shared_use = &items[0];
+            assert_tokens_eq!(
+                quote! {
+                    pub use super::{
+                        BindGroup0, BindGroup1, BindGroupLayout0, BindGroupLayout1, Camera,
+                    };
+                },
+                quote!(#shared_use)
+            );
+            let types: Vec<_> = items
+                .iter()
+                .filter_map(|item| match item {
+                    Item::Struct(s) => Some(s.ident.to_string()),
+                    _ => None,
+                })
+                .collect();
+            assert!(types.contains(&"Skinning".to_owned()));
+            assert!(!types
+                .iter()
+                .any(|t| t == "Camera" || t.starts_with("BindGroup")));
+        }
+    }
+
+    #[test]
+    fn permutation_invalid_names() {
+        let error = |names: &[&str]| {
+            let permutations: Vec<_> = names
+                .iter()
+                .map(|name| Permutation { name, defines: &[] })
+                .collect();
+            match create_shader_permutations(SOURCE, &permutations, WriteOptions::default()) {
+                Err(CreateModuleError::InvalidPermutation { name, message }) => (name, message),
+                _ => panic!("expected an invalid permutation error"),
+            }
+        };
+
+        for name in ["static-mesh", "", "1st", "type", "self", "r#type"] {
+            assert_eq!(
+                (
+                    name.to_owned(),
+                    "the name is not a valid Rust identifier".to_owned()
+                ),
+                error(&[name])
+            );
+        }
+        assert_eq!(
+            (
+                "skinned".to_owned(),
+                "another permutation has the same name".to_owned()
+            ),
+            error(&["skinned", "static_mesh", "skinned"])
+        );
+    }
+
+    #[test]
+    fn permutation_modules_single_permutation() {
+        let text = create_shader_permutations(
+            SOURCE,
+            &[Permutation {
+                name: "skinned",
+                defines: &[("SKINNING", "1")],
+            }],
+            WriteOptions::default(),
+        )
+        .unwrap();
+        let file = syn::parse_file(&text).unwrap();
+        assert_eq!(
+            vec![
+                "Camera",
+                "Skinning",
+                "BindGroupLayout0",
+                "BindGroup0",
+                "BindGroupLayout1",
+                "BindGroup1"
+            ],
+            top_level_types(&file)
+        );
+    }
+}
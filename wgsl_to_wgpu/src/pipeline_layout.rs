@@ -1,4 +1,7 @@
-use crate::wgsl::VertexInput;
+use crate::{
+    entry::{compatible_fragment_entries, fragment_entry_name},
+    wgsl::VertexInput,
+};
 
 use super::bindgroup::BindGroup;
 use proc_macro2::{Literal, Span, TokenStream};
@@ -10,9 +13,15 @@ struct PipelineData {
     pipeline_key: Ident,
     pipeline_key_definition: TokenStream,
     pipeline_impl_definitions: TokenStream,
+    pipeline_type: TokenStream,
+    pipeline_type_definition: TokenStream,
 }
 
-fn define_render_pipeline_key(entry_name: &str, step_args: &[Ident]) -> (TokenStream, Ident) {
+fn define_render_pipeline_key(
+    entry_name: &str,
+    step_args: &[Ident],
+    fragment_field: &TokenStream,
+) -> (TokenStream, Ident) {
     let name = Ident::new(&format!("PipelineKey_{}", entry_name), Span::call_site());
     (
         quote! {
@@ -23,7 +32,7 @@ fn define_render_pipeline_key(entry_name: &str, step_args: &[Ident]) -> (TokenSt
                 primitive: wgpu::PrimitiveState,
                 depth_stencil: Option<wgpu::DepthStencilState>,
                 multisample: wgpu::MultisampleState,
-                fragment: FragmentEntry,
+                #fragment_field
                 multiview: Option<std::num::NonZero<u32>>,
             }
         },
@@ -49,8 +58,55 @@ fn define_create_render_pipeline(
         .iter()
         .map(|input| Ident::new(&format!("{}_step_mode", input.name), Span::call_site()))
         .collect();
+
+    // Depth only or vertex only passes don't need a fragment stage.
+    // Vertex entries without compatible fragment entries have no type to choose from.
+    let has_fragment = compatible_fragment_entries(module, entry).next().is_some();
+    let fragment_entry = fragment_entry_name(entry);
+    let (fragment_field, fragment_arg) = if has_fragment {
+        (
+            quote!(fragment: Option<#fragment_entry>,),
+            quote!(fragment,),
+        )
+    } else {
+        (TokenStream::new(), TokenStream::new())
+    };
+    // Check the targets before caching to return an error instead of failing wgpu validation.
+    let (return_type, validate_targets, pipeline) = if has_fragment {
+        (
+            quote!(Result<std::sync::Arc<wgpu::RenderPipeline>, IncompatibleTargetError>),
+            quote! {
+                if let Some(fragment) = &fragment {
+                    fragment.validate_targets()?;
+                }
+            },
+            quote!(Ok(pipeline)),
+        )
+    } else {
+        (
+            quote!(std::sync::Arc<wgpu::RenderPipeline>),
+            TokenStream::new(),
+            quote!(pipeline),
+        )
+    };
+    let fragment_state = if has_fragment {
+        quote! {
+            fragment.as_ref().map(|fragment| {
+                let (entry_point, targets) = fragment.entry_point_and_targets();
+                wgpu::FragmentState {
+                    module,
+                    entry_point,
+                    compilation_options: compilation_options.clone(),
+                    targets,
+                }
+            })
+        }
+    } else {
+        quote!(None)
+    };
+
     let (pipeline_key_definition, pipeline_key) =
-        define_render_pipeline_key(entry_name, &step_args);
+        define_render_pipeline_key(entry_name, &step_args, &fragment_field);
 
     let vertex_buffer_layouts =
         structs
@@ -68,7 +124,7 @@ fn define_create_render_pipeline(
                     primitive,
                     depth_stencil,
                     multisample,
-                    fragment,
+                    #fragment_arg
                     multiview
                 } : #pipeline_key,
                 cache: Option<&wgpu::PipelineCache>,
@@ -80,7 +136,7 @@ fn define_create_render_pipeline(
                 constants: &constants,
                 ..Default::default()
             };
-            let (fragment_entry, targets) = fragment.entry_point_and_targets();
+            let fragment = #fragment_state;
             device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
                     label: None,
                     layout: Some(&self.layout),
@@ -93,14 +149,7 @@ fn define_create_render_pipeline(
                     primitive,
                     depth_stencil,
                     multisample,
-                    fragment: Some(
-                        wgpu::FragmentState {
-                            module,
-                            entry_point: fragment_entry,
-                            compilation_options,
-                            targets,
-                        }
-                    ),
+                    fragment,
                     multiview,
                     cache,
             })
@@ -114,22 +163,24 @@ fn define_create_render_pipeline(
                 #[builder(default)] primitive: wgpu::PrimitiveState,
                 depth_stencil: Option<wgpu::DepthStencilState>,
                 #[builder(default)] multisample: wgpu::MultisampleState,
-                fragment: FragmentEntry,
+                #fragment_field
                 multiview: Option<std::num::NonZero<u32>>,
                 cache: Option<&wgpu::PipelineCache>,
-        ) -> std::sync::Arc<wgpu::RenderPipeline> {
+        ) -> #return_type {
+            #validate_targets
             let key = #pipeline_key {
                 #(#step_args,)*
                 overrides,
                 primitive,
                 depth_stencil,
                 multisample,
-                fragment,
+                #fragment_arg
                 multiview
             };
-            self.#pipeline_cache.lock().unwrap().entry(key).or_insert_with_key(
+            let pipeline = self.#pipeline_cache.lock().unwrap().entry(key).or_insert_with_key(
                 |key| std::sync::Arc::new(self.#from_key_name(key.clone(), cache))
-            ).clone()
+            ).clone();
+            #pipeline
         }
     };
     PipelineData {
@@ -137,19 +188,176 @@ fn define_create_render_pipeline(
         pipeline_key,
         pipeline_key_definition,
         pipeline_impl_definitions,
+        pipeline_type: quote!(wgpu::RenderPipeline),
+        pipeline_type_definition: TokenStream::new(),
     }
 }
 
-// TODO: Figure out the most ergonomic place to output this.
-fn workgroup_size(e: &naga::EntryPoint) -> TokenStream {
-    let name = Ident::new(
-        &format!("{}_WORKGROUP_SIZE", e.name.to_uppercase()),
-        Span::call_site(),
-    );
+fn define_compute_pipeline_type(
+    e: &naga::EntryPoint,
+    bind_groups: &[BindGroup],
+) -> (TokenStream, Ident) {
+    let name = Ident::new(&format!("ComputePipeline_{}", e.name), Span::call_site());
+
+    // naga rejects override expressions for workgroup sizes, so each dimension is a constant.
     let [x, y, z] = e
         .workgroup_size
         .map(|s| Literal::usize_unsuffixed(s as usize));
-    quote!(pub const #name: [u32; 3] = [#x, #y, #z];)
+
+    // The pipeline layout includes all groups, so all groups need to be set.
+    // Groups with dynamic bindings are paired with their dynamic offsets.
+    let group_types = bind_groups.iter().map(|g| {
+        let group_type = &g.group_type;
+        if g.dynamic_offsets {
+            quote!((&#group_type, &[u32]))
+        } else {
+            quote!(&#group_type)
+        }
+    });
+    let bind_groups_type = quote!((#(#group_types,)*));
+    let set_bind_groups = bind_groups.iter().enumerate().map(|(i, g)| {
+        let i = syn::Index::from(i);
+        if g.dynamic_offsets {
+            quote!(bind_groups.#i.0.set_compute(pass, bind_groups.#i.1);)
+        } else {
+            quote!(bind_groups.#i.set_compute(pass);)
+        }
+    });
+
+    (
+        quote! {
+            #[derive(Debug)]
+            pub struct #name {
+                pipeline: wgpu::ComputePipeline,
+                max_workgroups_per_dimension: u32,
+            }
+
+            impl std::ops::Deref for #name {
+                type Target = wgpu::ComputePipeline;
+                fn deref(&self) -> &Self::Target {
+                    &self.pipeline
+                }
+            }
+
+            impl #name {
+                /// The `@workgroup_size` of the entry point.
+                /// Workgroup sizes can't use override constants, so this is the same for all pipelines.
+                pub fn workgroup_size() -> [u32; 3] {
+                    [#x, #y, #z]
+                }
+
+                /// The number of workgroups needed to cover `size` invocations in each dimension.
+                /// Returns an error if a dimension exceeds the device limit.
+                pub fn workgroup_count(&self, size: [u32; 3]) -> Result<[u32; 3], WorkgroupCountError> {
+                    let [x, y, z] = Self::workgroup_size();
+                    let count = [size[0].div_ceil(x), size[1].div_ceil(y), size[2].div_ceil(z)];
+                    if count.iter().any(|c| *c > self.max_workgroups_per_dimension) {
+                        return Err(WorkgroupCountError {
+                            count,
+                            max_workgroups_per_dimension: self.max_workgroups_per_dimension,
+                        });
+                    }
+                    Ok(count)
+                }
+
+                fn set(&self, pass: &mut wgpu::ComputePass, bind_groups: #bind_groups_type) {
+                    pass.set_pipeline(self);
+                    #(#set_bind_groups)*
+                }
+
+                /// Dispatch enough workgroups to cover `size` invocations in each dimension.
+                /// Nothing is recorded if the workgroup count exceeds the device limit.
+                pub fn dispatch(
+                    &self,
+                    pass: &mut wgpu::ComputePass,
+                    bind_groups: #bind_groups_type,
+                    size: [u32; 3],
+                ) -> Result<(), WorkgroupCountError> {
+                    let count = self.workgroup_count(size)?;
+                    self.set(pass, bind_groups);
+                    pass.dispatch_workgroups(count[0], count[1], count[2]);
+                    Ok(())
+                }
+
+                /// Dispatch using the workgroup counts at `index` in `buffer`.
+                pub fn dispatch_indirect(
+                    &self,
+                    pass: &mut wgpu::ComputePass,
+                    bind_groups: #bind_groups_type,
+                    buffer: &DispatchIndirectBuffer,
+                    index: u64,
+                ) {
+                    self.set(pass, bind_groups);
+                    pass.dispatch_workgroups_indirect(buffer, index * DispatchIndirectBuffer::ARGS_SIZE);
+                }
+            }
+        },
+        name,
+    )
+}
+
+fn define_workgroup_count_error() -> TokenStream {
+    quote! {
+        /// A direct dispatch that needs more workgroups than the device supports in a dimension.
+        #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+        pub struct WorkgroupCountError {
+            pub count: [u32; 3],
+            pub max_workgroups_per_dimension: u32,
+        }
+
+        impl std::fmt::Display for WorkgroupCountError {
+            fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                write!(
+                    f,
+                    "workgroup count {:?} exceeds the device limit of {} per dimension",
+                    self.count, self.max_workgroups_per_dimension
+                )
+            }
+        }
+
+        impl std::error::Error for WorkgroupCountError {}
+    }
+}
+
+fn define_dispatch_indirect_buffer() -> TokenStream {
+    quote! {
+        /// A buffer of [wgpu::util::DispatchIndirectArgs] for indirect compute dispatches.
+        #[derive(Debug)]
+        pub struct DispatchIndirectBuffer(wgpu::Buffer);
+
+        impl std::ops::Deref for DispatchIndirectBuffer {
+            type Target = wgpu::Buffer;
+            fn deref(&self) -> &Self::Target {
+                &self.0
+            }
+        }
+
+        impl DispatchIndirectBuffer {
+            pub const ARGS_SIZE: u64 = std::mem::size_of::<wgpu::util::DispatchIndirectArgs>() as u64;
+
+            pub fn new(buffer: wgpu::Buffer) -> Self {
+                assert!(
+                    buffer.usage().contains(wgpu::BufferUsages::INDIRECT),
+                    "indirect dispatch buffers require wgpu::BufferUsages::INDIRECT"
+                );
+                Self(buffer)
+            }
+
+            pub fn init(
+                device: &wgpu::Device,
+                args: &[wgpu::util::DispatchIndirectArgs],
+                usage: wgpu::BufferUsages,
+            ) -> Self {
+                use wgpu::util::DeviceExt;
+                let contents: Vec<u8> = args.iter().flat_map(|a| a.as_bytes()).copied().collect();
+                Self(device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                    label: None,
+                    contents: &contents,
+                    usage: usage | wgpu::BufferUsages::INDIRECT,
+                }))
+            }
+        }
+    }
 }
 
 fn define_compute_pipeline_key(entry_name: &str) -> (TokenStream, Ident) {
@@ -165,9 +373,14 @@ fn define_compute_pipeline_key(entry_name: &str) -> (TokenStream, Ident) {
     )
 }
 
-fn define_create_compute_pipeline(entry: &naga::EntryPoint) -> PipelineData {
+fn define_create_compute_pipeline(
+    entry: &naga::EntryPoint,
+    bind_groups: &[BindGroup],
+) -> PipelineData {
     let entry_name = &entry.name;
     let (pipeline_key_definition, pipeline_key) = define_compute_pipeline_key(entry_name);
+    let (pipeline_type_definition, pipeline_type) =
+        define_compute_pipeline_type(entry, bind_groups);
 
     let pipeline_cache = Ident::new(&format!("{}_pipelines", entry_name), Span::call_site());
     let function_name = Ident::new(&format!("{}_pipeline", entry_name), Span::call_site());
@@ -178,7 +391,7 @@ fn define_create_compute_pipeline(entry: &naga::EntryPoint) -> PipelineData {
             &self,
             #pipeline_key { overrides } : #pipeline_key,
             cache: Option<&wgpu::PipelineCache>,
-        ) -> wgpu::ComputePipeline {
+        ) -> #pipeline_type {
             let device = &self.device;
             let module = &self.shader_module;
             let constants = overrides.constants();
@@ -186,14 +399,18 @@ fn define_create_compute_pipeline(entry: &naga::EntryPoint) -> PipelineData {
                     constants: &constants,
                     ..Default::default()
             };
-            device.create_compute_pipeline(&wgpu::ComputePipelineDescriptor {
+            let pipeline = device.create_compute_pipeline(&wgpu::ComputePipelineDescriptor {
                     label: None,
                     layout: Some(&self.layout),
                     module,
                     entry_point: #entry_name,
                     compilation_options,
                     cache,
-            })
+            });
+            #pipeline_type {
+                pipeline,
+                max_workgroups_per_dimension: device.limits().max_compute_workgroups_per_dimension,
+            }
         }
 
         #[builder(finish_fn = get)]
@@ -201,7 +418,7 @@ fn define_create_compute_pipeline(entry: &naga::EntryPoint) -> PipelineData {
             &self,
             #[builder(default)] overrides: OverrideConstants,
             cache: Option<&wgpu::PipelineCache>,
-        ) -> std::sync::Arc<wgpu::ComputePipeline> {
+        ) -> std::sync::Arc<#pipeline_type> {
             let key = #pipeline_key { overrides };
             self.#pipeline_cache.lock().unwrap().entry(key).or_insert_with_key(
                 |key| std::sync::Arc::new(self.#from_key_name(key.clone(), cache))
@@ -213,25 +430,21 @@ fn define_create_compute_pipeline(entry: &naga::EntryPoint) -> PipelineData {
         pipeline_key,
         pipeline_key_definition,
         pipeline_impl_definitions,
+        pipeline_type: quote!(#pipeline_type),
+        pipeline_type_definition,
     }
 }
 
 pub fn define_pipeline_layout(module: &naga::Module, bind_groups: &[BindGroup]) -> TokenStream {
-    let (pipeline_datas, pipeline_results): (Vec<_>, Vec<_>) = module
+    let pipeline_datas: Vec<_> = module
         .entry_points
         .iter()
         .filter_map(|e| match e.stage {
-            naga::ShaderStage::Vertex => Some((
-                define_create_render_pipeline(module, e),
-                quote!(wgpu::RenderPipeline),
-            )),
-            naga::ShaderStage::Compute => Some((
-                define_create_compute_pipeline(e),
-                quote!(wgpu::ComputePipeline),
-            )),
+            naga::ShaderStage::Vertex => Some(define_create_render_pipeline(module, e)),
+            naga::ShaderStage::Compute => Some(define_create_compute_pipeline(e, bind_groups)),
             _ => None,
         })
-        .unzip();
+        .collect();
 
     let bind_group_layout_types: Vec<_> = bind_groups.iter().map(|g| &g.layout_type).collect();
     let bind_group_layouts_type = quote!((#(#bind_group_layout_types,)*));
@@ -240,8 +453,8 @@ pub fn define_pipeline_layout(module: &naga::Module, bind_groups: &[BindGroup])
         .iter()
         .map(|data| data.pipeline_cache.clone())
         .collect();
-    let pipeline_cache_field_types = pipeline_datas.iter().zip(pipeline_results.iter()).map(|(PipelineData { pipeline_key, ..}, result)|
-        quote!(std::sync::Mutex<std::collections::HashMap<#pipeline_key, std::sync::Arc<#result>>>)
+    let pipeline_cache_field_types = pipeline_datas.iter().map(|PipelineData { pipeline_key, pipeline_type, ..}|
+        quote!(std::sync::Mutex<std::collections::HashMap<#pipeline_key, std::sync::Arc<#pipeline_type>>>)
     );
 
     let pipeline_key_definitions = pipeline_datas
@@ -250,6 +463,15 @@ pub fn define_pipeline_layout(module: &naga::Module, bind_groups: &[BindGroup])
     let pipeline_impl_definitions = pipeline_datas
         .iter()
         .map(|data| &data.pipeline_impl_definitions);
+    let pipeline_type_definitions = pipeline_datas
+        .iter()
+        .map(|data| &data.pipeline_type_definition);
+    let has_compute = module
+        .entry_points
+        .iter()
+        .any(|e| e.stage == naga::ShaderStage::Compute);
+    let workgroup_count_error = has_compute.then(define_workgroup_count_error);
+    let dispatch_indirect_buffer = has_compute.then(define_dispatch_indirect_buffer);
 
     quote! {
         pub struct PipelineLayout {
@@ -269,6 +491,12 @@ pub fn define_pipeline_layout(module: &naga::Module, bind_groups: &[BindGroup])
 
         #(#pipeline_key_definitions)*
 
+        #(#pipeline_type_definitions)*
+
+        #workgroup_count_error
+
+        #dispatch_indirect_buffer
+
         #[bon::bon]
         impl PipelineLayout {
             pub fn new(
@@ -290,3 +518,155 @@ pub fn define_pipeline_layout(module: &naga::Module, bind_groups: &[BindGroup])
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::{
+        assert_tokens_eq,
+        bindgroup::{bind_groups_module, get_bind_group_data},
+    };
+    use indoc::indoc;
+
+    fn compute_pipeline_type(wgsl: &str, dynamic_bindings: &[String]) -> TokenStream {
+        let module = naga::front::wgsl::parse_str(wgsl).unwrap();
+        let bind_group_data = get_bind_group_data(&module).unwrap();
+        let (_, bind_groups) = bind_groups_module(
+            &bind_group_data,
+            wgpu::ShaderStages::COMPUTE,
+            dynamic_bindings,
+        );
+        let (actual, _) = define_compute_pipeline_type(&module.entry_points[0], &bind_groups);
+        actual
+    }
+
+    #[test]
+    fn write_compute_pipeline_type() {
+        let source = indoc! {r#"
+            @group(0) @binding(0) var<storage, read_write> values: array<f32>;
+            @group(1) @binding(0) var<uniform> scale: f32;
+
+            @compute
+            @workgroup_size(64, 2)
+            fn main() {
+                values[0] *= scale;
+            }
+        "#};
+
+        let actual = compute_pipeline_type(source, &[]);
+
+        assert_tokens_eq!(
+            quote! {
+                #[derive(Debug)]
+                pub struct ComputePipeline_main {
+                    pipeline: wgpu::ComputePipeline,
+                    max_workgroups_per_dimension: u32,
+                }
+
+                impl std::ops::Deref for ComputePipeline_main {
+                    type Target = wgpu::ComputePipeline;
+                    fn deref(&self) -> &Self::Target {
+                        &self.pipeline
+                    }
+                }
+
+                impl ComputePipeline_main {
+                    /// The `@workgroup_size` of the entry point.
+                    /// Workgroup sizes can't use override constants, so this is the same for all pipelines.
+                    pub fn workgroup_size() -> [u32; 3] {
+                        [64, 2, 1]
+                    }
+
+                    /// The number of workgroups needed to cover `size` invocations in each dimension.
+                    /// Returns an error if a dimension exceeds the device limit.
+                    pub fn workgroup_count(&self, size: [u32; 3]) -> Result<[u32; 3], WorkgroupCountError> {
+                        let [x, y, z] = Self::workgroup_size();
+                        let count = [size[0].div_ceil(x), size[1].div_ceil(y), size[2].div_ceil(z)];
+                        if count.iter().any(|c| *c > self.max_workgroups_per_dimension) {
+                            return Err(WorkgroupCountError {
+                                count,
+                                max_workgroups_per_dimension: self.max_workgroups_per_dimension,
+                            });
+                        }
+                        Ok(count)
+                    }
+
+                    fn set(&self, pass: &mut wgpu::ComputePass, bind_groups: (&BindGroup0, &BindGroup1,)) {
+                        pass.set_pipeline(self);
+                        bind_groups.0.set_compute(pass);
+                        bind_groups.1.set_compute(pass);
+                    }
+
+                    /// Dispatch enough workgroups to cover `size` invocations in each dimension.
+                    /// Nothing is recorded if the workgroup count exceeds the device limit.
+                    pub fn dispatch(
+                        &self,
+                        pass: &mut wgpu::ComputePass,
+                        bind_groups: (&BindGroup0, &BindGroup1,),
+                        size: [u32; 3],
+                    ) -> Result<(), WorkgroupCountError> {
+                        let count = self.workgroup_count(size)?;
+                        self.set(pass, bind_groups);
+                        pass.dispatch_workgroups(count[0], count[1], count[2]);
+                        Ok(())
+                    }
+
+                    /// Dispatch using the workgroup counts at `index` in `buffer`.
+                    pub fn dispatch_indirect(
+                        &self,
+                        pass: &mut wgpu::ComputePass,
+                        bind_groups: (&BindGroup0, &BindGroup1,),
+                        buffer: &DispatchIndirectBuffer,
+                        index: u64,
+                    ) {
+                        self.set(pass, bind_groups);
+                        pass.dispatch_workgroups_indirect(buffer, index * DispatchIndirectBuffer::ARGS_SIZE);
+                    }
+                }
+            },
+            actual
+        );
+    }
+
+    #[test]
+    fn write_compute_pipeline_type_dynamic_offsets() {
+        let source = indoc! {r#"
+            @group(0) @binding(0) var<storage, read_write> values: array<f32>;
+            @group(1) @binding(0) var<uniform> scale: f32;
+
+            @compute
+            @workgroup_size(64)
+            fn main() {
+                values[0] *= scale;
+            }
+        "#};
+
+        let actual = compute_pipeline_type(source, &["scale".to_owned()]);
+
+        let file: syn::File = syn::parse2(actual).unwrap();
+        let set = file
+            .items
+            .iter()
+            .filter_map(|item| match item {
+                syn::Item::Impl(i) => Some(&i.items),
+                _ => None,
+            })
+            .flatten()
+            .find_map(|item| match item {
+                syn::ImplItem::Fn(f) if f.sig.ident == "set" => Some(f),
+                _ => None,
+            })
+            .unwrap();
+        assert_tokens_eq!(
+            quote! {
+                fn set(&self, pass: &mut wgpu::ComputePass, bind_groups: (&BindGroup0, (&BindGroup1, &[u32]),)) {
+                    pass.set_pipeline(self);
+                    bind_groups.0.set_compute(pass);
+                    bind_groups.1.0.set_compute(pass, bind_groups.1.1);
+                }
+            },
+            quote!(#set)
+        );
+    }
+}
@@ -0,0 +1,610 @@
+#[cfg(feature = "reflect")]
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    bindgroup::get_bind_group_data,
+    compose, parse_wgsl, validate_module,
+    wgsl::{get_vertex_input_structs, member_name},
+    CreateModuleError, WriteOptions,
+};
+
+/// Information about a WGSL shader used to generate the Rust bindings.
+///
+/// This is intended for tools like material editors or asset pipelines
+/// that need the same layout information as the generated code.
+/// Types are described using their names in WGSL like `vec4<f32>`.
+///
+/// Only the generated vertex attributes are created from [ShaderReflection::vertex_inputs].
+/// Bind groups, structs, override constants, and entry points are generated directly from the naga module,
+/// so the other fields describe the same shader but aren't used by the code generator.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "reflect", derive(Serialize, Deserialize))]
+pub struct ShaderReflection {
+    pub bind_groups: Vec<BindGroupReflection>,
+    pub structs: Vec<StructReflection>,
+    pub vertex_inputs: Vec<VertexInputReflection>,
+    pub override_constants: Vec<OverrideConstantReflection>,
+    pub entry_points: Vec<EntryPointReflection>,
+}
+
+/// A bind group and its bindings sorted by binding index.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "reflect", derive(Serialize, Deserialize))]
+pub struct BindGroupReflection {
+    pub group: u32,
+    pub bindings: Vec<BindingReflection>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "reflect", derive(Serialize, Deserialize))]
+pub struct BindingReflection {
+    pub name: Option<String>,
+    pub binding: u32,
+    pub resource: ResourceReflection,
+    pub ty: String,
+    /// The size in bytes for buffers.
+    /// Runtime sized arrays only include the size of a single element.
+    pub size: Option<u32>,
+}
+
+/// The kind of resource for a binding.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "reflect",
+    derive(Serialize, Deserialize),
+    serde(rename_all = "snake_case")
+)]
+pub enum ResourceReflection {
+    UniformBuffer,
+    StorageBuffer { read_only: bool },
+    Texture,
+    StorageTexture,
+    Sampler,
+    AccelerationStructure,
+    BindingArray,
+}
+
+/// A struct with member offsets and sizes using the WGSL memory layout.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "reflect", derive(Serialize, Deserialize))]
+pub struct StructReflection {
+    pub name: String,
+    pub size: u32,
+    pub alignment: u32,
+    pub members: Vec<StructMemberReflection>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "reflect", derive(Serialize, Deserialize))]
+pub struct StructMemberReflection {
+    /// The name of the field in the generated Rust struct.
+    pub name: String,
+    pub ty: String,
+    pub offset: u32,
+    pub size: u32,
+}
+
+/// A vertex input struct with an attribute for each member with a location.
+///
+/// Attribute offsets depend on the layout of the generated Rust struct and are not included.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "reflect", derive(Serialize, Deserialize))]
+pub struct VertexInputReflection {
+    pub name: String,
+    pub attributes: Vec<VertexAttributeReflection>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "reflect", derive(Serialize, Deserialize))]
+pub struct VertexAttributeReflection {
+    pub name: String,
+    pub location: u32,
+    /// Serialized using the WebGPU name like `float32x3`.
+    pub format: wgpu::VertexFormat,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "reflect", derive(Serialize, Deserialize))]
+pub struct OverrideConstantReflection {
+    pub name: Option<String>,
+    pub id: Option<u16>,
+    pub ty: String,
+    /// `true` if the override has no default and must be set when creating the pipeline.
+    pub required: bool,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "reflect", derive(Serialize, Deserialize))]
+pub struct EntryPointReflection {
+    pub name: String,
+    pub stage: StageReflection,
+    /// The workgroup size for compute entries.
+    pub workgroup_size: Option<[u32; 3]>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "reflect",
+    derive(Serialize, Deserialize),
+    serde(rename_all = "snake_case")
+)]
+pub enum StageReflection {
+    Vertex,
+    Fragment,
+    Compute,
+}
+
+/// Collect the bind groups, structs, vertex inputs, override constants, and entry points of a WGSL shader.
+///
+/// The [WriteOptions::defines] are applied before parsing,
+/// and the module is validated using [WriteOptions::validation_flags] and [WriteOptions::capabilities].
+/// Other options don't affect the reflection information.
+/// Serializing with `ShaderReflection::to_json` requires the `reflect` feature.
+///
+/// # Examples
+/**
+```rust no_run
+// build.rs
+# let wgsl_source = String::new();
+let options = wgsl_to_wgpu::WriteOptions::default();
+let reflection = wgsl_to_wgpu::reflect(&wgsl_source, options).unwrap();
+for group in &reflection.bind_groups {
+    println!("group {} has {} bindings", group.group, group.bindings.len());
+}
+```
+ */
+pub fn reflect(
+    wgsl_source: &str,
+    options: WriteOptions,
+) -> Result<ShaderReflection, CreateModuleError> {
+    let wgsl_source = compose::preprocess(wgsl_source, &options.defines)?;
+    let module = parse_wgsl(&wgsl_source, |e| e.emit_to_string(&wgsl_source))?;
+
+    // Validate the module to catch errors like invalid layouts.
+    validate_module(&module, &options).map_err(|e| CreateModuleError::ValidationError {
+        message: e.emit_to_string(&wgsl_source),
+    })?;
+
+    ShaderReflection::from_module(&module)
+}
+
+impl ShaderReflection {
+    /// Collect the reflection information for a validated module.
+    pub fn from_module(module: &naga::Module) -> Result<Self, CreateModuleError> {
+        let gctx = module.to_ctx();
+        let mut layouter = naga::proc::Layouter::default();
+        layouter
+            .update(gctx)
+            .map_err(|e| CreateModuleError::ValidationError {
+                message: e.to_string(),
+            })?;
+
+        let type_name = |ty: naga::Handle<naga::Type>| ty.to_wgsl(&gctx);
+        // Alignments are powers of two, so rounding up 1 gives the alignment itself.
+        let alignment = |ty: naga::Handle<naga::Type>| layouter[ty].alignment.round_up(1);
+
+        let bind_groups = get_bind_group_data(module)?
+            .into_iter()
+            .map(|(group, data)| {
+                let mut bindings: Vec<_> = data
+                    .bindings
+                    .iter()
+                    .map(|b| BindingReflection {
+                        name: Some(b.name.clone()),
+                        binding: b.binding_index,
+                        resource: resource(b.binding_type, b.address_space),
+                        ty: b
+                            .binding_type
+                            .name
+                            .clone()
+                            .unwrap_or_else(|| b.binding_type.inner.to_wgsl(&gctx)),
+                        size: matches!(
+                            b.address_space,
+                            naga::AddressSpace::Uniform | naga::AddressSpace::Storage { .. }
+                        )
+                        .then(|| b.binding_type.inner.size(gctx)),
+                    })
+                    .collect();
+                bindings.sort_by_key(|b| b.binding);
+                BindGroupReflection { group, bindings }
+            })
+            .collect();
+
+        let structs = module
+            .types
+            .iter()
+            .filter_map(|(handle, ty)| match &ty.inner {
+                naga::TypeInner::Struct { members, span } => Some(StructReflection {
+                    name: type_name(handle),
+                    size: *span,
+                    alignment: alignment(handle),
+                    members: members
+                        .iter()
+                        .enumerate()
+                        .map(|(i, m)| StructMemberReflection {
+                            name: member_name(m, i),
+                            ty: type_name(m.ty),
+                            offset: m.offset,
+                            size: layouter[m.ty].size,
+                        })
+                        .collect(),
+                }),
+                _ => None,
+            })
+            .collect();
+
+        let mut vertex_inputs = Vec::new();
+        for input in get_vertex_input_structs(module) {
+            let mut attributes = Vec::new();
+            for (location, name, m) in &input.fields {
+                let format = crate::wgsl::vertex_format(&module.types[m.ty]).ok_or_else(|| {
+                    CreateModuleError::UnsupportedVertexFormat {
+                        attribute: format!("{}.{name}", input.type_name),
+                        ty: type_name(m.ty),
+                    }
+                })?;
+                attributes.push(VertexAttributeReflection {
+                    name: name.clone(),
+                    location: *location,
+                    format,
+                });
+            }
+            vertex_inputs.push(VertexInputReflection {
+                name: input.type_name.to_string(),
+                attributes,
+            });
+        }
+
+        let override_constants = module
+            .overrides
+            .iter()
+            .map(|(_, o)| OverrideConstantReflection {
+                name: o.name.clone(),
+                id: o.id,
+                ty: type_name(o.ty),
+                required: o.init.is_none(),
+            })
+            .collect();
+
+        let entry_points = module
+            .entry_points
+            .iter()
+            .map(|e| EntryPointReflection {
+                name: e.name.clone(),
+                stage: match e.stage {
+                    naga::ShaderStage::Vertex => StageReflection::Vertex,
+                    naga::ShaderStage::Fragment => StageReflection::Fragment,
+                    naga::ShaderStage::Compute => StageReflection::Compute,
+                },
+                workgroup_size: (e.stage == naga::ShaderStage::Compute).then_some(e.workgroup_size),
+            })
+            .collect();
+
+        Ok(Self {
+            bind_groups,
+            structs,
+            vertex_inputs,
+            override_constants,
+            entry_points,
+        })
+    }
+
+    /// Serialize the reflection information as pretty printed JSON.
+    ///
+    /// Requires the `reflect` feature.
+    #[cfg(feature = "reflect")]
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).unwrap()
+    }
+}
+
+fn resource(ty: &naga::Type, address_space: naga::AddressSpace) -> ResourceReflection {
+    match (&ty.inner, address_space) {
+        (_, naga::AddressSpace::Uniform) => ResourceReflection::UniformBuffer,
+        (_, naga::AddressSpace::Storage { access }) => ResourceReflection::StorageBuffer {
+            read_only: !access.contains(naga::StorageAccess::STORE),
+        },
+        (naga::TypeInner::Image { class, .. }, _) => match class {
+            naga::ImageClass::Storage { .. } => ResourceReflection::StorageTexture,
+            _ => ResourceReflection::Texture,
+        },
+        (naga::TypeInner::Sampler { .. }, _) => ResourceReflection::Sampler,
+        (naga::TypeInner::AccelerationStructure, _) => ResourceReflection::AccelerationStructure,
+        _ => ResourceReflection::BindingArray,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use indoc::indoc;
+
+    #[test]
+    fn reflect_shader() {
+        let source = indoc! {r#"
+            struct VertexInput {
+                @location(0) position: vec3<f32>,
+                @location(1) uv: vec2<f32>,
+            }
+
+            struct Uniforms {
+                color: vec3<f32>,
+                scale: f32,
+                transform: mat4x4<f32>,
+            }
+
+            override brightness: f32;
+            @id(1) override gamma: f32 = 2.2;
+
+            @group(0) @binding(0) var<uniform> uniforms: Uniforms;
+            @group(0) @binding(1) var<storage, read> values: array<vec4<f32>>;
+            @group(1) @binding(0) var color_texture: texture_2d<f32>;
+            @group(1) @binding(1) var color_sampler: sampler;
+
+            @vertex
+            fn vs_main(in: VertexInput) -> @builtin(position) vec4<f32> {
+                return uniforms.transform * vec4(in.position * uniforms.scale, 1.0);
+            }
+
+            @fragment
+            fn fs_main() -> @location(0) vec4<f32> {
+                return vec4(uniforms.color * brightness, 1.0) + values[0];
+            }
+
+            @compute @workgroup_size(8, 8)
+            fn main() {}
+        "#};
+
+        let reflection = reflect(source, WriteOptions::default()).unwrap();
+
+        assert_eq!(
+            vec![
+                BindGroupReflection {
+                    group: 0,
+                    bindings: vec![
+                        BindingReflection {
+                            name: Some("uniforms".to_owned()),
+                            binding: 0,
+                            resource: ResourceReflection::UniformBuffer,
+                            ty: "Uniforms".to_owned(),
+                            size: Some(80),
+                        },
+                        BindingReflection {
+                            name: Some("values".to_owned()),
+                            binding: 1,
+                            resource: ResourceReflection::StorageBuffer { read_only: true },
+                            ty: "array<vec4<f32>>".to_owned(),
+                            size: Some(16),
+                        },
+                    ],
+                },
+                BindGroupReflection {
+                    group: 1,
+                    bindings: vec![
+                        BindingReflection {
+                            name: Some("color_texture".to_owned()),
+                            binding: 0,
+                            resource: ResourceReflection::Texture,
+                            ty: "texture_2d<f32>".to_owned(),
+                            size: None,
+                        },
+                        BindingReflection {
+                            name: Some("color_sampler".to_owned()),
+                            binding: 1,
+                            resource: ResourceReflection::Sampler,
+                            ty: "sampler".to_owned(),
+                            size: None,
+                        },
+                    ],
+                },
+            ],
+            reflection.bind_groups
+        );
+
+        let uniforms = &reflection.structs[1];
+        assert_eq!("Uniforms", uniforms.name);
+        assert_eq!((80, 16), (uniforms.size, uniforms.alignment));
+        assert_eq!(
+            vec![
+                ("color", "vec3<f32>", 0, 12),
+                ("scale", "f32", 12, 4),
+                ("transform", "mat4x4<f32>", 16, 64),
+            ],
+            uniforms
+                .members
+                .iter()
+                .map(|m| (m.name.as_str(), m.ty.as_str(), m.offset, m.size))
+                .collect::<Vec<_>>()
+        );
+
+        assert_eq!(
+            vec![VertexInputReflection {
+                name: "VertexInput".to_owned(),
+                attributes: vec![
+                    VertexAttributeReflection {
+                        name: "position".to_owned(),
+                        location: 0,
+                        format: wgpu::VertexFormat::Float32x3,
+                    },
+                    VertexAttributeReflection {
+                        name: "uv".to_owned(),
+                        location: 1,
+                        format: wgpu::VertexFormat::Float32x2,
+                    },
+                ],
+            }],
+            reflection.vertex_inputs
+        );
+
+        assert_eq!(
+            vec![
+                OverrideConstantReflection {
+                    name: Some("brightness".to_owned()),
+                    id: None,
+                    ty: "f32".to_owned(),
+                    required: true,
+                },
+                OverrideConstantReflection {
+                    name: Some("gamma".to_owned()),
+                    id: Some(1),
+                    ty: "f32".to_owned(),
+                    required: false,
+                },
+            ],
+            reflection.override_constants
+        );
+
+        assert_eq!(
+            vec![
+                ("vs_main", StageReflection::Vertex, None),
+                ("fs_main", StageReflection::Fragment, None),
+                ("main", StageReflection::Compute, Some([8, 8, 1])),
+            ],
+            reflection
+                .entry_points
+                .iter()
+                .map(|e| (e.name.as_str(), e.stage, e.workgroup_size))
+                .collect::<Vec<_>>()
+        );
+    }
+
+    #[cfg(feature = "reflect")]
+    #[test]
+    fn reflect_json() {
+        let source = indoc! {r#"
+            @group(0) @binding(0) var<storage, read_write> values: array<u32>;
+
+            @compute @workgroup_size(64)
+            fn main() {}
+        "#};
+
+        let reflection = reflect(source, WriteOptions::default()).unwrap();
+        let json = reflection.to_json();
+        assert!(
+            json.contains(
+                r#""resource": {
+            "storage_buffer": {
+              "read_only": false
+            }
+          }"#
+            ),
+            "{json}"
+        );
+        assert_eq!(reflection, serde_json::from_str(&json).unwrap());
+    }
+
+    #[test]
+    fn reflect_defines() {
+        let source = indoc! {r#"
+            #ifdef TEXTURED
+            @group(0) @binding(0) var color_texture: texture_2d<f32>;
+            #endif
+
+            @fragment
+            fn main() -> @location(0) vec4<f32> {
+                return vec4(1.0);
+            }
+        "#};
+
+        let reflection = reflect(source, WriteOptions::default()).unwrap();
+        assert!(reflection.bind_groups.is_empty());
+
+        let options = WriteOptions {
+            defines: vec![("TEXTURED".to_owned(), String::new())],
+            ..Default::default()
+        };
+        let reflection = reflect(source, options).unwrap();
+        assert_eq!(
+            vec![BindGroupReflection {
+                group: 0,
+                bindings: vec![BindingReflection {
+                    name: Some("color_texture".to_owned()),
+                    binding: 0,
+                    resource: ResourceReflection::Texture,
+                    ty: "texture_2d<f32>".to_owned(),
+                    size: None,
+                }]
+            }],
+            reflection.bind_groups
+        );
+    }
+
+    #[test]
+    fn reflect_unsupported_vertex_format() {
+        // Validation would also reject this, so use the unvalidated module.
+        let source = indoc! {r#"
+            struct VertexInput {
+                @location(0) position: vec3<f32>,
+                @location(1) visible: u32,
+                @location(2) flags: vec3<bool>,
+            };
+
+            @vertex
+            fn main(in: VertexInput) -> @builtin(position) vec4<f32> {
+                return vec4(in.position, 1.0);
+            }
+        "#};
+        let module = naga::front::wgsl::parse_str(source).unwrap();
+        assert!(matches!(
+            ShaderReflection::from_module(&module),
+            Err(CreateModuleError::UnsupportedVertexFormat { attribute, ty })
+                if attribute == "VertexInput.flags" && ty == "vec3<bool>"
+        ));
+    }
+
+    #[test]
+    fn reflect_unnamed_members() {
+        let source = indoc! {r#"
+            struct VertexInput {
+                @location(0) position: vec3<f32>,
+                @location(1) uv: vec2<f32>,
+            };
+
+            @vertex
+            fn main(in: VertexInput) -> @builtin(position) vec4<f32> {
+                return vec4(in.position, 1.0);
+            }
+        "#};
+        let mut module = naga::front::wgsl::parse_str(source).unwrap();
+
+        // Members from SPIR-V may not have names.
+        let (handle, ty) = module
+            .types
+            .iter()
+            .find(|(_, ty)| ty.name.as_deref() == Some("VertexInput"))
+            .unwrap();
+        let mut ty = ty.clone();
+        if let naga::TypeInner::Struct { members, .. } = &mut ty.inner {
+            for m in members {
+                m.name = None;
+            }
+        }
+        module.types.replace(handle, ty);
+
+        let reflection = ShaderReflection::from_module(&module).unwrap();
+        assert_eq!(
+            vec!["member_0", "member_1"],
+            reflection.structs[0]
+                .members
+                .iter()
+                .map(|m| m.name.as_str())
+                .collect::<Vec<_>>()
+        );
+        assert_eq!(
+            vec!["member_0", "member_1"],
+            reflection.vertex_inputs[0]
+                .attributes
+                .iter()
+                .map(|a| a.name.as_str())
+                .collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn reflect_parse_error() {
+        assert!(matches!(
+            reflect("fn main( {", WriteOptions::default()),
+            Err(CreateModuleError::ParseError { .. })
+        ));
+    }
+}
@@ -1,5 +1,10 @@
-use crate::bindgroup::BindGroup;
-use proc_macro2::{Span, TokenStream};
+use crate::{
+    bindgroup::BindGroup,
+    quote_features,
+    wgsl::{required_features, required_limits},
+    ShaderSourceMode,
+};
+use proc_macro2::{Literal, Span, TokenStream};
 use quote::quote;
 
 fn define_create_pipeline_layout(
@@ -59,25 +64,121 @@ fn define_create_pipeline_layout(
     }
 }
 
+/// The expression for `Shader::SOURCE`.
+pub fn shader_source(
+    module: &naga::Module,
+    module_info: &naga::valid::ModuleInfo,
+    wgsl_source: Option<&str>,
+    mode: &ShaderSourceMode,
+) -> TokenStream {
+    match (mode, wgsl_source) {
+        (ShaderSourceMode::Verbatim, Some(wgsl_source)) => quote!(#wgsl_source),
+        (ShaderSourceMode::IncludeStr(path), _) => quote!(include_str!(#path)),
+        _ => {
+            let wgsl_source = naga::back::wgsl::write_string(
+                module,
+                module_info,
+                // Without this, Naga changes `let A: f32 = 0f;` to `const: A = 0f;` which it then doesn't think is valid.
+                naga::back::wgsl::WriterFlags::EXPLICIT_TYPES,
+            )
+            .unwrap();
+            quote!(#wgsl_source)
+        }
+    }
+}
+
+/// The `Shader::SOURCE` constants and the `wgpu::ShaderSource` for creating the shader module.
+pub struct ShaderSource {
+    pub constants: TokenStream,
+    pub descriptor: TokenStream,
+}
+
+impl ShaderSource {
+    /// WGSL source from the expression returned by [shader_source].
+    pub fn wgsl(source: TokenStream) -> Self {
+        Self {
+            constants: quote!(pub const SOURCE: &'static str = #source;),
+            descriptor: quote! {
+                wgpu::ShaderSource::Wgsl(std::borrow::Cow::Borrowed(Self::SOURCE))
+            },
+        }
+    }
+
+    /// GLSL source for a single shader stage.
+    #[cfg_attr(not(feature = "glsl-in"), allow(dead_code))]
+    pub fn glsl(source: &str, stage: naga::ShaderStage, defines: &[(&str, &str)]) -> Self {
+        let stage = match stage {
+            naga::ShaderStage::Vertex => quote!(wgpu::naga::ShaderStage::Vertex),
+            naga::ShaderStage::Fragment => quote!(wgpu::naga::ShaderStage::Fragment),
+            naga::ShaderStage::Compute => quote!(wgpu::naga::ShaderStage::Compute),
+        };
+        let defines = defines.iter().map(|(name, value)| quote!((#name, #value)));
+        Self {
+            constants: quote! {
+                pub const SOURCE: &'static str = #source;
+                pub const STAGE: wgpu::naga::ShaderStage = #stage;
+                pub const DEFINES: &'static [(&'static str, &'static str)] = &[#(#defines),*];
+            },
+            descriptor: quote! {
+                wgpu::ShaderSource::Glsl {
+                    shader: std::borrow::Cow::Borrowed(Self::SOURCE),
+                    stage: Self::STAGE,
+                    defines: Self::DEFINES
+                        .iter()
+                        .map(|(name, value)| (name.to_string(), value.to_string()))
+                        .collect(),
+                }
+            },
+        }
+    }
+
+    /// SPIR-V source as 32-bit words.
+    #[cfg_attr(not(feature = "spv-in"), allow(dead_code))]
+    pub fn spirv(words: &[u32]) -> Self {
+        let words = words.iter().map(|w| Literal::u32_unsuffixed(*w));
+        Self {
+            constants: quote!(pub const SOURCE: &'static [u32] = &[#(#words),*];),
+            descriptor: quote! {
+                wgpu::ShaderSource::SpirV(std::borrow::Cow::Borrowed(Self::SOURCE))
+            },
+        }
+    }
+}
+
 pub fn define_shader(
     module: &naga::Module,
+    module_info: &naga::valid::ModuleInfo,
+    shader_source: ShaderSource,
+    translated_sources: TokenStream,
     bind_groups: &[BindGroup],
     push_constant_range: Option<TokenStream>,
 ) -> TokenStream {
-    let mut validator = naga::valid::Validator::new(
-        // TODO: We should probably make this part of the input options.
-        naga::valid::ValidationFlags::empty(),
-        // TODO: We should probably make this part of the input options.
-        naga::valid::Capabilities::all(),
-    );
-    let module_info = validator.validate(module).unwrap();
-    let wgsl_source = naga::back::wgsl::write_string(
-        module,
-        &module_info,
-        // Without this, Naga changes `let A: f32 = 0f;` to `const: A = 0f;` which it then doesn't think is valid.
-        naga::back::wgsl::WriterFlags::EXPLICIT_TYPES,
-    )
-    .unwrap();
+    let required_features = quote_features(required_features(module));
+
+    let limits: Vec<_> = required_limits(module, module_info)
+        .into_iter()
+        .map(|(name, value)| {
+            let name = syn::Ident::new(name, Span::call_site());
+            let value = Literal::u32_unsuffixed(value);
+            quote!(#name: limits.#name.max(#value))
+        })
+        .collect();
+    let required_limits = if limits.is_empty() {
+        quote!(wgpu::Limits::downlevel_webgl2_defaults())
+    } else {
+        quote! {
+            let limits = wgpu::Limits::downlevel_webgl2_defaults();
+            wgpu::Limits {
+                #(#limits,)*
+                ..limits
+            }
+        }
+    };
+
+    let ShaderSource {
+        constants: source_constants,
+        descriptor: source_descriptor,
+    } = shader_source;
 
     let all_bind_group_args: Vec<_> = bind_groups.iter().flat_map(|g| &g.new_args).collect();
     let create_pipeline_layout =
@@ -96,6 +197,31 @@ pub fn define_shader(
             #(#pipeline_layout_key_fields,)*
         }
 
+        #[derive(Debug, Clone, PartialEq, Eq)]
+        pub enum UnsupportedDeviceError {
+            MissingFeatures(wgpu::Features),
+            InsufficientLimit {
+                name: &'static str,
+                required: u64,
+                supported: u64,
+            },
+        }
+
+        impl std::fmt::Display for UnsupportedDeviceError {
+            fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                match self {
+                    Self::MissingFeatures(features) => {
+                        write!(f, "device does not support required features {features:?}")
+                    }
+                    Self::InsufficientLimit { name, required, supported } => {
+                        write!(f, "device limit {name} is {supported} but the shader requires {required}")
+                    }
+                }
+            }
+        }
+
+        impl std::error::Error for UnsupportedDeviceError {}
+
         pub struct Shader {
             device: std::sync::Arc<wgpu::Device>,
             shader_module: std::sync::Arc<wgpu::ShaderModule>,
@@ -115,22 +241,178 @@ pub fn define_shader(
 
         #[bon::bon]
         impl Shader {
-            pub const SOURCE: &'static str = #wgsl_source;
+            #source_constants
 
-            pub fn new(device: std::sync::Arc<wgpu::Device>) -> Self {
+            pub const REQUIRED_FEATURES: wgpu::Features = #required_features;
+
+            /// The minimum limits for this shader starting from [wgpu::Limits::downlevel_webgl2_defaults].
+            pub fn required_limits() -> wgpu::Limits {
+                #required_limits
+            }
+
+            /// Check that `device` supports the features and limits required by this shader.
+            pub fn check_device(device: &wgpu::Device) -> Result<(), UnsupportedDeviceError> {
+                let missing_features = Self::REQUIRED_FEATURES.difference(device.features());
+                if !missing_features.is_empty() {
+                    return Err(UnsupportedDeviceError::MissingFeatures(missing_features));
+                }
+
+                let mut result = Ok(());
+                Self::required_limits().check_limits_with_fail_fn(
+                    &device.limits(),
+                    true,
+                    |name, required, supported| {
+                        result = Err(UnsupportedDeviceError::InsufficientLimit { name, required, supported });
+                    },
+                );
+                result
+            }
+
+            pub fn new(device: std::sync::Arc<wgpu::Device>) -> Result<Self, UnsupportedDeviceError> {
+                Self::check_device(&device)?;
                 let shader_module = device.create_shader_module(wgpu::ShaderModuleDescriptor {
                     label: None,
-                    source: wgpu::ShaderSource::Wgsl(std::borrow::Cow::Borrowed(Self::SOURCE)),
+                    source: #source_descriptor,
                 });
-                let shader_module = std::sync::Arc::new(shader_module);
+                Ok(Self::from_shader_module(device, shader_module))
+            }
+
+            fn from_shader_module(device: std::sync::Arc<wgpu::Device>, shader_module: wgpu::ShaderModule) -> Self {
                 Self {
                     device,
-                    shader_module,
+                    shader_module: std::sync::Arc::new(shader_module),
                     pipeline_layout_cache: Default::default(),
                 }
             }
 
+            #translated_sources
+
             #create_pipeline_layout
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::assert_tokens_eq;
+    use indoc::indoc;
+
+    fn module_and_info(source: &str) -> (naga::Module, naga::valid::ModuleInfo) {
+        let module = naga::front::wgsl::parse_str(source).unwrap();
+        let module_info = naga::valid::Validator::new(
+            naga::valid::ValidationFlags::all(),
+            naga::valid::Capabilities::all(),
+        )
+        .validate(&module)
+        .unwrap();
+        (module, module_info)
+    }
+
+    const SOURCE: &str = indoc! {r#"
+        // Comments are removed by naga.
+        @fragment
+        fn main() {
+            let x = 1.0;
+        }
+    "#};
+
+    #[test]
+    fn shader_source_normalized() {
+        let (module, module_info) = module_and_info(SOURCE);
+        let actual = shader_source(
+            &module,
+            &module_info,
+            Some(SOURCE),
+            &ShaderSourceMode::Normalized,
+        );
+
+        assert_tokens_eq!(
+            quote!("@fragment \nfn main() {\n    return;\n}\n"),
+            actual
+        );
+    }
+
+    #[test]
+    fn shader_source_verbatim() {
+        let (module, module_info) = module_and_info(SOURCE);
+        let actual = shader_source(
+            &module,
+            &module_info,
+            Some(SOURCE),
+            &ShaderSourceMode::Verbatim,
+        );
+
+        assert_tokens_eq!(quote!(#SOURCE), actual);
+    }
+
+    #[test]
+    fn shader_source_verbatim_without_source() {
+        let (module, module_info) = module_and_info(SOURCE);
+        let actual = shader_source(&module, &module_info, None, &ShaderSourceMode::Verbatim);
+
+        assert_tokens_eq!(
+            quote!("@fragment \nfn main() {\n    return;\n}\n"),
+            actual
+        );
+    }
+
+    #[test]
+    fn shader_source_include_str() {
+        let (module, module_info) = module_and_info(SOURCE);
+        let actual = shader_source(
+            &module,
+            &module_info,
+            Some(SOURCE),
+            &ShaderSourceMode::IncludeStr("shader.wgsl".to_owned()),
+        );
+
+        assert_tokens_eq!(quote!(include_str!("shader.wgsl")), actual);
+    }
+
+    #[test]
+    fn shader_source_glsl() {
+        let source = "#version 450\nvoid main() {}\n";
+        let actual = ShaderSource::glsl(source, naga::ShaderStage::Compute, &[("SCALE", "2")]);
+
+        assert_tokens_eq!(
+            quote! {
+                pub const SOURCE: &'static str = "#version 450\nvoid main() {}\n";
+                pub const STAGE: wgpu::naga::ShaderStage = wgpu::naga::ShaderStage::Compute;
+                pub const DEFINES: &'static [(&'static str, &'static str)] = &[("SCALE", "2")];
+            },
+            actual.constants
+        );
+        assert_tokens_eq!(
+            quote! {
+                wgpu::ShaderSource::Glsl {
+                    shader: std::borrow::Cow::Borrowed(Self::SOURCE),
+                    stage: Self::STAGE,
+                    defines: Self::DEFINES
+                        .iter()
+                        .map(|(name, value)| (name.to_string(), value.to_string()))
+                        .collect(),
+                }
+            },
+            actual.descriptor
+        );
+    }
+
+    #[test]
+    fn shader_source_spirv() {
+        let actual = ShaderSource::spirv(&[0x07230203, 0x00010000]);
+
+        assert_tokens_eq!(
+            quote! {
+                pub const SOURCE: &'static [u32] = &[119734787, 65536];
+            },
+            actual.constants
+        );
+        assert_tokens_eq!(
+            quote! {
+                wgpu::ShaderSource::SpirV(std::borrow::Cow::Borrowed(Self::SOURCE))
+            },
+            actual.descriptor
+        );
+    }
+}
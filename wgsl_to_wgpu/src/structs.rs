@@ -5,9 +5,15 @@ use proc_macro2::{Literal, Span, TokenStream};
 use quote::quote;
 use syn::Ident;
 
-use crate::{wgsl::rust_type, WriteOptions};
+use crate::{
+    wgsl::{get_vertex_input_structs, member_name, rust_type, struct_name},
+    CreateModuleError, MatrixVectorTypes, WriteOptions,
+};
 
-pub fn structs(module: &naga::Module, options: WriteOptions) -> TokenStream {
+pub fn structs(
+    module: &naga::Module,
+    options: &WriteOptions,
+) -> Result<TokenStream, CreateModuleError> {
     // Initialize the layout calculator provided by naga.
     let mut layouter = naga::proc::Layouter::default();
     layouter.update(module.to_ctx()).unwrap();
@@ -17,6 +23,14 @@ pub fn structs(module: &naga::Module, options: WriteOptions) -> TokenStream {
         add_types_recursive(&mut global_variable_types, module, g.1.ty);
     }
 
+    // Named constants can also be composite values of struct types.
+    let mut constant_types = HashSet::new();
+    for c in module.constants.iter() {
+        if c.1.name.is_some() {
+            add_types_recursive(&mut constant_types, module, c.1.ty);
+        }
+    }
+
     // Create matching Rust structs for WGSL structs.
     // This is a UniqueArena, so each struct will only be generated once.
     let structs = module
@@ -36,9 +50,21 @@ pub fn structs(module: &naga::Module, options: WriteOptions) -> TokenStream {
                     .iter()
                     .any(|e| e.function.arguments.iter().any(|a| a.ty == *h))
                 || global_variable_types.contains(h)
+                || constant_types.contains(h)
         })
         .filter_map(|(t_handle, t)| {
             if let naga::TypeInner::Struct { members, .. } = &t.inner {
+                if let Some(path) = type_override(t, options) {
+                    return Some(Ok(type_alias(
+                        module,
+                        t,
+                        path,
+                        &layouter,
+                        t_handle,
+                        options,
+                        &global_variable_types,
+                    )));
+                }
                 Some(rust_struct(
                     t,
                     members,
@@ -51,9 +77,86 @@ pub fn structs(module: &naga::Module, options: WriteOptions) -> TokenStream {
             } else {
                 None
             }
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    Ok(quote!(#(#structs)*))
+}
+
+/// Check that each type in [WriteOptions::type_overrides] can replace a generated struct.
+pub fn check_type_overrides(
+    module: &naga::Module,
+    options: &WriteOptions,
+) -> Result<(), CreateModuleError> {
+    let vertex_inputs = get_vertex_input_structs(module);
+    for (name, path) in &options.type_overrides {
+        let error = |message: &str| CreateModuleError::InvalidTypeOverride {
+            name: name.to_string(),
+            message: message.to_owned(),
+        };
+
+        let is_struct = module.types.iter().any(|(_, t)| {
+            t.name.as_ref() == Some(name) && matches!(t.inner, naga::TypeInner::Struct { .. })
         });
+        if !is_struct {
+            return Err(error("no struct with this name in the module"));
+        }
+        if vertex_inputs.iter().any(|v| v.type_name == name) {
+            return Err(error("vertex input structs can't be overridden"));
+        }
+        syn::parse_str::<syn::Type>(path).map_err(|e| error(&e.to_string()))?;
+    }
+    Ok(())
+}
+
+fn type_override<'a>(t: &naga::Type, options: &'a WriteOptions) -> Option<&'a str> {
+    options
+        .type_overrides
+        .iter()
+        .find(|(name, _)| t.name.as_ref() == Some(name))
+        .map(|(_, path)| path.as_str())
+}
+
+fn type_alias(
+    module: &naga::Module,
+    t: &naga::Type,
+    path: &str,
+    layouter: &naga::proc::Layouter,
+    t_handle: naga::Handle<naga::Type>,
+    options: &WriteOptions,
+    global_variable_types: &HashSet<Handle<Type>>,
+) -> TokenStream {
+    let name = Ident::new(&struct_name(module, t), Span::call_site());
+    let path: syn::Type = syn::parse_str(path).unwrap();
+
+    // The overridden type should have the same size as the generated struct.
+    // Field offsets can't be checked since the fields may have different names.
+    let assert_layout =
+        if options.derive_bytemuck_host_shareable && global_variable_types.contains(&t_handle) {
+            assert_size(&name, layouter, t_handle)
+        } else {
+            quote!()
+        };
+
+    quote! {
+        pub type #name = #path;
+        #assert_layout
+    }
+}
 
-    quote!(#(#structs)*)
+fn assert_size(
+    struct_name: &Ident,
+    layouter: &naga::proc::Layouter,
+    t_handle: naga::Handle<naga::Type>,
+) -> TokenStream {
+    let layout = layouter[t_handle];
+
+    // TODO: Does the Rust alignment matter if it's copied to a buffer anyway?
+    let struct_size = Literal::usize_unsuffixed(layout.size as usize);
+    let assert_size_text = format!("size of {struct_name} does not match WGSL");
+    quote! {
+        const _: () = assert!(std::mem::size_of::<#struct_name>() == #struct_size, #assert_size_text);
+    }
 }
 
 fn rust_struct(
@@ -62,48 +165,39 @@ fn rust_struct(
     layouter: &naga::proc::Layouter,
     t_handle: naga::Handle<naga::Type>,
     module: &naga::Module,
-    options: WriteOptions,
+    options: &WriteOptions,
     global_variable_types: &HashSet<Handle<Type>>,
-) -> TokenStream {
-    let struct_name = Ident::new(t.name.as_ref().unwrap(), Span::call_site());
+) -> Result<TokenStream, CreateModuleError> {
+    let struct_name = Ident::new(&struct_name(module, t), Span::call_site());
 
     // Skip builtins since they don't require user specified data.
     let members: Vec<_> = members
         .iter()
-        .filter(|m| !matches!(m.binding, Some(naga::Binding::BuiltIn(_))))
-        .cloned()
+        .enumerate()
+        .filter(|(_, m)| !matches!(m.binding, Some(naga::Binding::BuiltIn(_))))
+        .map(|(i, m)| (member_name(m, i), m.clone()))
         .collect();
 
     let assert_member_offsets: Vec<_> = members
         .iter()
-        .map(|m| {
-            let name = Ident::new(m.name.as_ref().unwrap(), Span::call_site());
+        .map(|(name, m)| {
+            let assert_text = format!("offset of {struct_name}.{name} does not match WGSL");
+
+            let name = Ident::new(name, Span::call_site());
             let rust_offset = quote!(std::mem::offset_of!(#struct_name, #name));
 
             let wgsl_offset = Literal::usize_unsuffixed(m.offset as usize);
 
-            let assert_text = format!(
-                "offset of {}.{} does not match WGSL",
-                t.name.as_ref().unwrap(),
-                m.name.as_ref().unwrap()
-            );
             quote! {
                 const _: () = assert!(#rust_offset == #wgsl_offset, #assert_text);
             }
         })
         .collect();
 
-    let layout = layouter[t_handle];
-
-    // TODO: Does the Rust alignment matter if it's copied to a buffer anyway?
-    let struct_size = Literal::usize_unsuffixed(layout.size as usize);
-    let assert_size_text = format!("size of {} does not match WGSL", t.name.as_ref().unwrap());
-    let assert_size = quote! {
-        const _: () = assert!(std::mem::size_of::<#struct_name>() == #struct_size, #assert_size_text);
-    };
+    let assert_size = assert_size(&struct_name, layouter, t_handle);
 
     let has_rts_array = struct_has_rts_array_member(&members, module);
-    let members = struct_members(&members, module, options);
+    let member_fields = struct_members(&members, module, options);
     let mut derives = Vec::new();
 
     derives.push(quote!(Debug));
@@ -121,13 +215,33 @@ fn rust_struct(
     // This allows vertex input field types without padding like vec3 for positions.
     let is_host_shareable = global_variable_types.contains(&t_handle);
 
+    let runtime_array_error = |message: &str| CreateModuleError::UnsupportedRuntimeArray {
+        name: struct_name.to_string(),
+        message: message.to_owned(),
+    };
+
+    let derive_bytemuck = (options.derive_bytemuck_vertex && !is_host_shareable)
+        || (options.derive_bytemuck_host_shareable && is_host_shareable);
+    check_matrix_vector_types(
+        &struct_name,
+        &members,
+        module,
+        options,
+        derive_bytemuck,
+        is_host_shareable,
+    )?;
+
     if has_rts_array && !options.derive_encase_host_shareable {
-        panic!("Runtime-sized array fields are only supported with encase");
+        return Err(runtime_array_error(
+            "runtime-sized array fields are only supported with encase",
+        ));
     }
 
     if options.derive_bytemuck_vertex && !is_host_shareable {
         if has_rts_array {
-            panic!("Runtime-sized array fields are not supported with bytemuck");
+            return Err(runtime_array_error(
+                "runtime-sized array fields are not supported with bytemuck",
+            ));
         }
         derives.push(quote!(bytemuck::Pod));
         derives.push(quote!(bytemuck::Zeroable));
@@ -135,7 +249,9 @@ fn rust_struct(
 
     if options.derive_bytemuck_host_shareable && is_host_shareable {
         if has_rts_array {
-            panic!("Runtime-sized array fields are not supported with bytemuck");
+            return Err(runtime_array_error(
+                "runtime-sized array fields are not supported with bytemuck",
+            ));
         }
         derives.push(quote!(bytemuck::Pod));
         derives.push(quote!(bytemuck::Zeroable));
@@ -167,14 +283,14 @@ fn rust_struct(
     } else {
         quote!()
     };
-    quote! {
+    Ok(quote! {
         #repr_c
         #[derive(#(#derives),*)]
         pub struct #struct_name {
-            #(#members),*
+            #(#member_fields),*
         }
         #assert_layout
-    }
+    })
 }
 
 fn add_types_recursive(
@@ -198,15 +314,15 @@ fn add_types_recursive(
 }
 
 fn struct_members(
-    members: &[naga::StructMember],
+    members: &[(String, naga::StructMember)],
     module: &naga::Module,
-    options: WriteOptions,
+    options: &WriteOptions,
 ) -> Vec<TokenStream> {
     members
         .iter()
         .enumerate()
-        .map(|(index, member)| {
-            let member_name = Ident::new(member.name.as_ref().unwrap(), Span::call_site());
+        .map(|(index, (name, member))| {
+            let member_name = Ident::new(name, Span::call_site());
             let ty = &module.types[member.ty];
 
             if let naga::TypeInner::Array {
@@ -232,8 +348,69 @@ fn struct_members(
         .collect()
 }
 
-fn struct_has_rts_array_member(members: &[naga::StructMember], module: &naga::Module) -> bool {
-    members.iter().any(|m| {
+/// Check that the vector and matrix types of members have the WGSL layout and derived traits.
+fn check_matrix_vector_types(
+    struct_name: &Ident,
+    members: &[(String, naga::StructMember)],
+    module: &naga::Module,
+    options: &WriteOptions,
+    derive_bytemuck: bool,
+    is_host_shareable: bool,
+) -> Result<(), CreateModuleError> {
+    let format = options.matrix_vector_types;
+    for (name, m) in members {
+        let error = |message: &str| CreateModuleError::UnsupportedMatrixVectorType {
+            name: format!("{struct_name}.{name}"),
+            message: message.to_owned(),
+        };
+
+        // Arrays use the same type for their elements.
+        let mut ty = &module.types[m.ty].inner;
+        while let naga::TypeInner::Array { base, .. } = ty {
+            ty = &module.types[*base].inner;
+        }
+
+        // Non square matrices fall back to Rust arrays for cgmath and ultraviolet.
+        let (is_vector_or_matrix, has_unpadded_matrix) = match (format, ty) {
+            (MatrixVectorTypes::Mint, naga::TypeInner::Vector { .. }) => (true, false),
+            (MatrixVectorTypes::Mint, naga::TypeInner::Matrix { rows, .. }) => {
+                (true, *rows == naga::VectorSize::Tri)
+            }
+            (MatrixVectorTypes::Cgmath, naga::TypeInner::Vector { .. }) => (true, false),
+            (
+                MatrixVectorTypes::Cgmath | MatrixVectorTypes::Ultraviolet,
+                naga::TypeInner::Matrix { rows, columns, .. },
+            ) if rows == columns => (true, *rows == naga::VectorSize::Tri),
+            _ => (false, false),
+        };
+
+        if derive_bytemuck && is_vector_or_matrix {
+            match format {
+                MatrixVectorTypes::Mint => {
+                    return Err(error("mint types don't implement bytemuck traits"))
+                }
+                MatrixVectorTypes::Cgmath => {
+                    return Err(error("cgmath types don't implement bytemuck traits"))
+                }
+                _ => (),
+            }
+        }
+
+        // encase adds the padding between columns when writing the buffer.
+        if has_unpadded_matrix && is_host_shareable && !options.derive_encase_host_shareable {
+            return Err(error(
+                "matrices with three rows require column padding in host shareable structs, so use encase",
+            ));
+        }
+    }
+    Ok(())
+}
+
+fn struct_has_rts_array_member(
+    members: &[(String, naga::StructMember)],
+    module: &naga::Module,
+) -> bool {
+    members.iter().any(|(_, m)| {
         matches!(
             module.types[m.ty].inner,
             naga::TypeInner::Array {
@@ -253,7 +430,7 @@ mod tests {
 
     fn test_structs(wgsl: &str, rust: &str, options: WriteOptions) {
         let module = naga::front::wgsl::parse_str(wgsl).unwrap();
-        let structs = structs(&module, options);
+        let structs = structs(&module, &options).unwrap();
         assert_tokens_eq!(rust.parse().unwrap(), structs);
     }
 
@@ -290,6 +467,137 @@ mod tests {
         );
     }
 
+    #[test]
+    fn write_all_structs_mint() {
+        test_structs(
+            include_str!("data/struct/types.wgsl"),
+            include_str!("data/struct/types.mint.rs"),
+            WriteOptions {
+                matrix_vector_types: MatrixVectorTypes::Mint,
+                // Matrices with three rows need encase for column padding.
+                derive_encase_host_shareable: true,
+                ..Default::default()
+            },
+        );
+    }
+
+    #[test]
+    fn write_all_structs_cgmath() {
+        test_structs(
+            include_str!("data/struct/types.wgsl"),
+            include_str!("data/struct/types.cgmath.rs"),
+            WriteOptions {
+                matrix_vector_types: MatrixVectorTypes::Cgmath,
+                // Matrices with three rows need encase for column padding.
+                derive_encase_host_shareable: true,
+                ..Default::default()
+            },
+        );
+    }
+
+    #[test]
+    fn write_all_structs_ultraviolet() {
+        test_structs(
+            include_str!("data/struct/types.wgsl"),
+            include_str!("data/struct/types.ultraviolet.rs"),
+            WriteOptions {
+                matrix_vector_types: MatrixVectorTypes::Ultraviolet,
+                // Matrices with three rows need encase for column padding.
+                derive_encase_host_shareable: true,
+                ..Default::default()
+            },
+        );
+    }
+
+    #[test]
+    fn write_structs_unpadded_matrix() {
+        let source = indoc! {r#"
+            struct Uniforms {
+                a: mat3x2<f32>,
+                b: array<mat3x3<f32>, 2>,
+            };
+            var<uniform> u: Uniforms;
+
+            @fragment
+            fn main() {}
+        "#};
+        let module = naga::front::wgsl::parse_str(source).unwrap();
+
+        for format in [
+            MatrixVectorTypes::Mint,
+            MatrixVectorTypes::Cgmath,
+            MatrixVectorTypes::Ultraviolet,
+        ] {
+            let options = WriteOptions {
+                matrix_vector_types: format,
+                ..Default::default()
+            };
+            assert!(matches!(
+                structs(&module, &options),
+                Err(CreateModuleError::UnsupportedMatrixVectorType { name, .. }) if name == "Uniforms.b"
+            ));
+
+            let options = WriteOptions {
+                derive_encase_host_shareable: true,
+                ..options
+            };
+            assert!(structs(&module, &options).is_ok());
+        }
+    }
+
+    #[test]
+    fn write_structs_bytemuck_unsupported() {
+        let source = indoc! {r#"
+            struct VertexInput {
+                @location(0) position: vec3<f32>,
+            };
+
+            @vertex
+            fn main(in: VertexInput) -> @builtin(position) vec4<f32> {
+                return vec4(in.position, 1.0);
+            }
+        "#};
+        let module = naga::front::wgsl::parse_str(source).unwrap();
+
+        for (format, message) in [
+            (
+                MatrixVectorTypes::Mint,
+                "mint types don't implement bytemuck traits",
+            ),
+            (
+                MatrixVectorTypes::Cgmath,
+                "cgmath types don't implement bytemuck traits",
+            ),
+        ] {
+            assert_eq!(
+                Err(CreateModuleError::UnsupportedMatrixVectorType {
+                    name: "VertexInput.position".to_owned(),
+                    message: message.to_owned()
+                }),
+                structs(
+                    &module,
+                    &WriteOptions {
+                        matrix_vector_types: format,
+                        derive_bytemuck_vertex: true,
+                        ..Default::default()
+                    }
+                )
+                .map(|t| t.to_string())
+            );
+        }
+
+        // ultraviolet implements bytemuck traits with its bytemuck feature.
+        assert!(structs(
+            &module,
+            &WriteOptions {
+                matrix_vector_types: MatrixVectorTypes::Ultraviolet,
+                derive_bytemuck_vertex: true,
+                ..Default::default()
+            }
+        )
+        .is_ok());
+    }
+
     #[test]
     fn write_all_structs_encase_bytemuck() {
         test_structs(
@@ -352,7 +660,7 @@ mod tests {
 
         let actual = structs(
             &module,
-            WriteOptions {
+            &WriteOptions {
                 derive_bytemuck_vertex: false,
                 derive_bytemuck_host_shareable: false,
                 derive_encase_host_shareable: false,
@@ -361,7 +669,8 @@ mod tests {
                 rustfmt: true,
                 ..Default::default()
             },
-        );
+        )
+        .unwrap();
 
         assert_tokens_eq!(
             quote! {
@@ -398,7 +707,7 @@ mod tests {
 
         let actual = structs(
             &module,
-            WriteOptions {
+            &WriteOptions {
                 derive_bytemuck_vertex: true,
                 derive_bytemuck_host_shareable: true,
                 derive_encase_host_shareable: false,
@@ -407,7 +716,8 @@ mod tests {
                 rustfmt: true,
                 ..Default::default()
             },
-        );
+        )
+        .unwrap();
 
         assert_tokens_eq!(
             quote! {
@@ -457,11 +767,12 @@ mod tests {
 
         let actual = structs(
             &module,
-            WriteOptions {
+            &WriteOptions {
                 matrix_vector_types: MatrixVectorTypes::Nalgebra,
                 ..Default::default()
             },
-        );
+        )
+        .unwrap();
 
         assert_tokens_eq!(
             quote! {
@@ -491,11 +802,12 @@ mod tests {
 
         let actual = structs(
             &module,
-            WriteOptions {
+            &WriteOptions {
                 derive_encase_host_shareable: true,
                 ..Default::default()
             },
-        );
+        )
+        .unwrap();
 
         assert_tokens_eq!(
             quote! {
@@ -511,7 +823,6 @@ mod tests {
     }
 
     #[test]
-    #[should_panic]
     fn write_runtime_sized_array_no_encase() {
         let source = indoc! {r#"
             struct RtsStruct {
@@ -524,16 +835,18 @@ mod tests {
         "#};
         let module = naga::front::wgsl::parse_str(source).unwrap();
 
-        let _structs = structs(
-            &module,
-            WriteOptions {
-                ..Default::default()
-            },
-        );
+        assert!(matches!(
+            structs(
+                &module,
+                &WriteOptions {
+                    ..Default::default()
+                },
+            ),
+            Err(CreateModuleError::UnsupportedRuntimeArray { .. })
+        ));
     }
 
     #[test]
-    #[should_panic]
     fn write_runtime_sized_array_bytemuck_vertex() {
         let source = indoc! {r#"
             struct RtsStruct {
@@ -548,19 +861,21 @@ mod tests {
         "#};
         let module = naga::front::wgsl::parse_str(source).unwrap();
 
-        let _structs = structs(
-            &module,
-            WriteOptions {
-                derive_encase_host_shareable: true,
-                derive_bytemuck_vertex: true,
-                derive_bytemuck_host_shareable: false,
-                ..Default::default()
-            },
-        );
+        assert!(matches!(
+            structs(
+                &module,
+                &WriteOptions {
+                    derive_encase_host_shareable: true,
+                    derive_bytemuck_vertex: true,
+                    derive_bytemuck_host_shareable: false,
+                    ..Default::default()
+                },
+            ),
+            Err(CreateModuleError::UnsupportedRuntimeArray { .. })
+        ));
     }
 
     #[test]
-    #[should_panic]
     fn write_runtime_sized_array_bytemuck_host_shareable() {
         let source = indoc! {r#"
             struct RtsStruct {
@@ -573,15 +888,18 @@ mod tests {
         "#};
         let module = naga::front::wgsl::parse_str(source).unwrap();
 
-        let _structs = structs(
-            &module,
-            WriteOptions {
-                derive_encase_host_shareable: true,
-                derive_bytemuck_vertex: false,
-                derive_bytemuck_host_shareable: true,
-                ..Default::default()
-            },
-        );
+        assert!(matches!(
+            structs(
+                &module,
+                &WriteOptions {
+                    derive_encase_host_shareable: true,
+                    derive_bytemuck_vertex: false,
+                    derive_bytemuck_host_shareable: true,
+                    ..Default::default()
+                },
+            ),
+            Err(CreateModuleError::UnsupportedRuntimeArray { .. })
+        ));
     }
 
     #[test]
@@ -601,10 +919,119 @@ mod tests {
 
         let _structs = structs(
             &module,
-            WriteOptions {
+            &WriteOptions {
                 derive_encase_host_shareable: true,
                 ..Default::default()
             },
         );
     }
+
+    #[test]
+    fn write_type_overrides() {
+        let source = indoc! {r#"
+            struct Camera {
+                view_projection: mat4x4<f32>,
+            };
+
+            struct Scene {
+                camera: Camera,
+                time: f32,
+            };
+
+            @group(0) @binding(0)
+            var<uniform> scene: Scene;
+        "#};
+        let module = naga::front::wgsl::parse_str(source).unwrap();
+        let options = WriteOptions {
+            type_overrides: vec![("Camera".to_owned(), "crate::camera::Camera".to_owned())],
+            ..Default::default()
+        };
+        assert!(check_type_overrides(&module, &options).is_ok());
+
+        let actual = structs(&module, &options).unwrap();
+        assert_tokens_eq!(
+            quote! {
+                pub type Camera = crate::camera::Camera;
+                #[repr(C)]
+                #[derive(Debug, Copy, Clone, PartialEq)]
+                pub struct Scene {
+                    pub camera: Camera,
+                    pub time: f32,
+                }
+            },
+            actual
+        );
+    }
+
+    #[test]
+    fn write_type_overrides_bytemuck() {
+        let source = indoc! {r#"
+            struct Camera {
+                view_projection: mat4x4<f32>,
+            };
+
+            @group(0) @binding(0)
+            var<uniform> camera: Camera;
+        "#};
+        let module = naga::front::wgsl::parse_str(source).unwrap();
+        let options = WriteOptions {
+            derive_bytemuck_host_shareable: true,
+            type_overrides: vec![("Camera".to_owned(), "crate::camera::Camera".to_owned())],
+            ..Default::default()
+        };
+
+        let actual = structs(&module, &options).unwrap();
+        assert_tokens_eq!(
+            quote! {
+                pub type Camera = crate::camera::Camera;
+                const _: () = assert!(
+                    std::mem::size_of::<Camera>() == 64,
+                    "size of Camera does not match WGSL"
+                );
+            },
+            actual
+        );
+    }
+
+    #[test]
+    fn check_type_overrides_invalid() {
+        let source = indoc! {r#"
+            struct VertexInput {
+                @location(0) position: vec3<f32>,
+            };
+
+            struct Camera {
+                view_projection: mat4x4<f32>,
+            };
+
+            @group(0) @binding(0)
+            var<uniform> camera: Camera;
+
+            @vertex
+            fn main(in: VertexInput) -> @builtin(position) vec4<f32> {
+                return camera.view_projection * vec4(in.position, 1.0);
+            }
+        "#};
+        let module = naga::front::wgsl::parse_str(source).unwrap();
+        let message = |(name, path): (&str, &str)| match check_type_overrides(
+            &module,
+            &WriteOptions {
+                type_overrides: vec![(name.to_owned(), path.to_owned())],
+                ..Default::default()
+            },
+        ) {
+            Err(CreateModuleError::InvalidTypeOverride { message, .. }) => message,
+            r => panic!("unexpected result {r:?}"),
+        };
+
+        assert_eq!(
+            "no struct with this name in the module",
+            message(("Light", "crate::Light"))
+        );
+        assert_eq!(
+            "vertex input structs can't be overridden",
+            message(("VertexInput", "crate::Vertex"))
+        );
+        assert!(!message(("Camera", "crate::")).is_empty());
+    }
 }
@@ -0,0 +1,242 @@
+use proc_macro2::TokenStream;
+use quote::quote;
+
+use crate::{CreateModuleError, Translations};
+
+/// Items for `impl Shader` with the module translated to each language in `translations`.
+pub fn translated_sources(
+    module: &naga::Module,
+    module_info: &naga::valid::ModuleInfo,
+    translations: Translations,
+) -> Result<TokenStream, CreateModuleError> {
+    let mut items = Vec::new();
+    if translations.spirv {
+        items.push(spirv(module, module_info)?);
+    }
+    if translations.glsl {
+        items.push(glsl(module, module_info)?);
+    }
+    Ok(quote!(#(#items)*))
+}
+
+#[cfg(any(feature = "spv-out", feature = "glsl-out"))]
+fn translation_error(language: &'static str, error: impl std::error::Error) -> CreateModuleError {
+    CreateModuleError::TranslationError {
+        language,
+        message: crate::error_message(&error),
+    }
+}
+
+#[cfg(not(all(feature = "spv-out", feature = "glsl-out")))]
+fn missing_feature(language: &'static str, feature: &str) -> CreateModuleError {
+    CreateModuleError::TranslationError {
+        language,
+        message: format!("the `{feature}` feature is not enabled for wgsl_to_wgpu"),
+    }
+}
+
+#[cfg(feature = "spv-out")]
+fn spirv(
+    module: &naga::Module,
+    module_info: &naga::valid::ModuleInfo,
+) -> Result<TokenStream, CreateModuleError> {
+    let options = naga::back::spv::Options {
+        // wgpu flips the viewport on Vulkan instead of adjusting the coordinate space.
+        flags: naga::back::spv::WriterFlags::LABEL_VARYINGS
+            | naga::back::spv::WriterFlags::FORCE_POINT_SIZE,
+        bounds_check_policies: naga::proc::BoundsCheckPolicies {
+            index: naga::proc::BoundsCheckPolicy::Restrict,
+            buffer: naga::proc::BoundsCheckPolicy::Restrict,
+            image_load: naga::proc::BoundsCheckPolicy::Restrict,
+            ..Default::default()
+        },
+        ..Default::default()
+    };
+    let words = naga::back::spv::write_vec(module, module_info, &options, None)
+        .map_err(|e| translation_error("SPIR-V", e))?;
+    let words = words.into_iter().map(proc_macro2::Literal::u32_unsuffixed);
+
+    Ok(quote! {
+        /// The shader translated to SPIR-V when generating the bindings.
+        pub const SOURCE_SPIRV: &'static [u32] = &[#(#words),*];
+
+        /// Create the shader module from [Self::SOURCE_SPIRV] instead of parsing WGSL at runtime.
+        ///
+        /// Devices with [wgpu::Features::SPIRV_SHADER_PASSTHROUGH] use the SPIR-V directly.
+        /// Other devices translate the SPIR-V for the current backend,
+        /// which requires the `spirv` feature for wgpu.
+        pub fn new_spirv(device: std::sync::Arc<wgpu::Device>) -> Result<Self, UnsupportedDeviceError> {
+            Self::check_device(&device)?;
+            let source = std::borrow::Cow::Borrowed(Self::SOURCE_SPIRV);
+            let shader_module = if device.features().contains(wgpu::Features::SPIRV_SHADER_PASSTHROUGH) {
+                // SAFETY: The SPIR-V was written by naga from a validated module.
+                unsafe {
+                    device.create_shader_module_spirv(&wgpu::ShaderModuleDescriptorSpirV {
+                        label: None,
+                        source,
+                    })
+                }
+            } else {
+                device.create_shader_module(wgpu::ShaderModuleDescriptor {
+                    label: None,
+                    source: wgpu::ShaderSource::SpirV(source),
+                })
+            };
+            Ok(Self::from_shader_module(device, shader_module))
+        }
+    })
+}
+
+#[cfg(not(feature = "spv-out"))]
+fn spirv(
+    _module: &naga::Module,
+    _module_info: &naga::valid::ModuleInfo,
+) -> Result<TokenStream, CreateModuleError> {
+    Err(missing_feature("SPIR-V", "spv-out"))
+}
+
+#[cfg(feature = "glsl-out")]
+fn glsl(
+    module: &naga::Module,
+    module_info: &naga::valid::ModuleInfo,
+) -> Result<TokenStream, CreateModuleError> {
+    // GLSL only supports a single entry point per shader.
+    let options = naga::back::glsl::Options::default();
+    let mut arms = Vec::new();
+    for entry in &module.entry_points {
+        let mut source = String::new();
+        let pipeline_options = naga::back::glsl::PipelineOptions {
+            shader_stage: entry.stage,
+            entry_point: entry.name.clone(),
+            multiview: None,
+        };
+        naga::back::glsl::Writer::new(
+            &mut source,
+            module,
+            module_info,
+            &options,
+            &pipeline_options,
+            naga::proc::BoundsCheckPolicies::default(),
+        )
+        .and_then(|mut writer| writer.write())
+        .map_err(|e| translation_error("GLSL", e))?;
+
+        let name = &entry.name;
+        arms.push(quote!(#name => Some(#source)));
+    }
+
+    Ok(quote! {
+        /// The shader translated to GLSL for the entry point named `entry_point` when generating the bindings.
+        pub fn source_glsl(entry_point: &str) -> Option<&'static str> {
+            match entry_point {
+                #(#arms,)*
+                _ => None,
+            }
+        }
+    })
+}
+
+#[cfg(not(feature = "glsl-out"))]
+fn glsl(
+    _module: &naga::Module,
+    _module_info: &naga::valid::ModuleInfo,
+) -> Result<TokenStream, CreateModuleError> {
+    Err(missing_feature("GLSL", "glsl-out"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use indoc::indoc;
+
+    fn module_and_info(source: &str) -> (naga::Module, naga::valid::ModuleInfo) {
+        let module = naga::front::wgsl::parse_str(source).unwrap();
+        let module_info = naga::valid::Validator::new(
+            naga::valid::ValidationFlags::all(),
+            naga::valid::Capabilities::all(),
+        )
+        .validate(&module)
+        .unwrap();
+        (module, module_info)
+    }
+
+    const SOURCE: &str = indoc! {r#"
+        @vertex
+        fn vs_main() -> @builtin(position) vec4<f32> {
+            return vec4(0.0);
+        }
+
+        @fragment
+        fn fs_main() -> @location(0) vec4<f32> {
+            return vec4(1.0);
+        }
+    "#};
+
+    #[cfg(any(not(feature = "spv-out"), feature = "glsl-out"))]
+    const ALL: Translations = Translations {
+        spirv: true,
+        glsl: true,
+    };
+
+    #[test]
+    fn translated_sources_none() {
+        let (module, module_info) = module_and_info(SOURCE);
+        let actual = translated_sources(&module, &module_info, Translations::default()).unwrap();
+        assert!(actual.is_empty());
+    }
+
+    #[cfg(all(feature = "spv-out", feature = "glsl-out"))]
+    #[test]
+    fn translated_sources_all() {
+        let (module, module_info) = module_and_info(SOURCE);
+        let actual = translated_sources(&module, &module_info, ALL)
+            .unwrap()
+            .to_string();
+        assert!(actual.contains("pub const SOURCE_SPIRV"));
+        assert!(actual.contains("pub fn new_spirv"));
+        assert!(actual.contains("\"vs_main\" => Some"));
+        assert!(actual.contains("\"fs_main\" => Some"));
+    }
+
+    #[cfg(feature = "spv-out")]
+    #[test]
+    fn translated_sources_overrides() {
+        let source = indoc! {r#"
+            override scale: f32 = 1.0;
+
+            @fragment
+            fn main() -> @location(0) vec4<f32> {
+                return vec4(scale);
+            }
+        "#};
+        let (module, module_info) = module_and_info(source);
+        let result = translated_sources(
+            &module,
+            &module_info,
+            Translations {
+                spirv: true,
+                ..Default::default()
+            },
+        );
+        assert!(matches!(
+            result,
+            Err(CreateModuleError::TranslationError {
+                language: "SPIR-V",
+                ..
+            })
+        ));
+    }
+
+    #[cfg(not(feature = "spv-out"))]
+    #[test]
+    fn translated_sources_missing_feature() {
+        let (module, module_info) = module_and_info(SOURCE);
+        assert_eq!(
+            Err(CreateModuleError::TranslationError {
+                language: "SPIR-V",
+                message: "the `spv-out` feature is not enabled for wgsl_to_wgpu".to_owned()
+            }),
+            translated_sources(&module, &module_info, ALL).map(|t| t.to_string())
+        );
+    }
+}
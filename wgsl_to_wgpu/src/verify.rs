@@ -0,0 +1,207 @@
+use std::path::{Path, PathBuf};
+
+use syn::{visit_mut::VisitMut, Expr};
+use thiserror::Error;
+
+use crate::{create_shader_module_source_tokens, CreateModuleError, WriteOptions};
+
+/// Errors for generated files that do not match the output for the current shader.
+#[derive(Debug, PartialEq, Eq, Error)]
+pub enum Diff {
+    /// The existing file has different Rust tokens than the generated output.
+    #[error("{} is out of date\n{diff}", path.display())]
+    OutOfDate {
+        path: PathBuf,
+        /// A unified diff from the existing file to the generated output.
+        /// Both are formatted the same way, so only token changes are shown.
+        diff: String,
+    },
+
+    /// The existing file must be readable.
+    #[error("failed to read {}: {message}", path.display())]
+    ReadError { path: PathBuf, message: String },
+
+    /// The shader must generate valid bindings.
+    #[error(transparent)]
+    ShaderError(#[from] CreateModuleError),
+}
+
+/// Check that the generated file at `generated_path` is up to date
+/// with the output of [create_shader_module](crate::create_shader_module) for `wgsl_source`.
+///
+/// Files are compared by their Rust tokens, so comments and formatting differences like
+/// [WriteOptions::rustfmt] do not matter.
+/// This is intended for tests or CI for projects that check the generated bindings into version control.
+///
+/// Requires the `verify` feature.
+///
+/// # Examples
+/**
+```rust no_run
+// tests/bindings.rs
+let wgsl_source = std::fs::read_to_string("src/shader.wgsl").unwrap();
+let options = wgsl_to_wgpu::WriteOptions::default();
+if let Err(e) = wgsl_to_wgpu::verify("src/shader.rs", &wgsl_source, options) {
+    panic!("{e}");
+}
+```
+ */
+pub fn verify(
+    generated_path: impl AsRef<Path>,
+    wgsl_source: &str,
+    options: WriteOptions,
+) -> Result<(), Diff> {
+    let path = generated_path.as_ref();
+    let existing = std::fs::read_to_string(path).map_err(|e| Diff::ReadError {
+        path: path.to_owned(),
+        message: e.to_string(),
+    })?;
+
+    // The generated tokens are always valid Rust.
+    let tokens = create_shader_module_source_tokens(wgsl_source, options)?;
+    let expected = normalize(&tokens.to_string()).unwrap();
+
+    // Files that are not valid Rust are compared as is and always differ.
+    let existing = normalize(&existing).unwrap_or(existing);
+
+    if existing == expected {
+        Ok(())
+    } else {
+        let path_name = path.display().to_string();
+        let diff = similar::TextDiff::from_lines(&existing, &expected)
+            .unified_diff()
+            .header(&path_name, &path_name)
+            .to_string();
+        Err(Diff::OutOfDate {
+            path: path.to_owned(),
+            diff,
+        })
+    }
+}
+
+/// Format the file the same way regardless of whether it was formatted with rustfmt.
+fn normalize(text: &str) -> Option<String> {
+    let mut file = syn::parse_file(text).ok()?;
+    RemoveBodyBlocks.visit_file_mut(&mut file);
+    Some(prettyplease::unparse(&file))
+}
+
+/// rustfmt adds braces around closure and match arm bodies that don't fit on one line.
+struct RemoveBodyBlocks;
+
+impl VisitMut for RemoveBodyBlocks {
+    fn visit_expr_mut(&mut self, expr: &mut Expr) {
+        match expr {
+            Expr::Closure(closure) if matches!(closure.output, syn::ReturnType::Default) => {
+                remove_block(&mut closure.body)
+            }
+            Expr::Match(m) => {
+                for arm in &mut m.arms {
+                    remove_block(&mut arm.body);
+                }
+            }
+            _ => (),
+        }
+        syn::visit_mut::visit_expr_mut(self, expr);
+    }
+}
+
+fn remove_block(body: &mut Expr) {
+    if let Expr::Block(block) = body {
+        if let (None, [], [syn::Stmt::Expr(inner, None)]) = (
+            &block.label,
+            block.attrs.as_slice(),
+            block.block.stmts.as_slice(),
+        ) {
+            *body = inner.clone();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use indoc::indoc;
+
+    const SOURCE: &str = indoc! {r#"
+        struct Uniforms {
+            color: vec4<f32>,
+            scale: f32,
+        }
+        @group(0) @binding(0) var<uniform> uniforms: Uniforms;
+
+        @fragment
+        fn main() -> @location(0) vec4<f32> {
+            return uniforms.color;
+        }
+    "#};
+
+    fn temp_file(name: &str, contents: &str) -> PathBuf {
+        // Include the process ID in case the tests run from multiple processes at once.
+        let path =
+            std::env::temp_dir().join(format!("wgsl_to_wgpu_{name}_{}.rs", std::process::id()));
+        std::fs::write(&path, contents).unwrap();
+        path
+    }
+
+    #[test]
+    fn verify_up_to_date() {
+        // Comments and formatting are ignored.
+        let tokens = create_shader_module_source_tokens(SOURCE, WriteOptions::default()).unwrap();
+        let path = temp_file(
+            "verify_up_to_date",
+            &format!("// File automatically generated by wgsl_to_wgpu.\n{tokens}"),
+        );
+
+        assert_eq!(Ok(()), verify(&path, SOURCE, WriteOptions::default()));
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn verify_out_of_date() {
+        let generated = crate::create_shader_module(SOURCE, WriteOptions::default()).unwrap();
+        let path = temp_file("verify_out_of_date", &generated);
+
+        let source = SOURCE.replace("scale: f32", "scale: u32");
+        match verify(&path, &source, WriteOptions::default()) {
+            Err(Diff::OutOfDate { diff, .. }) => {
+                assert!(diff.starts_with("--- "), "{diff}");
+                assert!(diff.contains("\n-    pub scale: f32,\n"), "{diff}");
+                assert!(diff.contains("\n+    pub scale: u32,\n"), "{diff}");
+            }
+            result => panic!("expected out of date, got {result:?}"),
+        }
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn normalize_body_blocks() {
+        assert_eq!(
+            normalize("fn f() { a.map(|x| g(x)); match x { A => 1, B => {} } }"),
+            normalize("fn f() { a.map(|x| { g(x) }); match x { A => { 1 } B => {} } }")
+        );
+        assert_ne!(
+            normalize("fn f() { a.map(|x| -> u32 { g(x) }); }"),
+            normalize("fn f() { a.map(|x| g(x)); }")
+        );
+    }
+
+    #[test]
+    fn verify_missing_file() {
+        let path = Path::new("missing_file.rs");
+        assert!(matches!(
+            verify(path, SOURCE, WriteOptions::default()),
+            Err(Diff::ReadError { .. })
+        ));
+    }
+
+    #[test]
+    fn verify_shader_error() {
+        let path = temp_file("verify_shader_error", "");
+        assert!(matches!(
+            verify(&path, "fn main( {", WriteOptions::default()),
+            Err(Diff::ShaderError(CreateModuleError::ParseError { .. }))
+        ));
+        std::fs::remove_file(path).unwrap();
+    }
+}
@@ -16,6 +16,240 @@ pub fn shader_stages(module: &naga::Module) -> wgpu::ShaderStages {
         .collect()
 }
 
+pub fn required_features(module: &naga::Module) -> wgpu::Features {
+    let mut features = wgpu::Features::empty();
+    for (_, ty) in module.types.iter() {
+        let scalar = match &ty.inner {
+            naga::TypeInner::Scalar(scalar)
+            | naga::TypeInner::Vector { scalar, .. }
+            | naga::TypeInner::Matrix { scalar, .. }
+            | naga::TypeInner::Atomic(scalar) => Some(scalar),
+            _ => None,
+        };
+        match scalar.map(|s| (s.kind, s.width)) {
+            Some((naga::ScalarKind::Float, 2)) => features |= wgpu::Features::SHADER_F16,
+            Some((naga::ScalarKind::Float, 8)) => features |= wgpu::Features::SHADER_F64,
+            Some((naga::ScalarKind::Sint | naga::ScalarKind::Uint, 8)) => {
+                features |= wgpu::Features::SHADER_INT64
+            }
+            _ => (),
+        }
+    }
+
+    for (_, global) in module.global_variables.iter() {
+        if global.space == naga::AddressSpace::PushConstant {
+            features |= wgpu::Features::PUSH_CONSTANTS;
+        }
+        if let naga::TypeInner::BindingArray { base, .. } = &module.types[global.ty].inner {
+            match (&module.types[*base].inner, global.space) {
+                (naga::TypeInner::Image { class, .. }, _) => {
+                    features |= wgpu::Features::TEXTURE_BINDING_ARRAY;
+                    if matches!(class, naga::ImageClass::Storage { .. }) {
+                        features |= wgpu::Features::STORAGE_RESOURCE_BINDING_ARRAY;
+                    }
+                }
+                (naga::TypeInner::Sampler { .. }, _) => {
+                    features |= wgpu::Features::TEXTURE_BINDING_ARRAY
+                }
+                (_, naga::AddressSpace::Storage { .. }) => {
+                    features |= wgpu::Features::BUFFER_BINDING_ARRAY
+                        | wgpu::Features::STORAGE_RESOURCE_BINDING_ARRAY
+                }
+                _ => features |= wgpu::Features::BUFFER_BINDING_ARRAY,
+            }
+        }
+    }
+
+    for entry in &module.entry_points {
+        if crate::entry::fragment_targets(module, &entry.function)
+            .iter()
+            .any(|t| t.second_blend_source)
+        {
+            features |= wgpu::Features::DUAL_SOURCE_BLENDING;
+        }
+        if entry.early_depth_test.is_some() {
+            features |= wgpu::Features::SHADER_EARLY_DEPTH_TEST;
+        }
+        let mut built_ins = Vec::new();
+        for argument in &entry.function.arguments {
+            add_built_ins(
+                &mut built_ins,
+                module,
+                argument.ty,
+                argument.binding.as_ref(),
+            );
+        }
+        for built_in in built_ins {
+            match built_in {
+                naga::BuiltIn::PrimitiveIndex => features |= wgpu::Features::SHADER_PRIMITIVE_INDEX,
+                naga::BuiltIn::ViewIndex => features |= wgpu::Features::MULTIVIEW,
+                _ => (),
+            }
+        }
+    }
+    features
+}
+
+fn add_built_ins(
+    built_ins: &mut Vec<naga::BuiltIn>,
+    module: &naga::Module,
+    ty: naga::Handle<naga::Type>,
+    binding: Option<&naga::Binding>,
+) {
+    match binding {
+        Some(naga::Binding::BuiltIn(built_in)) => built_ins.push(*built_in),
+        Some(naga::Binding::Location { .. }) => (),
+        None => {
+            if let naga::TypeInner::Struct { members, .. } = &module.types[ty].inner {
+                for member in members {
+                    add_built_ins(built_ins, module, member.ty, member.binding.as_ref());
+                }
+            }
+        }
+    }
+}
+
+/// The minimum values for the `wgpu::Limits` fields used by `module`.
+/// Limits that don't depend on the shader are not included.
+pub fn required_limits(
+    module: &naga::Module,
+    module_info: &naga::valid::ModuleInfo,
+) -> Vec<(&'static str, u32)> {
+    let mut limits = Vec::new();
+
+    let push_constant_size = module
+        .global_variables
+        .iter()
+        .filter(|(_, g)| g.space == naga::AddressSpace::PushConstant)
+        .map(|(_, g)| module.types[g.ty].inner.size(module.to_ctx()))
+        .max();
+    if let Some(size) = push_constant_size {
+        limits.push(("max_push_constant_size", size));
+    }
+
+    // Bind group layouts use the same visibility for all bindings.
+    // Each stage counts all the bindings in the module.
+    let mut bind_groups = 0;
+    let mut bindings_per_bind_group = 0;
+    let mut uniform_buffers = 0;
+    let mut storage_buffers = 0;
+    let mut sampled_textures = 0;
+    let mut storage_textures = 0;
+    let mut samplers = 0;
+    let mut uniform_buffer_size = 0;
+    let mut storage_buffer_size = 0;
+    for (_, global) in module.global_variables.iter() {
+        if let Some(binding) = &global.binding {
+            bind_groups = bind_groups.max(binding.group + 1);
+            bindings_per_bind_group = bindings_per_bind_group.max(binding.binding + 1);
+
+            let (ty, count) = match &module.types[global.ty].inner {
+                naga::TypeInner::BindingArray { base, size } => match size {
+                    naga::ArraySize::Constant(size) => (*base, size.get()),
+                    naga::ArraySize::Dynamic => (*base, 1),
+                },
+                _ => (global.ty, 1),
+            };
+            let size = module.types[ty].inner.size(module.to_ctx());
+            match (&module.types[ty].inner, global.space) {
+                (naga::TypeInner::Image { class, .. }, _) => {
+                    if matches!(class, naga::ImageClass::Storage { .. }) {
+                        storage_textures += count;
+                    } else {
+                        sampled_textures += count;
+                    }
+                }
+                (naga::TypeInner::Sampler { .. }, _) => samplers += count,
+                (_, naga::AddressSpace::Uniform) => {
+                    uniform_buffers += count;
+                    uniform_buffer_size = uniform_buffer_size.max(size);
+                }
+                (_, naga::AddressSpace::Storage { .. }) => {
+                    storage_buffers += count;
+                    storage_buffer_size = storage_buffer_size.max(size);
+                }
+                _ => (),
+            }
+        }
+    }
+    for (name, value) in [
+        ("max_bind_groups", bind_groups),
+        ("max_bindings_per_bind_group", bindings_per_bind_group),
+        ("max_uniform_buffers_per_shader_stage", uniform_buffers),
+        ("max_storage_buffers_per_shader_stage", storage_buffers),
+        ("max_sampled_textures_per_shader_stage", sampled_textures),
+        ("max_storage_textures_per_shader_stage", storage_textures),
+        ("max_samplers_per_shader_stage", samplers),
+        ("max_uniform_buffer_binding_size", uniform_buffer_size),
+        ("max_storage_buffer_binding_size", storage_buffer_size),
+    ] {
+        if value > 0 {
+            limits.push((name, value));
+        }
+    }
+
+    let color_attachments = module
+        .entry_points
+        .iter()
+        .filter_map(|e| {
+            crate::entry::fragment_targets(module, &e.function)
+                .iter()
+                .map(|t| t.location + 1)
+                .max()
+        })
+        .max();
+    if let Some(count) = color_attachments {
+        limits.push(("max_color_attachments", count));
+    }
+
+    let compute_entries: Vec<_> = module
+        .entry_points
+        .iter()
+        .enumerate()
+        .filter(|(_, e)| e.stage == naga::ShaderStage::Compute)
+        .collect();
+    if !compute_entries.is_empty() {
+        // WebGPU rounds the size of each workgroup variable up to a multiple of 16.
+        let workgroup_storage_size = compute_entries
+            .iter()
+            .map(|(i, _)| {
+                let info = module_info.get_entry_point(*i);
+                module
+                    .global_variables
+                    .iter()
+                    .filter(|(h, g)| {
+                        g.space == naga::AddressSpace::WorkGroup && !info[*h].is_empty()
+                    })
+                    .map(|(_, g)| module.types[g.ty].inner.size(module.to_ctx()).div_ceil(16) * 16)
+                    .sum()
+            })
+            .max()
+            .unwrap_or_default();
+        let max_size = |i: usize| {
+            compute_entries
+                .iter()
+                .map(|(_, e)| e.workgroup_size[i])
+                .max()
+                .unwrap_or_default()
+        };
+        let invocations = compute_entries
+            .iter()
+            .map(|(_, e)| e.workgroup_size.iter().product())
+            .max()
+            .unwrap_or_default();
+
+        if workgroup_storage_size > 0 {
+            limits.push(("max_compute_workgroup_storage_size", workgroup_storage_size));
+        }
+        limits.push(("max_compute_invocations_per_workgroup", invocations));
+        limits.push(("max_compute_workgroup_size_x", max_size(0)));
+        limits.push(("max_compute_workgroup_size_y", max_size(1)));
+        limits.push(("max_compute_workgroup_size_z", max_size(2)));
+    }
+
+    limits
+}
+
 pub fn rust_scalar_type(scalar: &naga::Scalar) -> TokenStream {
     // TODO: Support other widths?
     match (scalar.kind, scalar.width) {
@@ -25,6 +259,10 @@ pub fn rust_scalar_type(scalar: &naga::Scalar) -> TokenStream {
         (naga::ScalarKind::Uint, 2) => quote!(u16),
         (naga::ScalarKind::Sint, 4) => quote!(i32),
         (naga::ScalarKind::Uint, 4) => quote!(u32),
+        (naga::ScalarKind::Sint, 8) => quote!(i64),
+        (naga::ScalarKind::Uint, 8) => quote!(u64),
+        // This requires the "bytemuck" feature of half when deriving bytemuck.
+        (naga::ScalarKind::Float, 2) => quote!(half::f16),
         (naga::ScalarKind::Float, 4) => quote!(f32),
         (naga::ScalarKind::Float, 8) => quote!(f64),
         // TODO: Do booleans have a width?
@@ -74,6 +312,11 @@ pub fn rust_type(module: &naga::Module, ty: &naga::Type, format: MatrixVectorTyp
             MatrixVectorTypes::Rust { .. } => rust_vector_type(*size, scalar.kind, scalar.width),
             MatrixVectorTypes::Glam => glam_vector_type(*size, scalar.kind, scalar.width),
             MatrixVectorTypes::Nalgebra => nalgebra_vector_type(*size, scalar.kind, scalar.width),
+            MatrixVectorTypes::Mint => mint_vector_type(*size, scalar.kind, scalar.width),
+            MatrixVectorTypes::Cgmath => cgmath_vector_type(*size, scalar.kind, scalar.width),
+            MatrixVectorTypes::Ultraviolet => {
+                ultraviolet_vector_type(*size, scalar.kind, scalar.width)
+            }
         },
         naga::TypeInner::Matrix {
             columns,
@@ -83,6 +326,11 @@ pub fn rust_type(module: &naga::Module, ty: &naga::Type, format: MatrixVectorTyp
             MatrixVectorTypes::Rust { .. } => rust_matrix_type(*rows, *columns, scalar.width),
             MatrixVectorTypes::Glam => glam_matrix_type(*rows, *columns, scalar.width),
             MatrixVectorTypes::Nalgebra => nalgebra_matrix_type(*rows, *columns, scalar.width),
+            MatrixVectorTypes::Mint => mint_matrix_type(*rows, *columns, scalar.width),
+            MatrixVectorTypes::Cgmath => cgmath_matrix_type(*rows, *columns, scalar.width),
+            MatrixVectorTypes::Ultraviolet => {
+                ultraviolet_matrix_type(*rows, *columns, scalar.width)
+            }
         },
         naga::TypeInner::Image { .. } => todo!(),
         naga::TypeInner::Sampler { .. } => todo!(),
@@ -108,7 +356,7 @@ pub fn rust_type(module: &naga::Module, ty: &naga::Type, format: MatrixVectorTyp
             members: _,
             span: _,
         } => {
-            let name = Ident::new(ty.name.as_ref().unwrap(), Span::call_site());
+            let name = Ident::new(&struct_name(module, ty), Span::call_site());
             quote!(#name)
         }
         naga::TypeInner::BindingArray { base: _, size: _ } => todo!(),
@@ -117,6 +365,24 @@ pub fn rust_type(module: &naga::Module, ty: &naga::Type, format: MatrixVectorTyp
     }
 }
 
+/// The Rust name for a struct type.
+/// Structs from SPIR-V or GLSL may not have a name, so these use their index in the module.
+pub fn struct_name(module: &naga::Module, ty: &naga::Type) -> String {
+    ty.name.clone().unwrap_or_else(|| {
+        let index = module.types.get(ty).map(|h| h.index()).unwrap_or_default();
+        format!("Struct{index}")
+    })
+}
+
+/// The Rust name for the struct member at `index`.
+/// Members from SPIR-V may not have a name, so these use their index in the struct.
+pub fn member_name(member: &naga::StructMember, index: usize) -> String {
+    member
+        .name
+        .clone()
+        .unwrap_or_else(|| format!("member_{index}"))
+}
+
 fn rust_matrix_type(rows: naga::VectorSize, columns: naga::VectorSize, width: u8) -> TokenStream {
     let inner_type = rust_scalar_type(&naga::Scalar {
         kind: naga::ScalarKind::Float,
@@ -156,6 +422,63 @@ fn nalgebra_matrix_type(
     quote!(nalgebra::SMatrix<#inner_type, #rows, #columns>)
 }
 
+fn mint_matrix_type(rows: naga::VectorSize, columns: naga::VectorSize, width: u8) -> TokenStream {
+    let inner_type = rust_scalar_type(&naga::Scalar {
+        kind: naga::ScalarKind::Float,
+        width,
+    });
+    let name = mint_matrix_name(rows, columns);
+    quote!(mint::#name<#inner_type>)
+}
+
+fn mint_matrix_name(rows: naga::VectorSize, columns: naga::VectorSize) -> Ident {
+    // WGSL matrices are column major, so use the column major mint types.
+    // mint names non square matrices by rows then columns like ColumnMatrix2x3.
+    let name = if rows == columns {
+        format!("ColumnMatrix{}", rows as u8)
+    } else {
+        format!("ColumnMatrix{}x{}", rows as u8, columns as u8)
+    };
+    Ident::new(&name, Span::call_site())
+}
+
+fn cgmath_matrix_type(rows: naga::VectorSize, columns: naga::VectorSize, width: u8) -> TokenStream {
+    // cgmath only supports square matrices of f32 or f64.
+    // Use Rust types for unsupported matrices.
+    let inner_type = rust_scalar_type(&naga::Scalar {
+        kind: naga::ScalarKind::Float,
+        width,
+    });
+    match (rows, columns, width) {
+        (naga::VectorSize::Bi, naga::VectorSize::Bi, 4 | 8) => quote!(cgmath::Matrix2<#inner_type>),
+        (naga::VectorSize::Tri, naga::VectorSize::Tri, 4 | 8) => {
+            quote!(cgmath::Matrix3<#inner_type>)
+        }
+        (naga::VectorSize::Quad, naga::VectorSize::Quad, 4 | 8) => {
+            quote!(cgmath::Matrix4<#inner_type>)
+        }
+        _ => rust_matrix_type(rows, columns, width),
+    }
+}
+
+fn ultraviolet_matrix_type(
+    rows: naga::VectorSize,
+    columns: naga::VectorSize,
+    width: u8,
+) -> TokenStream {
+    // ultraviolet only supports square matrices.
+    // Use Rust types for unsupported matrices.
+    match (rows, columns, width) {
+        (naga::VectorSize::Bi, naga::VectorSize::Bi, 4) => quote!(ultraviolet::Mat2),
+        (naga::VectorSize::Tri, naga::VectorSize::Tri, 4) => quote!(ultraviolet::Mat3),
+        (naga::VectorSize::Quad, naga::VectorSize::Quad, 4) => quote!(ultraviolet::Mat4),
+        (naga::VectorSize::Bi, naga::VectorSize::Bi, 8) => quote!(ultraviolet::DMat2),
+        (naga::VectorSize::Tri, naga::VectorSize::Tri, 8) => quote!(ultraviolet::DMat3),
+        (naga::VectorSize::Quad, naga::VectorSize::Quad, 8) => quote!(ultraviolet::DMat4),
+        _ => rust_matrix_type(rows, columns, width),
+    }
+}
+
 fn rust_vector_type(size: naga::VectorSize, kind: naga::ScalarKind, width: u8) -> TokenStream {
     let inner_type = rust_scalar_type(&naga::Scalar { kind, width });
     let size = Literal::usize_unsuffixed(size as usize);
@@ -187,15 +510,172 @@ fn nalgebra_vector_type(size: naga::VectorSize, kind: naga::ScalarKind, width: u
     quote!(nalgebra::SVector<#inner_type, #size>)
 }
 
-pub fn vertex_format(ty: &naga::Type) -> wgpu::VertexFormat {
+fn mint_vector_type(size: naga::VectorSize, kind: naga::ScalarKind, width: u8) -> TokenStream {
+    let inner_type = rust_scalar_type(&naga::Scalar { kind, width });
+    match size {
+        naga::VectorSize::Bi => quote!(mint::Vector2<#inner_type>),
+        naga::VectorSize::Tri => quote!(mint::Vector3<#inner_type>),
+        naga::VectorSize::Quad => quote!(mint::Vector4<#inner_type>),
+    }
+}
+
+fn cgmath_vector_type(size: naga::VectorSize, kind: naga::ScalarKind, width: u8) -> TokenStream {
+    let inner_type = rust_scalar_type(&naga::Scalar { kind, width });
+    match size {
+        naga::VectorSize::Bi => quote!(cgmath::Vector2<#inner_type>),
+        naga::VectorSize::Tri => quote!(cgmath::Vector3<#inner_type>),
+        naga::VectorSize::Quad => quote!(cgmath::Vector4<#inner_type>),
+    }
+}
+
+fn ultraviolet_vector_type(
+    size: naga::VectorSize,
+    kind: naga::ScalarKind,
+    width: u8,
+) -> TokenStream {
+    // Integer vectors require the "int" feature and f64 vectors require the "f64" feature.
+    match (size, kind, width) {
+        (naga::VectorSize::Bi, naga::ScalarKind::Float, 4) => quote!(ultraviolet::Vec2),
+        (naga::VectorSize::Tri, naga::ScalarKind::Float, 4) => quote!(ultraviolet::Vec3),
+        (naga::VectorSize::Quad, naga::ScalarKind::Float, 4) => quote!(ultraviolet::Vec4),
+        (naga::VectorSize::Bi, naga::ScalarKind::Float, 8) => quote!(ultraviolet::DVec2),
+        (naga::VectorSize::Tri, naga::ScalarKind::Float, 8) => quote!(ultraviolet::DVec3),
+        (naga::VectorSize::Quad, naga::ScalarKind::Float, 8) => quote!(ultraviolet::DVec4),
+        (naga::VectorSize::Bi, naga::ScalarKind::Uint, 4) => quote!(ultraviolet::UVec2),
+        (naga::VectorSize::Tri, naga::ScalarKind::Uint, 4) => quote!(ultraviolet::UVec3),
+        (naga::VectorSize::Quad, naga::ScalarKind::Uint, 4) => quote!(ultraviolet::UVec4),
+        (naga::VectorSize::Bi, naga::ScalarKind::Sint, 4) => quote!(ultraviolet::IVec2),
+        (naga::VectorSize::Tri, naga::ScalarKind::Sint, 4) => quote!(ultraviolet::IVec3),
+        (naga::VectorSize::Quad, naga::ScalarKind::Sint, 4) => quote!(ultraviolet::IVec4),
+        // Use Rust types for unsupported types.
+        _ => rust_vector_type(size, kind, width),
+    }
+}
+
+pub fn vector_value(
+    scalar: naga::Scalar,
+    components: &[TokenStream],
+    format: MatrixVectorTypes,
+) -> TokenStream {
+    let size = vector_size(components.len());
+    let (kind, width) = (scalar.kind, scalar.width);
+    match format {
+        MatrixVectorTypes::Rust { .. } => quote!([#(#components),*]),
+        MatrixVectorTypes::Glam => {
+            let ty = glam_vector_type(size, kind, width);
+            if is_array_type(&ty) {
+                quote!([#(#components),*])
+            } else {
+                quote!(#ty::new(#(#components),*))
+            }
+        }
+        MatrixVectorTypes::Nalgebra => {
+            quote!(nalgebra::SVector::from_array_storage(nalgebra::ArrayStorage([[#(#components),*]])))
+        }
+        MatrixVectorTypes::Mint => {
+            let fields = vector_fields(components.len());
+            let name = Ident::new(&format!("Vector{}", size as u8), Span::call_site());
+            quote!(mint::#name { #(#fields: #components),* })
+        }
+        MatrixVectorTypes::Cgmath => {
+            let name = Ident::new(&format!("Vector{}", size as u8), Span::call_site());
+            quote!(cgmath::#name::new(#(#components),*))
+        }
+        MatrixVectorTypes::Ultraviolet => {
+            let ty = ultraviolet_vector_type(size, kind, width);
+            if is_array_type(&ty) {
+                quote!([#(#components),*])
+            } else {
+                quote!(#ty::new(#(#components),*))
+            }
+        }
+    }
+}
+
+pub fn matrix_value(
+    scalar: naga::Scalar,
+    columns: &[Vec<TokenStream>],
+    format: MatrixVectorTypes,
+) -> Option<TokenStream> {
+    let rows = vector_size(columns.first()?.len());
+    let size = vector_size(columns.len());
+    let width = scalar.width;
+
+    let ty = match format {
+        MatrixVectorTypes::Rust { .. } => rust_matrix_type(rows, size, width),
+        MatrixVectorTypes::Glam => glam_matrix_type(rows, size, width),
+        MatrixVectorTypes::Nalgebra => nalgebra_matrix_type(rows, size, width),
+        MatrixVectorTypes::Mint => mint_matrix_type(rows, size, width),
+        MatrixVectorTypes::Cgmath => cgmath_matrix_type(rows, size, width),
+        MatrixVectorTypes::Ultraviolet => ultraviolet_matrix_type(rows, size, width),
+    };
+    if is_array_type(&ty) {
+        // Fill the nested arrays with the components in column major order like WGSL.
+        // The inner arrays of the Rust type have one element for each column.
+        let components: Vec<_> = columns.iter().flatten().collect();
+        let arrays = components.chunks(columns.len()).map(|c| quote!([#(#c),*]));
+        return Some(quote!([#(#arrays),*]));
+    }
+
+    let column_values: Vec<_> = columns
+        .iter()
+        .map(|c| vector_value(scalar, c, format))
+        .collect();
+    match format {
+        MatrixVectorTypes::Rust { .. } => None,
+        MatrixVectorTypes::Glam | MatrixVectorTypes::Ultraviolet => {
+            let constructor = match format {
+                MatrixVectorTypes::Glam => quote!(from_cols),
+                _ => quote!(new),
+            };
+            Some(quote!(#ty::#constructor(#(#column_values),*)))
+        }
+        MatrixVectorTypes::Nalgebra => Some(quote!(
+            nalgebra::SMatrix::from_array_storage(nalgebra::ArrayStorage([#([#(#columns),*]),*]))
+        )),
+        MatrixVectorTypes::Mint => {
+            let name = mint_matrix_name(rows, size);
+            let fields = vector_fields(columns.len());
+            Some(quote!(mint::#name { #(#fields: #column_values),* }))
+        }
+        MatrixVectorTypes::Cgmath => {
+            // cgmath only has a const constructor from column major components.
+            let name = Ident::new(&format!("Matrix{}", size as u8), Span::call_site());
+            let components = columns.iter().flatten();
+            Some(quote!(cgmath::#name::new(#(#components),*)))
+        }
+    }
+}
+
+fn vector_size(len: usize) -> naga::VectorSize {
+    match len {
+        2 => naga::VectorSize::Bi,
+        3 => naga::VectorSize::Tri,
+        _ => naga::VectorSize::Quad,
+    }
+}
+
+fn vector_fields(len: usize) -> impl Iterator<Item = Ident> {
+    ["x", "y", "z", "w"]
+        .into_iter()
+        .take(len)
+        .map(|f| Ident::new(f, Span::call_site()))
+}
+
+fn is_array_type(ty: &TokenStream) -> bool {
+    matches!(syn::parse2(ty.clone()), Ok(syn::Type::Array(_)))
+}
+
+/// Returns `None` for types without a matching wgpu vertex format.
+pub fn vertex_format(ty: &naga::Type) -> Option<wgpu::VertexFormat> {
     // Not all wgsl types work as vertex attributes in wgpu.
-    match &ty.inner {
+    let format = match &ty.inner {
         naga::TypeInner::Scalar(scalar) => match (scalar.kind, scalar.width) {
             (naga::ScalarKind::Sint, 4) => wgpu::VertexFormat::Sint32,
             (naga::ScalarKind::Uint, 4) => wgpu::VertexFormat::Uint32,
             (naga::ScalarKind::Float, 4) => wgpu::VertexFormat::Float32,
             (naga::ScalarKind::Float, 8) => wgpu::VertexFormat::Float64,
-            _ => todo!(),
+            _ => return None,
         },
         naga::TypeInner::Vector { size, scalar } => match size {
             naga::VectorSize::Bi => match (scalar.kind, scalar.width) {
@@ -205,16 +685,17 @@ pub fn vertex_format(ty: &naga::Type) -> wgpu::VertexFormat {
                 (naga::ScalarKind::Uint, 2) => wgpu::VertexFormat::Uint16x2,
                 (naga::ScalarKind::Uint, 4) => wgpu::VertexFormat::Uint32x2,
                 (naga::ScalarKind::Sint, 4) => wgpu::VertexFormat::Sint32x2,
+                (naga::ScalarKind::Float, 2) => wgpu::VertexFormat::Float16x2,
                 (naga::ScalarKind::Float, 4) => wgpu::VertexFormat::Float32x2,
                 (naga::ScalarKind::Float, 8) => wgpu::VertexFormat::Float64x2,
-                _ => todo!(),
+                _ => return None,
             },
             naga::VectorSize::Tri => match (scalar.kind, scalar.width) {
                 (naga::ScalarKind::Uint, 4) => wgpu::VertexFormat::Uint32x3,
                 (naga::ScalarKind::Sint, 4) => wgpu::VertexFormat::Sint32x3,
                 (naga::ScalarKind::Float, 4) => wgpu::VertexFormat::Float32x3,
                 (naga::ScalarKind::Float, 8) => wgpu::VertexFormat::Float64x3,
-                _ => todo!(),
+                _ => return None,
             },
             naga::VectorSize::Quad => match (scalar.kind, scalar.width) {
                 (naga::ScalarKind::Sint, 1) => wgpu::VertexFormat::Sint8x4,
@@ -223,20 +704,23 @@ pub fn vertex_format(ty: &naga::Type) -> wgpu::VertexFormat {
                 (naga::ScalarKind::Uint, 2) => wgpu::VertexFormat::Uint16x4,
                 (naga::ScalarKind::Uint, 4) => wgpu::VertexFormat::Uint32x4,
                 (naga::ScalarKind::Sint, 4) => wgpu::VertexFormat::Sint32x4,
+                (naga::ScalarKind::Float, 2) => wgpu::VertexFormat::Float16x4,
                 (naga::ScalarKind::Float, 4) => wgpu::VertexFormat::Float32x4,
                 (naga::ScalarKind::Float, 8) => wgpu::VertexFormat::Float64x4,
-                _ => todo!(),
+                _ => return None,
             },
         },
-        _ => todo!(), // are these types even valid as attributes?
-    }
+        _ => return None,
+    };
+    Some(format)
 }
 
 #[derive(PartialEq, Eq)]
 pub struct VertexInput {
     pub name: String,
     pub type_name: Ident,
-    pub fields: Vec<(u32, StructMember)>,
+    /// The location, Rust field name, and member for each member with a location.
+    pub fields: Vec<(u32, String, StructMember)>,
 }
 
 // TODO: Handle errors.
@@ -271,17 +755,18 @@ pub fn vertex_entry_structs(
                 naga::TypeInner::Struct { members, span: _ } => {
                     let input = VertexInput {
                         name: argument.name.as_ref().unwrap().clone(),
-                        type_name: Ident::new(arg_type.name.as_ref().unwrap(), Span::call_site()),
+                        type_name: Ident::new(&struct_name(module, arg_type), Span::call_site()),
                         fields: members
                             .iter()
-                            .filter_map(|member| {
+                            .enumerate()
+                            .filter_map(|(i, member)| {
                                 // Skip builtins since they have no location binding.
                                 let location = match member.binding.as_ref().unwrap() {
                                     naga::Binding::BuiltIn(_) => None,
                                     naga::Binding::Location { location, .. } => Some(*location),
                                 }?;
 
-                                Some((location, member.clone()))
+                                Some((location, member_name(member, i), member.clone()))
                             })
                             .collect(),
                     };
@@ -302,9 +787,21 @@ pub fn vertex_entry_structs(
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::assert_tokens_eq;
     use indoc::indoc;
     use pretty_assertions::assert_eq;
 
+    fn f16_type(inner: fn(naga::Scalar) -> naga::TypeInner) -> naga::Type {
+        // The WGSL frontend doesn't parse f16 yet, so create the types directly.
+        naga::Type {
+            name: None,
+            inner: inner(naga::Scalar {
+                kind: naga::ScalarKind::Float,
+                width: 2,
+            }),
+        }
+    }
+
     #[test]
     fn shader_stages_none() {
         let source = "";
@@ -377,4 +874,227 @@ mod tests {
         let module = naga::front::wgsl::parse_str(source).unwrap();
         assert_eq!(wgpu::ShaderStages::all(), shader_stages(&module));
     }
+
+    #[test]
+    fn rust_type_f16() {
+        let module = naga::Module::default();
+        let scalar = f16_type(naga::TypeInner::Scalar);
+        let vector = f16_type(|scalar| naga::TypeInner::Vector {
+            size: naga::VectorSize::Quad,
+            scalar,
+        });
+        let matrix = f16_type(|scalar| naga::TypeInner::Matrix {
+            columns: naga::VectorSize::Bi,
+            rows: naga::VectorSize::Bi,
+            scalar,
+        });
+
+        let format = MatrixVectorTypes::Rust { ordered: false };
+        assert_tokens_eq!(quote!(half::f16), rust_type(&module, &scalar, format));
+        assert_tokens_eq!(quote!([half::f16; 4]), rust_type(&module, &vector, format));
+        assert_tokens_eq!(
+            quote!([[half::f16; 2]; 2]),
+            rust_type(&module, &matrix, format)
+        );
+
+        // Math libraries without f16 support should use Rust types.
+        let format = MatrixVectorTypes::Glam;
+        assert_tokens_eq!(quote!([half::f16; 4]), rust_type(&module, &vector, format));
+        assert_tokens_eq!(
+            quote!([[half::f16; 2]; 2]),
+            rust_type(&module, &matrix, format)
+        );
+    }
+
+    #[test]
+    fn vertex_format_f16() {
+        let vec2 = f16_type(|scalar| naga::TypeInner::Vector {
+            size: naga::VectorSize::Bi,
+            scalar,
+        });
+        let vec4 = f16_type(|scalar| naga::TypeInner::Vector {
+            size: naga::VectorSize::Quad,
+            scalar,
+        });
+        assert_eq!(Some(wgpu::VertexFormat::Float16x2), vertex_format(&vec2));
+        assert_eq!(Some(wgpu::VertexFormat::Float16x4), vertex_format(&vec4));
+
+        // wgpu has no vertex formats for vec3<f16> or f16.
+        let vec3 = f16_type(|scalar| naga::TypeInner::Vector {
+            size: naga::VectorSize::Tri,
+            scalar,
+        });
+        assert_eq!(None, vertex_format(&vec3));
+        assert_eq!(None, vertex_format(&f16_type(naga::TypeInner::Scalar)));
+    }
+
+    #[test]
+    fn required_features_none() {
+        let source = indoc! {r#"
+            @group(0) @binding(0) var<uniform> a: vec4<f32>;
+
+            @fragment
+            fn main()  {}
+        "#};
+
+        let module = naga::front::wgsl::parse_str(source).unwrap();
+        assert_eq!(wgpu::Features::empty(), required_features(&module));
+    }
+
+    #[test]
+    fn required_features_f16() {
+        let mut module = naga::Module::default();
+        module.types.insert(
+            f16_type(|scalar| naga::TypeInner::Vector {
+                size: naga::VectorSize::Quad,
+                scalar,
+            }),
+            naga::Span::UNDEFINED,
+        );
+        assert_eq!(wgpu::Features::SHADER_F16, required_features(&module));
+    }
+
+    #[test]
+    fn required_features_dual_source_blending() {
+        let source = indoc! {r#"
+            struct FragmentOutput {
+                @location(0) color: vec4<f32>,
+                @location(0) @second_blend_source blend: vec4<f32>,
+            }
+
+            @fragment
+            fn main() -> FragmentOutput {
+                return FragmentOutput(vec4(0.0), vec4(1.0));
+            }
+        "#};
+
+        let module = naga::front::wgsl::parse_str(source).unwrap();
+        assert_eq!(
+            wgpu::Features::DUAL_SOURCE_BLENDING,
+            required_features(&module)
+        );
+    }
+
+    #[test]
+    fn required_features_64bit_push_constants_binding_arrays() {
+        let source = indoc! {r#"
+            struct PushConstants {
+                a: f64,
+                b: u64,
+            }
+
+            var<push_constant> constants: PushConstants;
+            @group(0) @binding(0) var textures: binding_array<texture_2d<f32>, 2>;
+            @group(0) @binding(1) var<storage, read> buffers: binding_array<array<f32>, 2>;
+
+            @fragment
+            fn main(@builtin(primitive_index) index: u32) {}
+        "#};
+
+        let module = naga::front::wgsl::parse_str(source).unwrap();
+        assert_eq!(
+            wgpu::Features::SHADER_F64
+                | wgpu::Features::SHADER_INT64
+                | wgpu::Features::PUSH_CONSTANTS
+                | wgpu::Features::TEXTURE_BINDING_ARRAY
+                | wgpu::Features::BUFFER_BINDING_ARRAY
+                | wgpu::Features::STORAGE_RESOURCE_BINDING_ARRAY
+                | wgpu::Features::SHADER_PRIMITIVE_INDEX,
+            required_features(&module)
+        );
+    }
+
+    fn validate(module: &naga::Module) -> naga::valid::ModuleInfo {
+        naga::valid::Validator::new(
+            naga::valid::ValidationFlags::empty(),
+            naga::valid::Capabilities::all(),
+        )
+        .validate(module)
+        .unwrap()
+    }
+
+    #[test]
+    fn required_limits_empty() {
+        let source = indoc! {r#"
+            @fragment
+            fn main() {}
+        "#};
+
+        let module = naga::front::wgsl::parse_str(source).unwrap();
+        assert!(required_limits(&module, &validate(&module)).is_empty());
+    }
+
+    #[test]
+    fn required_limits_render() {
+        let source = indoc! {r#"
+            struct Uniforms {
+                color: vec4<f32>,
+            }
+
+            struct FragmentOutput {
+                @location(0) color: vec4<f32>,
+                @location(2) normal: vec4<f32>,
+            }
+
+            var<push_constant> constants: vec4<f32>;
+            @group(0) @binding(0) var<uniform> uniforms: Uniforms;
+            @group(0) @binding(2) var color_texture: texture_2d<f32>;
+            @group(1) @binding(0) var textures: binding_array<texture_2d<f32>, 4>;
+            @group(1) @binding(1) var color_sampler: sampler;
+            @group(1) @binding(2) var<storage, read> values: array<vec2<f32>>;
+
+            @fragment
+            fn main() -> FragmentOutput {
+                return FragmentOutput(vec4(0.0), vec4(0.0));
+            }
+        "#};
+
+        let module = naga::front::wgsl::parse_str(source).unwrap();
+        assert_eq!(
+            vec![
+                ("max_push_constant_size", 16),
+                ("max_bind_groups", 2),
+                ("max_bindings_per_bind_group", 3),
+                ("max_uniform_buffers_per_shader_stage", 1),
+                ("max_storage_buffers_per_shader_stage", 1),
+                ("max_sampled_textures_per_shader_stage", 5),
+                ("max_samplers_per_shader_stage", 1),
+                ("max_uniform_buffer_binding_size", 16),
+                ("max_storage_buffer_binding_size", 8),
+                ("max_color_attachments", 3),
+            ],
+            required_limits(&module, &validate(&module))
+        );
+    }
+
+    #[test]
+    fn required_limits_compute() {
+        let source = indoc! {r#"
+            var<workgroup> a: array<f32, 64>;
+            var<workgroup> b: u32;
+            var<workgroup> c: array<vec4<f32>, 64>;
+
+            @compute @workgroup_size(64, 2, 1)
+            fn main1() {
+                a[0] = f32(b);
+            }
+
+            @compute @workgroup_size(8, 8, 4)
+            fn main2() {
+                c[0] = vec4(0.0);
+            }
+        "#};
+
+        let module = naga::front::wgsl::parse_str(source).unwrap();
+        assert_eq!(
+            vec![
+                ("max_compute_workgroup_storage_size", 1024),
+                ("max_compute_invocations_per_workgroup", 256),
+                ("max_compute_workgroup_size_x", 64),
+                ("max_compute_workgroup_size_y", 8),
+                ("max_compute_workgroup_size_z", 4),
+            ],
+            required_limits(&module, &validate(&module))
+        );
+    }
 }
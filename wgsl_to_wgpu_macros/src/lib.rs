@@ -0,0 +1,421 @@
+//! # wgsl_to_wgpu_macros
+//! Procedural macros for generating typesafe Rust bindings from WGSL shaders
+//! with [wgsl_to_wgpu](https://docs.rs/wgsl_to_wgpu) without a build script.
+//!
+//! Use [include_bindings!] for WGSL files and [wgsl!] for WGSL source written inline.
+//!
+//! The generated code is the same as the output of `wgsl_to_wgpu::create_shader_module`
+//! and requires the same dependencies like wgpu and bon.
+use std::path::{Path, PathBuf};
+
+use proc_macro2::{Span, TokenStream};
+use quote::quote;
+use syn::{
+    parse::{Parse, ParseStream},
+    punctuated::Punctuated,
+    spanned::Spanned,
+    Expr, Ident, Lit, LitStr, Token,
+};
+use wgsl_to_wgpu::{
+    create_shader_module_composed_tokens, create_shader_module_source_tokens, ComposedSource,
+    MatrixVectorTypes, ShaderSourceMode, WriteOptions,
+};
+
+/// Generate the bindings for a WGSL file at compile time.
+///
+/// The path is relative to the directory containing the `Cargo.toml` of the crate.
+/// The crate is recompiled when the shader or any file it includes changes.
+/// Errors are reported as compile errors with the WGSL file, line, and column.
+///
+/// The optional arguments set the fields of `WriteOptions` with the same names:
+/// - `derive_bytemuck_vertex`, `derive_bytemuck_host_shareable`, `derive_encase_host_shareable`,
+///   `derive_serde`, and `constant_macros` as `true` or `false`
+/// - `matrix_vector_types` as `"rust"`, `"rust-ordered"`, `"glam"`, `"nalgebra"`, `"mint"`,
+///   `"cgmath"`, or `"ultraviolet"`
+/// - `source_mode` as `"normalized"` or `"verbatim"`
+/// - `translations` as a list like `["spirv", "glsl"]`
+/// - `defines` and `type_overrides` as a list of string pairs
+/// - `dynamic_bindings` as a list of strings
+///
+/// `include_dirs` sets the additional directories to search for `#include` files.
+///
+/// # Examples
+/// ```ignore
+/// mod shader {
+///     wgsl_to_wgpu_macros::include_bindings!(
+///         "src/shader.wgsl",
+///         derive_bytemuck_vertex = true,
+///         matrix_vector_types = "glam",
+///         defines = [("SKINNING", "1")],
+///         include_dirs = ["src/shaders/common"],
+///     );
+/// }
+/// ```
+#[proc_macro]
+pub fn include_bindings(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    let input = syn::parse_macro_input!(input as Input);
+    include_bindings_tokens(&input, &manifest_dir())
+        .unwrap_or_else(|e| e.to_compile_error())
+        .into()
+}
+
+/// Generate the bindings for WGSL source in a string literal at compile time.
+///
+/// This is intended for small shaders like blits or mipmap generation
+/// that are only used by the surrounding Rust code.
+/// Errors are reported as compile errors with the line and column in the WGSL source.
+///
+/// The optional arguments are the same as [include_bindings!] except for `include_dirs`
+/// since `#include` directives are not supported for inline source.
+///
+/// # Examples
+/// ```ignore
+/// mod blit {
+///     wgsl_to_wgpu_macros::wgsl!(
+///         r#"
+///         @group(0) @binding(0) var color_texture: texture_2d<f32>;
+///
+///         @vertex
+///         fn vs_main(@builtin(vertex_index) index: u32) -> @builtin(position) vec4<f32> {
+///             let uv = vec2(f32((index << 1u) & 2u), f32(index & 2u));
+///             return vec4(uv * 2.0 - 1.0, 0.0, 1.0);
+///         }
+///
+///         @fragment
+///         fn fs_main(@builtin(position) position: vec4<f32>) -> @location(0) vec4<f32> {
+///             return textureLoad(color_texture, vec2<i32>(position.xy), 0);
+///         }
+///         "#,
+///         matrix_vector_types = "glam",
+///     );
+/// }
+/// ```
+#[proc_macro]
+pub fn wgsl(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    let input = syn::parse_macro_input!(input as Input);
+    wgsl_tokens(&input)
+        .unwrap_or_else(|e| e.to_compile_error())
+        .into()
+}
+
+struct Input {
+    path: LitStr,
+    args: Punctuated<Arg, Token![,]>,
+}
+
+struct Arg {
+    name: Ident,
+    value: Expr,
+}
+
+impl Parse for Input {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let path = input.parse()?;
+        let args = if input.is_empty() {
+            Punctuated::new()
+        } else {
+            input.parse::<Token![,]>()?;
+            Punctuated::parse_terminated(input)?
+        };
+        Ok(Self { path, args })
+    }
+}
+
+impl Parse for Arg {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let name = input.parse()?;
+        input.parse::<Token![=]>()?;
+        let value = input.parse()?;
+        Ok(Self { name, value })
+    }
+}
+
+fn manifest_dir() -> PathBuf {
+    std::env::var_os("CARGO_MANIFEST_DIR")
+        .map(PathBuf::from)
+        .unwrap_or_default()
+}
+
+fn include_bindings_tokens(input: &Input, base_dir: &Path) -> syn::Result<TokenStream> {
+    let (options, search_paths) = write_options(input, Some(base_dir))?;
+
+    let path = base_dir.join(input.path.value());
+    let error = |e: wgsl_to_wgpu::CreateModuleError| syn::Error::new(input.path.span(), e);
+
+    let composed = ComposedSource::new(&path, &search_paths, &options.defines).map_err(error)?;
+    let bindings = create_shader_module_composed_tokens(&composed, options).map_err(error)?;
+
+    // Depending on each file with include_str tracks changes on stable Rust.
+    let files = composed
+        .files
+        .iter()
+        .map(|f| f.to_string_lossy().into_owned());
+    Ok(quote! {
+        #(const _: &str = include_str!(#files);)*
+        #bindings
+    })
+}
+
+fn wgsl_tokens(input: &Input) -> syn::Result<TokenStream> {
+    let (options, _) = write_options(input, None)?;
+    create_shader_module_source_tokens(&input.path.value(), options)
+        .map_err(|e| syn::Error::new(input.path.span(), e))
+}
+
+/// Parse the arguments after the path or source literal.
+/// `include_dirs` is only supported for files with a `base_dir`.
+fn write_options(
+    input: &Input,
+    base_dir: Option<&Path>,
+) -> syn::Result<(WriteOptions, Vec<PathBuf>)> {
+    let mut options = WriteOptions::default();
+    let mut search_paths = Vec::new();
+    for arg in &input.args {
+        let value = &arg.value;
+        match arg.name.to_string().as_str() {
+            "derive_bytemuck_vertex" => options.derive_bytemuck_vertex = bool_value(value)?,
+            "derive_bytemuck_host_shareable" => {
+                options.derive_bytemuck_host_shareable = bool_value(value)?
+            }
+            "derive_encase_host_shareable" => {
+                options.derive_encase_host_shareable = bool_value(value)?
+            }
+            "derive_serde" => options.derive_serde = bool_value(value)?,
+            "constant_macros" => options.constant_macros = bool_value(value)?,
+            "matrix_vector_types" => {
+                options.matrix_vector_types = match str_value(value)?.as_str() {
+                    "rust" => MatrixVectorTypes::Rust { ordered: false },
+                    "rust-ordered" => MatrixVectorTypes::Rust { ordered: true },
+                    "glam" => MatrixVectorTypes::Glam,
+                    "nalgebra" => MatrixVectorTypes::Nalgebra,
+                    "mint" => MatrixVectorTypes::Mint,
+                    "cgmath" => MatrixVectorTypes::Cgmath,
+                    "ultraviolet" => MatrixVectorTypes::Ultraviolet,
+                    _ => {
+                        return Err(syn::Error::new(
+                            value.span(),
+                            "unrecognized matrix vector types",
+                        ))
+                    }
+                }
+            }
+            "source_mode" => {
+                options.source_mode = match str_value(value)?.as_str() {
+                    "normalized" => ShaderSourceMode::Normalized,
+                    "verbatim" => ShaderSourceMode::Verbatim,
+                    _ => return Err(syn::Error::new(value.span(), "unrecognized source mode")),
+                }
+            }
+            "translations" => {
+                for (language, span) in list_value(value, str_value)? {
+                    match language.as_str() {
+                        "spirv" => options.translations.spirv = true,
+                        "glsl" => options.translations.glsl = true,
+                        _ => return Err(syn::Error::new(span, "unrecognized language")),
+                    }
+                }
+            }
+            "defines" => options.defines = values(list_value(value, pair_value)?),
+            "type_overrides" => options.type_overrides = values(list_value(value, pair_value)?),
+            "dynamic_bindings" => options.dynamic_bindings = values(list_value(value, str_value)?),
+            "include_dirs" => {
+                let base_dir = base_dir.ok_or_else(|| {
+                    syn::Error::new(
+                        arg.name.span(),
+                        "includes are not supported for inline source",
+                    )
+                })?;
+                for (dir, _) in list_value(value, str_value)? {
+                    search_paths.push(base_dir.join(dir));
+                }
+            }
+            _ => return Err(syn::Error::new(arg.name.span(), "unrecognized option")),
+        }
+    }
+    Ok((options, search_paths))
+}
+
+fn bool_value(value: &Expr) -> syn::Result<bool> {
+    match value {
+        Expr::Lit(syn::ExprLit {
+            lit: Lit::Bool(b), ..
+        }) => Ok(b.value),
+        _ => Err(syn::Error::new(value.span(), "expected `true` or `false`")),
+    }
+}
+
+fn str_value(value: &Expr) -> syn::Result<String> {
+    match value {
+        Expr::Lit(syn::ExprLit {
+            lit: Lit::Str(s), ..
+        }) => Ok(s.value()),
+        _ => Err(syn::Error::new(value.span(), "expected a string literal")),
+    }
+}
+
+fn pair_value(value: &Expr) -> syn::Result<(String, String)> {
+    match value {
+        Expr::Tuple(t) if t.elems.len() == 2 => {
+            Ok((str_value(&t.elems[0])?, str_value(&t.elems[1])?))
+        }
+        _ => Err(syn::Error::new(
+            value.span(),
+            "expected a pair of string literals",
+        )),
+    }
+}
+
+fn list_value<T>(
+    value: &Expr,
+    element: impl Fn(&Expr) -> syn::Result<T>,
+) -> syn::Result<Vec<(T, Span)>> {
+    match value {
+        Expr::Array(a) => a
+            .elems
+            .iter()
+            .map(|e| Ok((element(e)?, e.span())))
+            .collect(),
+        _ => Err(syn::Error::new(value.span(), "expected a list")),
+    }
+}
+
+fn values<T>(list: Vec<(T, Span)>) -> Vec<T> {
+    list.into_iter().map(|(v, _)| v).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn bindings(input: TokenStream) -> syn::Result<TokenStream> {
+        let base_dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("../wgsl_to_wgpu/src/data");
+        include_bindings_tokens(&syn::parse2(input).unwrap(), &base_dir)
+    }
+
+    fn error_message(input: TokenStream) -> String {
+        bindings(input).unwrap_err().to_string()
+    }
+
+    #[test]
+    fn include_bindings_options() {
+        let tokens = bindings(quote! {
+            "compose/main.wgsl",
+            derive_bytemuck_vertex = true,
+            matrix_vector_types = "glam",
+            source_mode = "verbatim",
+            defines = [("LIGHTING", "1")],
+            include_dirs = ["compose/shared"],
+        })
+        .unwrap();
+        let file: syn::File = syn::parse2(tokens).unwrap();
+
+        // Each composed file is tracked for changes.
+        let tracked: Vec<_> = file
+            .items
+            .iter()
+            .filter_map(|item| match item {
+                syn::Item::Const(c) if c.ident == "_" => Some(quote!(#c).to_string()),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(3, tracked.len());
+        assert!(tracked[0].contains("main.wgsl"));
+        let structs: Vec<_> = file
+            .items
+            .iter()
+            .filter_map(|item| match item {
+                syn::Item::Struct(s) => Some(s.ident.to_string()),
+                _ => None,
+            })
+            .collect();
+        assert!(structs.contains(&"Camera".to_owned()));
+        assert!(structs.contains(&"BindGroup0".to_owned()));
+    }
+
+    #[test]
+    fn include_bindings_errors() {
+        assert_eq!(
+            "unrecognized option",
+            error_message(quote!("compose/main.wgsl", derive_bytemuck = true))
+        );
+        assert_eq!(
+            "expected `true` or `false`",
+            error_message(quote!("compose/main.wgsl", derive_serde = "yes"))
+        );
+        assert_eq!(
+            "unrecognized matrix vector types",
+            error_message(quote!("compose/main.wgsl", matrix_vector_types = "glm"))
+        );
+        assert_eq!(
+            "expected a pair of string literals",
+            error_message(quote!("compose/main.wgsl", defines = ["SKINNING"]))
+        );
+    }
+
+    #[test]
+    fn include_bindings_shader_errors() {
+        let message = error_message(quote!("compose/invalid.wgsl"));
+        assert!(
+            message.starts_with("failed to parse the shader: "),
+            "{message}"
+        );
+        assert!(message.contains("invalid.wgsl:"), "{message}");
+
+        let message = error_message(quote!("compose/missing_file.wgsl"));
+        assert!(message.starts_with("failed to read "), "{message}");
+    }
+
+    fn inline_error_message(input: TokenStream) -> String {
+        wgsl_tokens(&syn::parse2(input).unwrap())
+            .unwrap_err()
+            .to_string()
+    }
+
+    #[test]
+    fn wgsl_options() {
+        let tokens = wgsl_tokens(
+            &syn::parse2(quote! {
+                r#"
+                struct Uniforms {
+                    color: vec4<f32>,
+                }
+                @group(0) @binding(0) var<uniform> uniforms: Uniforms;
+
+                @fragment
+                fn fs_main() -> @location(0) vec4<f32> {
+                #ifdef RED
+                    return vec4(1.0, 0.0, 0.0, 1.0);
+                #else
+                    return uniforms.color;
+                #endif
+                }
+                "#,
+                matrix_vector_types = "glam",
+                source_mode = "verbatim",
+                defines = [("RED", "1")],
+            })
+            .unwrap(),
+        )
+        .unwrap();
+        let text = tokens.to_string();
+        assert!(text.contains("pub struct Uniforms"), "{text}");
+        assert!(text.contains("glam :: Vec4"), "{text}");
+        assert!(text.contains("pub struct BindGroup0"), "{text}");
+        assert!(text.contains("return vec4(1.0, 0.0, 0.0, 1.0);"), "{text}");
+    }
+
+    #[test]
+    fn wgsl_errors() {
+        assert_eq!(
+            "includes are not supported for inline source",
+            inline_error_message(quote!("", include_dirs = ["shaders"]))
+        );
+
+        let message = inline_error_message(quote!("@fragment\nfn main( {}"));
+        assert!(
+            message.starts_with("failed to parse the shader: "),
+            "{message}"
+        );
+        assert!(message.contains("wgsl:2:10"), "{message}");
+    }
+}